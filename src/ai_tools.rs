@@ -1,189 +1,553 @@
 use anyhow::{Context, Result};
-use std::process::{Command, Stdio};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 use std::env;
 use colored::Colorize;
 
+use crate::claude_status::StatusProfile;
+use crate::executables::find_executable;
+use crate::output::{self, coded, say, ErrorCode};
+use crate::state::{WorktreeInfo, XlaudeState};
+
+/// How a tool is told which worktree to work in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PathMode {
+    /// Started with the worktree as working directory
+    #[default]
+    Cwd,
+    /// Also given the worktree path as last argument, like editors expect
+    Argument,
+}
+
+/// Named permission settings of a tool, such as `yolo`, `ask` or `readonly`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionProfile {
+    /// Arguments passed after the tool's default arguments
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Extra environment variables, e.g. pointing to a settings file
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+}
+
 /// Represents an AI coding CLI tool
+#[derive(Debug, Clone)]
 pub struct AiTool {
+    /// Key of the tool in the `tools` configuration
+    pub id: String,
     /// Name of the AI tool
     pub name: String,
     /// Command to execute
     pub command: String,
     /// Default CLI arguments
     pub default_args: Vec<String>,
+    /// Extra environment variables for the tool
+    pub env: BTreeMap<String, String>,
     /// Environment variable to override the command
     pub env_var: String,
+    pub path_mode: PathMode,
+    /// Command that succeeds when the tool is installed, instead of looking
+    /// up `command` in PATH
+    pub detect: Option<Vec<String>>,
+    /// Flag that continues the most recent conversation, if supported
+    pub continue_flag: Option<String>,
+    /// Flag that takes the id of a conversation to resume, if supported
+    pub resume_flag: Option<String>,
+    /// Arguments passing an initial prompt, with `{prompt}` standing for it
+    pub prompt_args: Option<Vec<String>>,
+    /// Arguments that run a prompt without interaction and print the answer
+    pub headless_args: Option<Vec<String>>,
+    /// Permission profiles by name
+    pub profiles: BTreeMap<String, PermissionProfile>,
+    /// Permission profile the tool is started with, if any
+    pub profile: Option<String>,
+    /// Session provider reading the tool's history, see `session_provider::provider`
+    pub sessions: Option<String>,
+    /// How the tool's terminal output is read for its status
+    pub status: StatusProfile,
+    /// Tools are tried from the lowest priority up
+    pub priority: i32,
+    pub disabled: bool,
+}
+
+/// Entry of the `tools` configuration, defining a new tool or overriding
+/// fields of a built-in one with the same id
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detect: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub continue_flag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resume_flag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_args: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headless_args: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, PermissionProfile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sessions: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<StatusProfile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disabled: Option<bool>,
+}
+
+/// Which earlier conversation to pick up when launching a tool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionResume {
+    /// The most recent conversation in the worktree
+    Continue,
+    /// A conversation by id, or a selection from the known sessions
+    Resume(Option<String>),
 }
 
-/// Available AI coding CLI tools in order of preference
-pub enum AiToolType {
-    OpenCode,
-    QwenCode,
-    Zed,  // Zed IDE with Gemini CLI integration
-    Claude,
-}
-
-impl AiToolType {
-    /// Get the corresponding AiTool configuration
-    pub fn get_config(&self) -> AiTool {
-        match self {
-            AiToolType::OpenCode => AiTool {
-                name: "OpenCode".to_string(),
-                command: "opencode".to_string(),
-                default_args: vec![], // OpenCode doesn't need special flags by default
-                env_var: "XLAUDE_OPENCODE_CMD".to_string(),
-            },
-            AiToolType::QwenCode => AiTool {
-                name: "Qwen Code".to_string(),
-                command: "qwen".to_string(),
-                default_args: vec![], // Qwen doesn't need special flags by default
-                env_var: "XLAUDE_QWEN_CMD".to_string(),
-            },
-            AiToolType::Zed => AiTool {
-                name: "Zed IDE".to_string(),
-                command: "zed".to_string(),
-                default_args: vec![], // Zed will use Gemini CLI integration automatically
-                env_var: "XLAUDE_ZED_CMD".to_string(),
-            },
-            AiToolType::Claude => AiTool {
-                name: "Claude".to_string(),
-                command: "claude".to_string(),
-                default_args: vec!["--dangerously-skip-permissions".to_string()],
-                env_var: "XLAUDE_CLAUDE_CMD".to_string(),
-            },
+impl AiTool {
+    /// A tool with nothing but a command, named after its id
+    fn new(id: &str) -> Self {
+        AiTool {
+            id: id.to_string(),
+            name: id.to_string(),
+            command: id.to_string(),
+            default_args: vec![],
+            env: BTreeMap::new(),
+            env_var: command_env_var(id),
+            path_mode: PathMode::Cwd,
+            detect: None,
+            continue_flag: None,
+            resume_flag: None,
+            prompt_args: None,
+            headless_args: None,
+            profiles: BTreeMap::new(),
+            profile: None,
+            sessions: None,
+            status: StatusProfile::Generic,
+            priority: 100,
+            disabled: false,
+        }
+    }
+
+    fn apply(&mut self, config: &ToolConfig) {
+        let config = config.clone();
+        self.name = config.name.unwrap_or(self.name.clone());
+        self.command = config.command.unwrap_or(self.command.clone());
+        self.default_args = config.args.unwrap_or(self.default_args.clone());
+        self.env.extend(config.env);
+        self.path_mode = config.path.unwrap_or(self.path_mode);
+        self.detect = config.detect.or(self.detect.take());
+        self.continue_flag = config.continue_flag.or(self.continue_flag.take());
+        self.resume_flag = config.resume_flag.or(self.resume_flag.take());
+        self.prompt_args = config.prompt_args.or(self.prompt_args.take());
+        self.headless_args = config.headless_args.or(self.headless_args.take());
+        self.profiles.extend(config.profiles);
+        self.profile = config.profile.or(self.profile.take());
+        self.sessions = config.sessions.or(self.sessions.take());
+        self.status = config.status.unwrap_or(self.status);
+        self.priority = config.priority.unwrap_or(self.priority);
+        self.disabled = config.disabled.unwrap_or(self.disabled);
+    }
+
+    /// Arguments that make the tool pick up an earlier conversation
+    pub fn resume_args(&self, resume: &SessionResume) -> Result<Vec<String>> {
+        let unsupported = || coded(
+            ErrorCode::InvalidState,
+            format!("{} cannot continue or resume sessions", self.name),
+        );
+        match resume {
+            SessionResume::Continue => {
+                let flag = self.continue_flag.as_ref().ok_or_else(unsupported)?;
+                Ok(vec![flag.clone()])
+            }
+            SessionResume::Resume(id) => {
+                let flag = self.resume_flag.as_ref().ok_or_else(unsupported)?;
+                let id = id.as_ref().ok_or_else(|| coded(
+                    ErrorCode::InputRequired,
+                    format!("{} needs a session id, pass --resume <id>", self.name),
+                ))?;
+                Ok(vec![flag.clone(), id.clone()])
+            }
+        }
+    }
+
+    /// Arguments that start the tool with `prompt` as its first message, if supported
+    pub fn prompt_args(&self, prompt: &str) -> Option<Vec<String>> {
+        self.prompt_args.as_deref().map(|args| with_prompt(args, prompt))
+    }
+
+    /// Arguments that run `prompt` to completion without a terminal, if supported
+    pub fn headless_args(&self, prompt: &str) -> Option<Vec<String>> {
+        self.headless_args.as_deref().map(|args| with_prompt(args, prompt))
+    }
+
+    /// The tool started with the permission profile `name`, or with its own
+    /// default profile if no name is given
+    pub fn with_profile(&self, name: Option<&str>) -> Result<AiTool> {
+        let Some(name) = name.or(self.profile.as_deref()) else {
+            return Ok(self.clone());
+        };
+        if !self.profiles.contains_key(name) {
+            let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            let known = if known.is_empty() { "none".to_string() } else { known.join(", ") };
+            return Err(coded(
+                ErrorCode::InvalidState,
+                format!("{} has no permission profile '{name}' (available: {known})", self.name),
+            )
+            .into());
+        }
+        Ok(AiTool {
+            profile: Some(name.to_string()),
+            ..self.clone()
+        })
+    }
+
+    /// Settings of the permission profile the tool is started with
+    fn active_profile(&self) -> Option<&PermissionProfile> {
+        self.profiles.get(self.profile.as_deref()?)
+    }
+
+    /// Environment variables the tool is started with
+    pub fn environment(&self) -> BTreeMap<String, String> {
+        let mut env = self.env.clone();
+        if let Some(profile) = self.active_profile() {
+            env.extend(profile.env.clone());
+        }
+        env
+    }
+
+    /// Program and arguments that launch the tool in the worktree at `path`
+    pub fn command_line(&self, extra_args: &[String], path: &Path) -> Vec<String> {
+        let mut line = vec![self.command.clone()];
+        line.extend(self.default_args.iter().cloned());
+        if let Some(profile) = self.active_profile() {
+            line.extend(profile.args.iter().cloned());
+        }
+        line.extend(extra_args.iter().cloned());
+        if self.path_mode == PathMode::Argument {
+            line.push(path.to_string_lossy().to_string());
+        }
+        line
+    }
+
+    /// Command set through the tool's environment variable, if any
+    pub fn command_override(&self) -> Option<String> {
+        env::var(&self.env_var).ok().filter(|cmd| !cmd.is_empty())
+    }
+
+    /// The tool with its command replaced through its environment variable
+    fn overridden(&self) -> Option<AiTool> {
+        let command = self.command_override()?;
+        say!("{} Using custom command from {}: {}", "🔧".blue(), self.env_var, command);
+        Some(AiTool {
+            command,
+            ..self.clone()
+        })
+    }
+
+    /// Whether the tool is installed, by its detection command or PATH lookup
+    pub fn is_installed(&self) -> bool {
+        match self.detect.as_deref() {
+            Some([program, args @ ..]) => Command::new(program)
+                .args(args)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok_and(|status| status.success()),
+            _ => is_command_available(&self.command),
         }
     }
 }
 
-/// Check if a command is available in the system PATH
-fn is_command_available(command: &str) -> bool {
-    let cmd_check = if cfg!(target_os = "windows") {
-        Command::new("where")
-            .arg(command)
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .status()
-    } else {
-        Command::new("which")
-            .arg(command)
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .status()
+/// Configured arguments with `{prompt}` replaced by the prompt
+fn with_prompt(args: &[String], prompt: &str) -> Vec<String> {
+    args.iter().map(|arg| arg.replace("{prompt}", prompt)).collect()
+}
+
+/// `XLAUDE_<ID>_CMD`, the variable overriding a tool's command
+fn command_env_var(id: &str) -> String {
+    let id: String = id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    format!("XLAUDE_{id}_CMD")
+}
+
+/// Tools xlaude knows without configuration, in order of preference
+fn builtin_tools() -> Vec<AiTool> {
+    let flags = |continue_flag: &str, resume_flag: &str| {
+        (Some(continue_flag.to_string()), Some(resume_flag.to_string()))
+    };
+    let profiles = |yolo: PermissionProfile, ask: PermissionProfile, readonly: PermissionProfile| {
+        BTreeMap::from([("yolo".to_string(), yolo), ("ask".to_string(), ask), ("readonly".to_string(), readonly)])
+    };
+    let args = |args: &[&str]| PermissionProfile {
+        args: args.iter().map(ToString::to_string).collect(),
+        env: BTreeMap::new(),
+    };
+    // OpenCode reads permission settings as JSON from the environment
+    let opencode_permission = |edit: &str, bash: &str| PermissionProfile {
+        args: vec![],
+        env: BTreeMap::from([(
+            "OPENCODE_PERMISSION".to_string(),
+            format!(r#"{{"edit":"{edit}","bash":"{bash}","webfetch":"allow"}}"#),
+        )]),
+    };
+
+    let (continue_flag, resume_flag) = flags("--continue", "--session");
+    let opencode = AiTool {
+        name: "OpenCode".to_string(),
+        continue_flag,
+        resume_flag,
+        prompt_args: Some(vec!["--prompt".to_string(), "{prompt}".to_string()]),
+        headless_args: Some(vec!["run".to_string(), "{prompt}".to_string()]),
+        profiles: profiles(
+            opencode_permission("allow", "allow"),
+            opencode_permission("ask", "ask"),
+            opencode_permission("deny", "deny"),
+        ),
+        sessions: Some("opencode".to_string()),
+        priority: 10,
+        ..AiTool::new("opencode")
+    };
+
+    let (continue_flag, resume_flag) = flags("--continue", "--resume");
+    let qwen = AiTool {
+        name: "Qwen Code".to_string(),
+        continue_flag,
+        resume_flag,
+        prompt_args: Some(vec!["--prompt-interactive".to_string(), "{prompt}".to_string()]),
+        headless_args: Some(vec!["--prompt".to_string(), "{prompt}".to_string()]),
+        profiles: profiles(
+            args(&["--approval-mode", "yolo"]),
+            args(&["--approval-mode", "default"]),
+            args(&["--approval-mode", "plan"]),
+        ),
+        sessions: Some("qwen".to_string()),
+        priority: 20,
+        ..AiTool::new("qwen")
+    };
+
+    // Zed IDE with Gemini CLI integration, opened on the worktree
+    let zed = AiTool {
+        name: "Zed IDE".to_string(),
+        path_mode: PathMode::Argument,
+        priority: 30,
+        ..AiTool::new("zed")
+    };
+
+    let (continue_flag, resume_flag) = flags("--continue", "--resume");
+    let claude = AiTool {
+        name: "Claude".to_string(),
+        continue_flag,
+        resume_flag,
+        prompt_args: Some(vec!["{prompt}".to_string()]),
+        headless_args: Some(vec!["--print".to_string(), "{prompt}".to_string()]),
+        profiles: profiles(
+            args(&["--dangerously-skip-permissions"]),
+            args(&["--permission-mode", "default"]),
+            args(&["--permission-mode", "plan"]),
+        ),
+        // Claude has always been started without permission prompts
+        profile: Some("yolo".to_string()),
+        sessions: Some("claude".to_string()),
+        status: StatusProfile::Claude,
+        priority: 40,
+        ..AiTool::new("claude")
     };
-    
-    match cmd_check {
-        Ok(status) => status.success(),
-        Err(_) => false,
+
+    vec![opencode, qwen, zed, claude]
+}
+
+/// Built-in tools merged with the `tools` configuration, including disabled
+/// ones, in order of preference
+pub fn all_tools(config: &HashMap<String, ToolConfig>) -> Vec<AiTool> {
+    let mut tools = builtin_tools();
+    for (id, tool_config) in config {
+        match tools.iter_mut().find(|tool| tool.id == *id) {
+            Some(tool) => tool.apply(tool_config),
+            None => {
+                let mut tool = AiTool::new(id);
+                tool.apply(tool_config);
+                tools.push(tool);
+            }
+        }
+    }
+    tools.sort_by(|a, b| a.priority.cmp(&b.priority).then_with(|| a.id.cmp(&b.id)));
+    tools
+}
+
+/// The enabled tool with `id`
+pub fn get_tool(config: &HashMap<String, ToolConfig>, id: &str) -> Result<AiTool> {
+    let tool = all_tools(config)
+        .into_iter()
+        .find(|tool| tool.id == id)
+        .context(coded(ErrorCode::NotFound, format!("Unknown AI tool '{id}'")))?;
+    if tool.disabled {
+        return Err(coded(
+            ErrorCode::InvalidState,
+            format!("AI tool '{id}' is disabled in the configuration"),
+        )
+        .into());
+    }
+    Ok(tool)
+}
+
+/// The enabled tool with `id`, ready to launch: with its command from its
+/// environment variable, or else installed
+pub fn resolve_tool(config: &HashMap<String, ToolConfig>, id: &str) -> Result<AiTool> {
+    let tool = get_tool(config, id)?;
+    if let Some(tool) = tool.overridden() {
+        return Ok(tool);
+    }
+    if !tool.is_installed() {
+        return Err(coded(
+            ErrorCode::NotFound,
+            format!("{} is not installed: {} not found", tool.name, tool.command),
+        )
+        .into());
     }
+    Ok(tool)
+}
+
+/// Tool the dashboard starts for worktrees without a preferred tool
+pub const DASHBOARD_TOOL: &str = "claude";
+
+/// Id of the tool to start for a worktree: the one it was last opened with,
+/// else its repository's `default_tool`
+pub fn preferred_tool(state: &XlaudeState, info: &WorktreeInfo) -> Option<String> {
+    info.tool.clone().or_else(|| {
+        state
+            .repos
+            .get(&info.repo_name)
+            .and_then(|repo| repo.default_tool.clone())
+    })
+}
+
+/// Permission profile chosen for a worktree: its own, else its repository's
+/// `default_profile`; tools fall back to their own default without one
+pub fn preferred_profile(state: &XlaudeState, info: &WorktreeInfo) -> Option<String> {
+    info.profile.clone().or_else(|| {
+        state
+            .repos
+            .get(&info.repo_name)
+            .and_then(|repo| repo.default_profile.clone())
+    })
+}
+
+/// Status profile of the tool with `id`, the generic one for unknown tools
+pub fn status_profile(config: &HashMap<String, ToolConfig>, id: &str) -> StatusProfile {
+    all_tools(config)
+        .into_iter()
+        .find(|tool| tool.id == id)
+        .map_or(StatusProfile::Generic, |tool| tool.status)
+}
+
+/// Status profile of the tool running in a worktree's dashboard session
+pub fn worktree_status_profile(state: &XlaudeState, info: &WorktreeInfo) -> StatusProfile {
+    let id = preferred_tool(state, info).unwrap_or_else(|| DASHBOARD_TOOL.to_string());
+    status_profile(&state.tools, &id)
+}
+
+/// Permission profile of the tool started in a worktree's dashboard session
+pub fn worktree_permission_profile(state: &XlaudeState, info: &WorktreeInfo) -> Option<String> {
+    preferred_profile(state, info).or_else(|| {
+        let id = preferred_tool(state, info).unwrap_or_else(|| DASHBOARD_TOOL.to_string());
+        all_tools(&state.tools).into_iter().find(|tool| tool.id == id)?.profile
+    })
+}
+
+/// Program names of all known AI tools, used to recognise running agents
+pub fn known_tool_commands(config: &HashMap<String, ToolConfig>) -> Vec<String> {
+    all_tools(config)
+        .iter()
+        .map(|tool| {
+            Path::new(&tool.command)
+                .file_name()
+                .map_or_else(|| tool.command.clone(), |name| name.to_string_lossy().to_string())
+        })
+        .collect()
+}
+
+/// Check if a command is available in the system PATH
+pub fn is_command_available(command: &str) -> bool {
+    find_executable(command).is_some()
 }
 
 /// Attempts to find the first available AI tool
-/// 
-/// Returns the first available tool based on priority:
+///
+/// Enabled tools are tried by priority, by default:
 /// 1. OpenCode
 /// 2. Qwen Code
 /// 3. Zed IDE (with Gemini CLI)
 /// 4. Claude
 ///
-/// If none are found, returns None
-pub fn find_available_tool() -> Option<AiTool> {
-    println!("{} Searching for available AI tools...", "🔍".cyan());
-    
+/// followed by tools from the `tools` configuration. If none are found,
+/// returns None
+pub fn find_available_tool(config: &HashMap<String, ToolConfig>) -> Option<AiTool> {
+    let mut tools: Vec<AiTool> = all_tools(config)
+        .into_iter()
+        .filter(|tool| !tool.disabled)
+        .collect();
+
     // Check if user prefers Gemini (Zed) first
     if env::var("XLAUDE_PREFER_GEMINI").is_ok() {
-        println!("{} XLAUDE_PREFER_GEMINI is set, prioritizing Zed IDE", "⚡".yellow());
-        let zed_tool = AiToolType::Zed.get_config();
-        
-        // Check if Zed is explicitly set via environment variable
-        if let Ok(custom_cmd) = env::var(&zed_tool.env_var) {
-            if !custom_cmd.is_empty() {
-                println!("{} Using custom Zed command from {}: {}", "🔧".blue(), zed_tool.env_var, custom_cmd);
-                return Some(AiTool {
-                    command: custom_cmd,
-                    ..zed_tool
-                });
-            }
-        }
-        
-        // Check if Zed is available
-        if is_command_available(&zed_tool.command) {
-            println!("{} Found Zed IDE: {}", "✅".green(), zed_tool.command);
-            return Some(zed_tool);
-        } else {
-            println!("{} Zed IDE not found: {}", "❌".red(), zed_tool.command);
-        }
-    } else {
-        println!("{} XLAUDE_PREFER_GEMINI not set, using default priority order", "📋".blue());
-    }
-    
-    // Default priority order of tools to try
-    let tools = [
-        AiToolType::OpenCode,
-        AiToolType::QwenCode, 
-        AiToolType::Zed,
-        AiToolType::Claude,
-    ];
-    
-    for tool_type in tools.iter() {
-        let tool = tool_type.get_config();
-        println!("{} Checking {}: {}", "🔎".cyan(), tool.name, tool.command);
-        
-        // Check if the tool is explicitly set via environment variable
-        if let Ok(custom_cmd) = env::var(&tool.env_var) {
-            if !custom_cmd.is_empty() {
-                println!("{} Using custom command from {}: {}", "🔧".blue(), tool.env_var, custom_cmd);
-                return Some(AiTool {
-                    command: custom_cmd,
-                    ..tool
-                });
-            }
-        }
-        
-        // Check if the default command is available
-        if is_command_available(&tool.command) {
-            println!("{} Found {}: {}", "✅".green(), tool.name, tool.command);
-            return Some(tool);
-        } else {
-            println!("{} {} not found: {}", "❌".red(), tool.name, tool.command);
+        say!("{} XLAUDE_PREFER_GEMINI is set, prioritizing Zed IDE", "⚡".yellow());
+        if let Some(index) = tools.iter().position(|tool| tool.id == "zed") {
+            let zed = tools.remove(index);
+            tools.insert(0, zed);
         }
     }
-    
-    println!("{} No AI tools found", "⚠️".yellow());
-    None
+
+    tools
+        .into_iter()
+        .find_map(|tool| tool.overridden().or_else(|| tool.is_installed().then_some(tool)))
 }
 
 
 /// Launch an AI tool with the specified command and arguments, optionally with a specific path
-pub fn launch_ai_tool_with_path(tool: &AiTool, stdin_mode: StdinMode, worktree_path: Option<std::path::PathBuf>) -> Result<()> {
-    let mut cmd = Command::new(&tool.command);
-    
-    // Add default arguments if using the standard command
-    if tool.command == tool_type_to_default_cmd(&tool.name) {
-        for arg in &tool.default_args {
-            cmd.arg(arg);
-        }
+///
+/// `extra_args` are passed after the tool's default arguments.
+pub fn launch_ai_tool_with_path(tool: &AiTool, stdin_mode: StdinMode, worktree_path: Option<PathBuf>, extra_args: &[String]) -> Result<()> {
+    let target_path = match worktree_path {
+        Some(path) => path,
+        // Fall back to current directory
+        None => env::current_dir().context("Failed to get current directory")?,
+    };
+    if tool.path_mode == PathMode::Argument {
+        say!("{} Opening {} at worktree path: {}", "📁".green(), tool.name, target_path.display());
     }
-    
-    // For Zed, pass the worktree path as an argument to ensure it opens the correct directory
-    if tool.name == "Zed IDE" {
-        let target_path = if let Some(path) = worktree_path {
-            // Use the provided worktree path
-            path
-        } else {
-            // Fall back to current directory
-            std::env::current_dir().context("Failed to get current directory")?
-        };
-        
-        let path_str = target_path.to_string_lossy();
-        println!("{} Opening Zed at worktree path: {}", "📁".green(), path_str);
-        
-        // Pass the worktree path as an argument to zed command
-        cmd.arg(&target_path);
-    }
-    
+
+    let line = tool.command_line(extra_args, &target_path);
+    let mut cmd = Command::new(&line[0]);
+    cmd.args(&line[1..]);
+
     // Inherit all environment variables
     cmd.envs(env::vars());
-    
+    cmd.envs(tool.environment());
+
     // Handle stdin based on mode
     match stdin_mode {
         StdinMode::Inherit => {
@@ -193,36 +557,117 @@ pub fn launch_ai_tool_with_path(tool: &AiTool, stdin_mode: StdinMode, worktree_p
             cmd.stdin(Stdio::null());
         },
     }
-    
+
     // Print the full command being executed
-    let cmd_str = format!("{} {}", 
-        tool.command, 
-        cmd.get_args().map(|os| os.to_string_lossy()).collect::<Vec<_>>().join(" ")
-    );
-    println!("{} Executing: {}", "🚀".yellow(), cmd_str);
-    
+    say!("{} Executing: {}", "🚀".yellow(), line.join(" "));
+
     let status = cmd.status()
         .with_context(|| format!("Failed to launch {}", tool.name))?;
-    
+
     if !status.success() {
         anyhow::bail!("{} exited with error", tool.name);
     }
-    
+
     Ok(())
 }
 
-/// Map a tool type name to its default command
-fn tool_type_to_default_cmd(name: &str) -> &str {
-    match name {
-        "OpenCode" => "opencode",
-        "Qwen Code" => "qwen",
-        "Zed IDE" => "zed",
-        "Claude" => "claude",
-        _ => "",
+/// How a headless run of a tool ended
+pub struct HeadlessRun {
+    /// Exit code, `None` when the tool was killed
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    /// Everything the tool printed on stdout
+    pub output: String,
+}
+
+/// Run a tool without a terminal, copying its output to `log` as well as to
+/// our own output, and kill it once `timeout` has passed
+pub fn run_ai_tool_headless(tool: &AiTool, path: &Path, args: &[String], log: &Path, timeout: Option<Duration>) -> Result<HeadlessRun> {
+    let line = tool.command_line(args, path);
+    say!("{} Executing: {}", "🚀".yellow(), line.join(" "));
+
+    let log = File::create(log).with_context(|| format!("Failed to create log file {}", log.display()))?;
+    let log = Arc::new(Mutex::new(log));
+    let mut command = Command::new(&line[0]);
+    command
+        .args(&line[1..])
+        .current_dir(path)
+        .envs(tool.environment())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // Lead a process group of its own so a timeout also stops what the tool started
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let mut child = command.spawn().with_context(|| format!("Failed to launch {}", tool.name))?;
+
+    // Echo what the tool prints like we print our own messages
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let stdout = child.stdout.take().map(|pipe| copy_output(pipe, log.clone(), !output::is_json(), Some(output.clone())));
+    let stderr = child.stderr.take().map(|pipe| copy_output(pipe, log.clone(), false, None));
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().context("Failed to wait for the AI tool")? {
+            break Some(status);
+        }
+        if timeout.is_some_and(|timeout| started.elapsed() >= timeout) {
+            kill_process_group(&mut child);
+            break None;
+        }
+        thread::sleep(Duration::from_millis(100));
+    };
+
+    // Programs the tool left running in the background may keep the pipes
+    // open forever, so stop copying once the tool is gone and had time to flush
+    let deadline = Instant::now() + OUTPUT_GRACE;
+    for handle in stdout.into_iter().chain(stderr) {
+        while !handle.is_finished() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        if handle.is_finished() {
+            let _ = handle.join();
+        }
     }
-    // Future Options: Work vs Play
-    // Work: Cursor + Claude, VSCode + OpenAI, openCode + Bedrock, Vertex AI
-    // Play: OpenCode, Qwen, Claude, Kimi K2, Gemini, GHT 4.5, DeepSeek, Kilo, Cline, Trae
+
+    let output = output.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(HeadlessRun {
+        exit_code: status.and_then(|status| status.code()),
+        timed_out: status.is_none(),
+        output: String::from_utf8_lossy(&output).to_string(),
+    })
+}
+
+/// How long output of a finished tool is still copied
+const OUTPUT_GRACE: Duration = Duration::from_secs(2);
+
+/// Kill a headless tool together with the processes it started
+fn kill_process_group(child: &mut Child) {
+    // The tool leads its own process group, whose id is its pid
+    #[cfg(unix)]
+    let _ = Command::new("kill")
+        .args(["-KILL", "--", &format!("-{}", child.id())])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Copy a pipe line by line to the log and to stdout or stderr, keeping a copy in `capture`
+fn copy_output(pipe: impl Read + Send + 'static, log: Arc<Mutex<File>>, to_stdout: bool, capture: Option<Arc<Mutex<Vec<u8>>>>) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(pipe);
+        let mut line = Vec::new();
+        while reader.read_until(b'\n', &mut line).is_ok_and(|read| read > 0) {
+            let _ = log.lock().unwrap_or_else(|e| e.into_inner()).write_all(&line);
+            let _ = if to_stdout { io::stdout().write_all(&line) } else { io::stderr().write_all(&line) };
+            if let Some(capture) = &capture {
+                capture.lock().unwrap_or_else(|e| e.into_inner()).extend_from_slice(&line);
+            }
+            line.clear();
+        }
+    })
 }
 
 /// Stdin handling mode for AI tool execution
@@ -232,3 +677,81 @@ pub enum StdinMode {
     /// Don't pass stdin to the AI tool
     Null,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tools_from_config() {
+        let config: HashMap<String, ToolConfig> = serde_json::from_value(serde_json::json!({
+            "claude": {"args": [], "priority": 1},
+            "qwen": {"disabled": true},
+            "aider": {"args": ["--no-auto-commits"], "env": {"AIDER_DARK_MODE": "1"}},
+            "cursor": {"name": "Cursor Agent", "command": "cursor-agent", "path": "argument"},
+        }))
+        .unwrap();
+
+        let tools = all_tools(&config);
+        let ids: Vec<&str> = tools.iter().map(|tool| tool.id.as_str()).collect();
+        assert_eq!(ids, ["claude", "opencode", "qwen", "zed", "aider", "cursor"]);
+
+        // Overrides keep the fields they do not set
+        let claude = &tools[0];
+        assert!(claude.default_args.is_empty());
+        assert_eq!(claude.resume_flag.as_deref(), Some("--resume"));
+        assert_eq!(claude.status, StatusProfile::Claude);
+        assert!(tools[2].disabled);
+
+        let aider = &tools[4];
+        assert_eq!(aider.name, "aider");
+        assert_eq!(aider.env_var, "XLAUDE_AIDER_CMD");
+        assert_eq!(aider.env["AIDER_DARK_MODE"], "1");
+        assert_eq!(
+            aider.command_line(&["--message".to_string(), "hi".to_string()], Path::new("/w")),
+            ["aider", "--no-auto-commits", "--message", "hi"]
+        );
+        assert_eq!(
+            tools[5].command_line(&[], Path::new("/w")),
+            ["cursor-agent", "/w"]
+        );
+
+        assert!(get_tool(&config, "qwen").is_err());
+        assert!(get_tool(&config, "gemini").is_err());
+        assert_eq!(get_tool(&config, "cursor").unwrap().prompt_args("hi"), None);
+        assert_eq!(
+            get_tool(&config, "opencode").unwrap().prompt_args("fix it"),
+            Some(vec!["--prompt".to_string(), "fix it".to_string()])
+        );
+        assert_eq!(get_tool(&config, "cursor").unwrap().name, "Cursor Agent");
+    }
+
+    #[test]
+    fn test_permission_profiles() {
+        let config: HashMap<String, ToolConfig> = serde_json::from_value(serde_json::json!({
+            "claude": {"profiles": {"ci": {"args": ["--allowedTools", "Bash(git:*) Edit"], "env": {"CI": "1"}}}},
+        }))
+        .unwrap();
+        let claude = get_tool(&config, "claude").unwrap();
+        let path = Path::new("/w");
+
+        // Claude starts without permission prompts unless told otherwise
+        assert_eq!(claude.command_line(&[], path), ["claude", "--dangerously-skip-permissions"]);
+        let readonly = claude.with_profile(Some("readonly")).unwrap();
+        assert_eq!(readonly.command_line(&[], path), ["claude", "--permission-mode", "plan"]);
+
+        let ci = claude.with_profile(Some("ci")).unwrap();
+        assert_eq!(ci.profile.as_deref(), Some("ci"));
+        assert_eq!(ci.command_line(&[], path), ["claude", "--allowedTools", "Bash(git:*) Edit"]);
+        assert_eq!(ci.environment()["CI"], "1");
+
+        let opencode = get_tool(&config, "opencode").unwrap();
+        assert!(opencode.with_profile(None).unwrap().profile.is_none());
+        let ask = opencode.with_profile(Some("ask")).unwrap();
+        assert!(ask.environment()["OPENCODE_PERMISSION"].contains(r#""bash":"ask""#));
+
+        // A profile the tool cannot enforce is an error, not a silent fallback
+        let err = get_tool(&config, "zed").unwrap().with_profile(Some("readonly")).unwrap_err();
+        assert!(err.to_string().contains("no permission profile 'readonly'"));
+    }
+}
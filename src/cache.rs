@@ -0,0 +1,116 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::state::get_config_dir;
+
+/// Size and modification time of a file when it was last read
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+/// How a file changed since its fingerprint was taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Unchanged,
+    /// Append-only files that grew can be read on from where the last read stopped
+    Grown,
+    /// Anything else means the file was rewritten and is read from the start
+    Rewritten,
+}
+
+impl Fingerprint {
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Self {
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+
+    /// How the file changed from this fingerprint to `current`
+    pub fn change(&self, current: &Self) -> Change {
+        if self == current {
+            Change::Unchanged
+        } else if current.size > self.size {
+            Change::Grown
+        } else {
+            Change::Rewritten
+        }
+    }
+}
+
+/// Read a cache file from the config directory; `None` if missing or unreadable
+pub fn load<T: DeserializeOwned>(file: &str) -> Option<T> {
+    let path = get_config_dir().ok()?.join(file);
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+/// Write a cache file to the config directory via a temp file so readers
+/// never see a partial file. Returns whether it was written; a cache that
+/// could not be written is rebuilt next time.
+pub fn store<T: Serialize>(file: &str, value: &T) -> bool {
+    let Ok(dir) = get_config_dir() else {
+        return false;
+    };
+    let Ok(content) = serde_json::to_string(value) else {
+        return false;
+    };
+    let path = dir.join(file);
+    let tmp = path.with_extension(format!("json.{}", std::process::id()));
+    if fs::create_dir_all(path.parent().unwrap_or(&dir)).is_ok()
+        && fs::write(&tmp, content).is_ok()
+        && fs::rename(&tmp, &path).is_ok()
+    {
+        true
+    } else {
+        let _ = fs::remove_file(&tmp);
+        false
+    }
+}
+
+/// Delete a cache file from the config directory, if there is one
+pub fn remove(file: &str) {
+    if let Ok(dir) = get_config_dir() {
+        let _ = fs::remove_file(dir.join(file));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_fingerprint_change() {
+        let now = SystemTime::now();
+        let cached = Fingerprint {
+            size: 10,
+            modified: Some(now),
+        };
+        let later = Some(now + Duration::from_secs(1));
+
+        assert_eq!(cached.change(&cached), Change::Unchanged);
+        let grown = Fingerprint {
+            size: 20,
+            modified: later,
+        };
+        assert_eq!(cached.change(&grown), Change::Grown);
+        let truncated = Fingerprint {
+            size: 5,
+            modified: later,
+        };
+        assert_eq!(cached.change(&truncated), Change::Rewritten);
+        // Same size but touched again: contents may differ
+        let rewritten = Fingerprint {
+            size: 10,
+            modified: later,
+        };
+        assert_eq!(cached.change(&rewritten), Change::Rewritten);
+        assert_eq!(Fingerprint::default().change(&grown), Change::Grown);
+    }
+}
@@ -1,119 +1,664 @@
+use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
 
-#[derive(Debug)]
-pub struct SessionInfo {
-    pub last_user_message: String,
-    pub last_timestamp: Option<DateTime<Utc>>,
+use crate::cache::{self, Change, Fingerprint};
+use crate::session_provider::{SessionInfo, SessionProvider, sort_sessions};
+use crate::state::XlaudeState;
+use crate::transcript::{MessageRecord, Record, Usage};
+use crate::usage::UsageBucket;
+
+const CACHE_FILE: &str = "transcript-cache.json";
+
+/// Bump when `TranscriptSummary` changes so stale caches are discarded
+const CACHE_VERSION: u32 = 2;
+
+/// What is extracted from a transcript, built up line by line
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct TranscriptSummary {
+    first_user_message: Option<String>,
+    first_timestamp: Option<DateTime<Utc>>,
+    last_user_message: Option<String>,
+    last_timestamp: Option<DateTime<Utc>>,
+    usage: Vec<UsageBucket>,
+    last_response: Option<LastResponse>,
 }
 
-pub fn get_claude_sessions(project_path: &Path) -> Vec<SessionInfo> {
-    // Get home directory
-    let Ok(home) = std::env::var("HOME") else {
-        return vec![];
-    };
+/// The response usage was last counted for.
+///
+/// Claude Code writes one record per content block of a response, each
+/// carrying the usage of the whole response, so repeats replace the count.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct LastResponse {
+    id: String,
+    bucket: usize,
+    usage: Usage,
+}
 
-    // Construct path to Claude projects directory
-    let claude_projects_dir = Path::new(&home).join(".claude").join("projects");
+impl TranscriptSummary {
+    /// Fold one JSONL line into the summary. Applying the same line twice is harmless.
+    fn apply_line(&mut self, line: &str) {
+        let record = match Record::parse(line) {
+            Record::User(record) => record,
+            Record::Assistant(record) => {
+                self.apply_usage(&record);
+                return;
+            }
+            _ => return,
+        };
 
-    // Get canonical path of the project
-    let Ok(canonical_path) = project_path.canonicalize() else {
-        return vec![];
-    };
+        if let Some(ts) = record.timestamp {
+            self.last_timestamp = Some(ts);
+        }
 
-    // Convert path to Claude's format (replace / with -)
-    let encoded_path = canonical_path.to_string_lossy().replace('/', "-");
+        let Some(content) = record.prompt_text() else {
+            return;
+        };
+
+        if self.first_user_message.is_none() {
+            self.first_user_message = Some(content.clone());
+            self.first_timestamp = self.last_timestamp;
+        }
+        self.last_user_message = Some(content);
+    }
 
-    let project_dir = claude_projects_dir.join(&encoded_path);
+    fn apply_usage(&mut self, record: &MessageRecord) {
+        let Some(message) = &record.message else {
+            return;
+        };
+        let (Some(usage), Some(timestamp)) = (message.usage, record.timestamp) else {
+            return;
+        };
+        // Synthetic messages such as API errors carry empty usage
+        if usage.total() == 0 {
+            return;
+        }
 
-    // List session files (.jsonl files)
-    let mut sessions = vec![];
-    if let Ok(entries) = fs::read_dir(&project_dir) {
-        for entry in entries.flatten() {
-            if let Some(name) = entry.file_name().to_str()
-                && std::path::Path::new(name)
-                    .extension()
-                    .is_some_and(|ext| ext.eq_ignore_ascii_case("jsonl"))
+        let repeated = self
+            .last_response
+            .as_ref()
+            .filter(|last| message.id.as_ref() == Some(&last.id));
+        let bucket = if let Some(last) = repeated {
+            let bucket = last.bucket;
+            self.usage[bucket].usage.subtract(&last.usage);
+            bucket
+        } else {
+            let hour = UsageBucket::hour_of(timestamp);
+            let model = message.model.clone().unwrap_or_default();
+            match self
+                .usage
+                .iter()
+                .position(|b| b.hour == hour && b.model == model)
             {
-                // Read session data from the file
-                let mut last_user_message = String::new();
-                let mut last_timestamp = None;
-
-                if let Ok(file) = fs::File::open(entry.path()) {
-                    let reader = BufReader::new(file);
-                    let mut user_messages = Vec::new();
-
-                    for line in reader.lines().map_while(Result::ok) {
-                        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&line)
-                            && json.get("type").and_then(|t| t.as_str()) == Some("user")
-                        {
-                            // Extract timestamp
-                            if let Some(ts_str) = json.get("timestamp").and_then(|t| t.as_str())
-                                && let Ok(ts) = DateTime::parse_from_rfc3339(ts_str)
-                            {
-                                last_timestamp = Some(ts.with_timezone(&Utc));
-                            }
-
-                            // Extract message content
-                            if let Some(message) = json.get("message") {
-                                let content =
-                                    message.get("content").and_then(|c| c.as_str()).map_or_else(
-                                        || {
-                                            message
-                                                .get("content")
-                                                .and_then(|c| c.as_array())
-                                                .map_or_else(String::new, |content_arr| {
-                                                    content_arr
-                                                        .iter()
-                                                        .filter_map(|item| {
-                                                            item.get("text")
-                                                                .and_then(|t| t.as_str())
-                                                        })
-                                                        .collect::<Vec<_>>()
-                                                        .join(" ")
-                                                })
-                                        },
-                                        std::string::ToString::to_string,
-                                    );
-
-                                // Filter out system messages and empty content
-                                if !content.is_empty()
-                                    && !content.starts_with("<local-command")
-                                    && !content.starts_with("<command-")
-                                    && !content.starts_with("Caveat:")
-                                    && !content.contains("[Request interrupted")
-                                {
-                                    user_messages.push(content);
-                                }
-                            }
-                        }
-                    }
-
-                    // Get the last meaningful user message
-                    if let Some(msg) = user_messages.last() {
-                        last_user_message.clone_from(msg);
-                    }
-                }
-
-                // Only add sessions with user messages
-                if !last_user_message.is_empty() {
-                    sessions.push(SessionInfo {
-                        last_user_message,
-                        last_timestamp,
+                Some(bucket) => bucket,
+                None => {
+                    self.usage.push(UsageBucket {
+                        hour,
+                        model,
+                        usage: Usage::default(),
                     });
+                    self.usage.len() - 1
                 }
             }
+        };
+
+        self.usage[bucket].usage.add(&usage);
+        self.last_response = message
+            .id
+            .clone()
+            .map(|id| LastResponse { id, bucket, usage });
+    }
+
+    /// Parse complete lines from `offset` on, returning the offset after the
+    /// last complete line. A trailing partial line is applied but re-read next time.
+    fn scan(&mut self, path: &Path, offset: u64) -> std::io::Result<u64> {
+        let mut file = fs::File::open(path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut reader = BufReader::new(file);
+
+        let mut offset = offset;
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            let read = reader.read_until(b'\n', &mut buf)?;
+            if read == 0 {
+                break;
+            }
+            self.apply_line(&String::from_utf8_lossy(&buf));
+            if buf.ends_with(b"\n") {
+                offset += read as u64;
+            }
         }
+        Ok(offset)
+    }
+
+    fn into_session(self, id: String) -> Option<SessionInfo> {
+        // Only sessions with user messages are reported
+        let last_user_message = self.last_user_message?;
+        Some(SessionInfo {
+            tool: ClaudeProvider.name(),
+            id,
+            first_user_message: self
+                .first_user_message
+                .unwrap_or_else(|| last_user_message.clone()),
+            first_timestamp: self.first_timestamp,
+            last_user_message,
+            last_timestamp: self.last_timestamp,
+            usage: self.usage,
+        })
     }
+}
+
+/// Cached summary of one transcript file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    #[serde(flatten)]
+    fingerprint: Fingerprint,
+    /// Bytes parsed so far, always at a line boundary
+    offset: u64,
+    summary: TranscriptSummary,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TranscriptCache {
+    version: u32,
+    entries: HashMap<PathBuf, CacheEntry>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl TranscriptCache {
+    fn load() -> Self {
+        cache::load::<Self>(CACHE_FILE)
+            .filter(|cache| cache.version == CACHE_VERSION)
+            .unwrap_or_else(|| Self {
+                version: CACHE_VERSION,
+                ..Self::default()
+            })
+    }
+
+    /// Write the cache if it changed
+    fn save(&mut self) {
+        if self.dirty && cache::store(CACHE_FILE, self) {
+            self.dirty = false;
+        }
+    }
+}
+
+fn cache() -> &'static Mutex<TranscriptCache> {
+    static CACHE: OnceLock<Mutex<TranscriptCache>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(TranscriptCache::load()))
+}
+
+/// A transcript file that needs (re)parsing
+struct ScanJob {
+    path: PathBuf,
+    fingerprint: Fingerprint,
+    offset: u64,
+    summary: TranscriptSummary,
+}
+
+impl ScanJob {
+    fn run(mut self) -> (PathBuf, Option<CacheEntry>) {
+        let entry = self
+            .summary
+            .scan(&self.path, self.offset)
+            .ok()
+            .map(|offset| CacheEntry {
+                fingerprint: self.fingerprint,
+                offset,
+                summary: self.summary,
+            });
+        (self.path, entry)
+    }
+}
+
+/// Parse jobs on a few threads, one chunk of files per thread
+fn run_jobs(jobs: Vec<ScanJob>) -> Vec<(PathBuf, Option<CacheEntry>)> {
+    if jobs.len() <= 1 {
+        return jobs.into_iter().map(ScanJob::run).collect();
+    }
+
+    let threads = std::thread::available_parallelism()
+        .map_or(4, |n| n.get())
+        .min(jobs.len());
+    let mut chunks: Vec<Vec<ScanJob>> = (0..threads).map(|_| Vec::new()).collect();
+    for (i, job) in jobs.into_iter().enumerate() {
+        chunks[i % threads].push(job);
+    }
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .into_iter()
+            .map(|chunk| {
+                scope.spawn(move || chunk.into_iter().map(ScanJob::run).collect::<Vec<_>>())
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_default())
+            .collect()
+    })
+}
+
+/// Claude's configuration directory: `$CLAUDE_CONFIG_DIR`, then
+/// `claude_config_dir` in the xlaude state, then `~/.claude`
+pub fn claude_config_dir() -> Option<PathBuf> {
+    static DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+    DIR.get_or_init(|| {
+        std::env::var_os("CLAUDE_CONFIG_DIR")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| XlaudeState::load().ok()?.claude_config_dir)
+            .or_else(|| {
+                let home = std::env::var_os("HOME")?;
+                Some(Path::new(&home).join(".claude"))
+            })
+    })
+    .clone()
+}
+
+/// Name of the project directory Claude uses for a working directory:
+/// every character other than an ASCII letter or digit becomes `-`
+pub fn encode_project_path(path: &Path) -> String {
+    path.to_string_lossy()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Directory where Claude stores transcripts for a project.
+///
+/// Tries the encoded canonical path and the path as given (Claude may have
+/// been started through a symlink), then project directories whose
+/// transcripts record the path as their `cwd`. If nothing exists yet, the
+/// directory Claude would create is returned.
+pub fn claude_project_dir(project_path: &Path) -> Option<PathBuf> {
+    let projects_dir = claude_config_dir()?.join("projects");
+    resolve_project_dir(&projects_dir, project_path)
+}
+
+fn resolve_project_dir(projects_dir: &Path, project_path: &Path) -> Option<PathBuf> {
+    let canonical = project_path.canonicalize().ok();
+    let mut candidates: Vec<PathBuf> = canonical.iter().cloned().collect();
+    if canonical.as_deref() != Some(project_path) && project_path.is_absolute() {
+        candidates.push(project_path.to_path_buf());
+    }
+    let candidates: Vec<PathBuf> = candidates
+        .iter()
+        .map(|path| projects_dir.join(encode_project_path(path)))
+        .collect();
+
+    if let Some(dir) = candidates.iter().find(|dir| dir.is_dir()) {
+        return Some(dir.clone());
+    }
+    let target = canonical.as_deref().unwrap_or(project_path);
+    find_project_by_cwd(projects_dir, target).or_else(|| candidates.into_iter().next())
+}
+
+/// Project directory whose transcripts were recorded in `cwd`.
+///
+/// The first `cwd` of every project directory is read once and re-read
+/// when a project directory is added or removed.
+fn find_project_by_cwd(projects_dir: &Path, cwd: &Path) -> Option<PathBuf> {
+    type CwdIndex = HashMap<PathBuf, (Option<SystemTime>, HashMap<PathBuf, PathBuf>)>;
+    static INDEX: OnceLock<Mutex<CwdIndex>> = OnceLock::new();
+
+    let modified = fs::metadata(projects_dir).and_then(|m| m.modified()).ok();
+    let mut index = INDEX
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    let entry = index.entry(projects_dir.to_path_buf()).or_default();
+    if entry.0 != modified {
+        *entry = (modified, index_project_cwds(projects_dir));
+    }
+    entry.1.get(cwd).cloned()
+}
+
+/// Map of recorded working directory to project directory
+fn index_project_cwds(projects_dir: &Path) -> HashMap<PathBuf, PathBuf> {
+    let Ok(entries) = fs::read_dir(projects_dir) else {
+        return HashMap::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|dir| dir.is_dir())
+        .filter_map(|dir| {
+            let cwd = transcript_files(&dir)
+                .iter()
+                .find_map(|file| recorded_cwd(file))?;
+            let cwd = cwd.canonicalize().unwrap_or(cwd);
+            Some((cwd, dir))
+        })
+        .collect()
+}
 
-    // Sort by timestamp (most recent first)
-    sessions.sort_by(|a, b| match (&b.last_timestamp, &a.last_timestamp) {
-        (Some(b_ts), Some(a_ts)) => b_ts.cmp(a_ts),
-        (Some(_), None) => std::cmp::Ordering::Less,
-        (None, Some(_)) => std::cmp::Ordering::Greater,
-        (None, None) => std::cmp::Ordering::Equal,
-    });
+/// The first `cwd` in a transcript, looking at its first few records only
+fn recorded_cwd(path: &Path) -> Option<PathBuf> {
+    let file = fs::File::open(path).ok()?;
+    BufReader::new(file)
+        .lines()
+        .take(20)
+        .map_while(Result::ok)
+        .find_map(|line| Record::parse(&line).message()?.cwd.clone())
+}
+
+/// Session transcripts (`.jsonl` files) in a Claude project directory
+pub fn transcript_files(project_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(project_dir) else {
+        return vec![];
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("jsonl"))
+        })
+        .collect()
+}
+
+/// Summaries of all sessions of a project, most recent first.
+///
+/// Summaries are cached by path, size and mtime in the config directory.
+/// Files that only grew are parsed from where the last parse stopped.
+pub fn get_claude_sessions(project_path: &Path) -> Vec<SessionInfo> {
+    let Some(project_dir) = claude_project_dir(project_path) else {
+        return vec![];
+    };
+    let files: Vec<(PathBuf, Fingerprint)> = transcript_files(&project_dir)
+        .into_iter()
+        .filter_map(|path| {
+            let fingerprint = Fingerprint::of(&path)?;
+            Some((path, fingerprint))
+        })
+        .collect();
+
+    let mut cache = cache().lock().unwrap_or_else(|e| e.into_inner());
+
+    let mut summaries = Vec::with_capacity(files.len());
+    let mut jobs = Vec::new();
+    for (path, fingerprint) in files {
+        let cached = cache.entries.get(&path);
+        match cached.map(|entry| (entry, entry.fingerprint.change(&fingerprint))) {
+            Some((entry, Change::Unchanged)) => {
+                summaries.push((path, entry.summary.clone()));
+            }
+            // Transcripts are append-only, so a grown file continues where we stopped
+            Some((entry, Change::Grown)) => jobs.push(ScanJob {
+                path,
+                fingerprint,
+                offset: entry.offset,
+                summary: entry.summary.clone(),
+            }),
+            _ => jobs.push(ScanJob {
+                path,
+                fingerprint,
+                offset: 0,
+                summary: TranscriptSummary::default(),
+            }),
+        }
+    }
+
+    for (path, entry) in run_jobs(jobs) {
+        if let Some(entry) = entry {
+            summaries.push((path.clone(), entry.summary.clone()));
+            cache.entries.insert(path, entry);
+            cache.dirty = true;
+        }
+    }
+
+    // Forget transcripts of this project that were deleted
+    let before = cache.entries.len();
+    cache
+        .entries
+        .retain(|path, _| path.parent() != Some(project_dir.as_path()) || path.exists());
+    cache.dirty |= cache.entries.len() != before;
+    cache.save();
+    drop(cache);
+
+    let mut sessions: Vec<SessionInfo> = summaries
+        .into_iter()
+        .filter_map(|(path, summary)| {
+            let id = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            summary.into_session(id)
+        })
+        .collect();
+
+    sort_sessions(&mut sessions);
     sessions
 }
+
+/// Sessions from Claude Code transcripts in `~/.claude/projects`
+pub struct ClaudeProvider;
+
+impl SessionProvider for ClaudeProvider {
+    fn name(&self) -> &'static str {
+        "Claude"
+    }
+
+    fn sessions(&self, path: &Path) -> Vec<SessionInfo> {
+        get_claude_sessions(path)
+    }
+
+    fn relocate(&self, old: &Path, new: &Path) -> Result<usize> {
+        let Some(config_dir) = claude_config_dir() else {
+            return Ok(0);
+        };
+        relocate_project_dir(&config_dir.join("projects"), old, new)
+    }
+}
+
+/// Move the project directory of `old` to where Claude looks for `new`,
+/// merging into it if Claude was already started at the new path
+fn relocate_project_dir(projects_dir: &Path, old: &Path, new: &Path) -> Result<usize> {
+    let new_path = new.canonicalize().unwrap_or_else(|_| new.to_path_buf());
+    let new_dir = projects_dir.join(encode_project_path(&new_path));
+    let Some(old_dir) =
+        resolve_project_dir(projects_dir, old).filter(|dir| dir.is_dir() && *dir != new_dir)
+    else {
+        return Ok(0);
+    };
+
+    let sessions = transcript_files(&old_dir).len();
+    if !new_dir.exists() {
+        fs::rename(&old_dir, &new_dir).with_context(|| {
+            format!(
+                "Failed to move {} to {}",
+                old_dir.display(),
+                new_dir.display()
+            )
+        })?;
+        return Ok(sessions);
+    }
+
+    let mut moved = 0;
+    for entry in fs::read_dir(&old_dir)?.flatten() {
+        let target = new_dir.join(entry.file_name());
+        // Session ids are unique, so an existing file is the same session
+        if target.exists() {
+            continue;
+        }
+        fs::rename(entry.path(), &target)
+            .with_context(|| format!("Failed to move {}", entry.path().display()))?;
+        if target.extension().is_some_and(|ext| ext == "jsonl") {
+            moved += 1;
+        }
+    }
+    // Only removed once everything was moved
+    let _ = fs::remove_dir(&old_dir);
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn user_line(timestamp: &str, content: &str) -> String {
+        format!(
+            r#"{{"type":"user","timestamp":"{timestamp}","message":{{"content":"{content}"}}}}"#
+        )
+    }
+
+    #[test]
+    fn test_summary_filters_system_messages() {
+        let mut summary = TranscriptSummary::default();
+        summary.apply_line(&user_line("2024-05-01T10:00:00Z", "Caveat: ignore me"));
+        summary.apply_line(r#"{"type":"assistant","message":{"content":"hello"}}"#);
+        summary.apply_line(&user_line("2024-05-01T10:01:00Z", "Fix the parser"));
+        summary.apply_line("not json");
+
+        assert_eq!(
+            summary.first_user_message.as_deref(),
+            Some("Fix the parser")
+        );
+        assert_eq!(
+            summary.first_timestamp.unwrap().to_rfc3339(),
+            "2024-05-01T10:01:00+00:00"
+        );
+        assert_eq!(summary.last_user_message.as_deref(), Some("Fix the parser"));
+    }
+
+    #[test]
+    fn test_usage_counts_each_response_once() {
+        let assistant = |id: &str, ts: &str, output: u64| {
+            format!(
+                r#"{{"type":"assistant","timestamp":"{ts}","message":{{"id":"{id}","model":"claude-sonnet-4","usage":{{"input_tokens":10,"output_tokens":{output},"cache_read_input_tokens":100}}}}}}"#
+            )
+        };
+
+        let mut summary = TranscriptSummary::default();
+        // Split records of one response repeat its usage
+        summary.apply_line(&assistant("m1", "2024-05-01T10:05:00Z", 5));
+        summary.apply_line(&assistant("m1", "2024-05-01T10:05:01Z", 20));
+        summary.apply_line(&assistant("m2", "2024-05-01T10:59:00Z", 7));
+        summary.apply_line(&assistant("m3", "2024-05-01T11:00:00Z", 1));
+
+        assert_eq!(summary.usage.len(), 2);
+        let first_hour = &summary.usage[0];
+        assert_eq!(first_hour.hour.to_rfc3339(), "2024-05-01T10:00:00+00:00");
+        assert_eq!(first_hour.usage.input_tokens, 20);
+        assert_eq!(first_hour.usage.output_tokens, 27);
+        assert_eq!(first_hour.usage.cache_read_input_tokens, 200);
+        assert_eq!(summary.usage[1].usage.output_tokens, 1);
+    }
+
+    #[test]
+    fn test_incremental_scan() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let mut file = fs::File::create(&path).unwrap();
+        writeln!(file, "{}", user_line("2024-05-01T10:00:00Z", "first")).unwrap();
+        // A line still being written is applied but not consumed
+        write!(file, "{}", user_line("2024-05-01T10:05:00Z", "second")).unwrap();
+
+        let mut summary = TranscriptSummary::default();
+        let offset = summary.scan(&path, 0).unwrap();
+        let first_line_len = user_line("2024-05-01T10:00:00Z", "first").len() as u64 + 1;
+        assert_eq!(offset, first_line_len);
+        assert_eq!(summary.last_user_message.as_deref(), Some("second"));
+
+        writeln!(file).unwrap();
+        writeln!(file, "{}", user_line("2024-05-01T10:10:00Z", "third")).unwrap();
+        let offset = summary.scan(&path, offset).unwrap();
+        assert_eq!(offset, fs::metadata(&path).unwrap().len());
+
+        let mut full = TranscriptSummary::default();
+        full.scan(&path, 0).unwrap();
+        assert_eq!(summary, full);
+        assert_eq!(summary.first_user_message.as_deref(), Some("first"));
+        assert_eq!(summary.last_user_message.as_deref(), Some("third"));
+    }
+
+    #[test]
+    fn test_encode_project_path() {
+        assert_eq!(
+            encode_project_path(Path::new("/home/me/src/my.repo-feat_x")),
+            "-home-me-src-my-repo-feat-x"
+        );
+    }
+
+    #[test]
+    fn test_resolve_project_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path().canonicalize().unwrap();
+        let projects = root.join("projects");
+
+        // Found under the faithfully encoded canonical path
+        let worktree = root.join("my.repo-feat_x");
+        fs::create_dir_all(&worktree).unwrap();
+        let expected = projects.join(encode_project_path(&worktree));
+        assert_eq!(
+            resolve_project_dir(&projects, &worktree),
+            Some(expected.clone())
+        );
+        fs::create_dir_all(&expected).unwrap();
+        assert_eq!(resolve_project_dir(&projects, &worktree), Some(expected));
+
+        // Claude was started through a symlink
+        #[cfg(unix)]
+        {
+            let linked = root.join("linked");
+            fs::create_dir_all(&linked).unwrap();
+            let link = root.join("link");
+            std::os::unix::fs::symlink(&linked, &link).unwrap();
+            let via_link = projects.join(encode_project_path(&link));
+            fs::create_dir_all(&via_link).unwrap();
+            assert_eq!(resolve_project_dir(&projects, &link), Some(via_link));
+        }
+
+        // Encoded some other way, but the transcript records the cwd
+        let other = root.join("other");
+        fs::create_dir_all(&other).unwrap();
+        let renamed = projects.join("renamed");
+        fs::create_dir_all(&renamed).unwrap();
+        fs::write(
+            renamed.join("s.jsonl"),
+            format!(
+                "{}\n",
+                serde_json::json!({"type": "user", "cwd": other, "message": {"content": "hi"}})
+            ),
+        )
+        .unwrap();
+        assert_eq!(resolve_project_dir(&projects, &other), Some(renamed));
+    }
+
+    #[test]
+    fn test_relocate_project_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path().canonicalize().unwrap();
+        let projects = root.join("projects");
+        let old = root.join("old");
+        let new = root.join("new");
+        fs::create_dir_all(&new).unwrap();
+
+        let old_dir = projects.join(encode_project_path(&old));
+        fs::create_dir_all(&old_dir).unwrap();
+        fs::write(old_dir.join("a.jsonl"), "").unwrap();
+        assert_eq!(relocate_project_dir(&projects, &old, &new).unwrap(), 1);
+        let new_dir = projects.join(encode_project_path(&new));
+        assert!(new_dir.join("a.jsonl").exists());
+        assert!(!old_dir.exists());
+
+        // Merged into the sessions already started at the new path
+        fs::create_dir_all(&old_dir).unwrap();
+        fs::write(old_dir.join("a.jsonl"), "").unwrap();
+        fs::write(old_dir.join("b.jsonl"), "").unwrap();
+        assert_eq!(relocate_project_dir(&projects, &old, &new).unwrap(), 1);
+        assert!(new_dir.join("b.jsonl").exists());
+
+        assert_eq!(relocate_project_dir(&projects, &old, &new).unwrap(), 0);
+    }
+}
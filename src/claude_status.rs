@@ -1,5 +1,18 @@
+use serde::{Deserialize, Serialize};
 use std::time::{Duration, Instant};
 
+use crate::tmux::TmuxManager;
+
+/// Which patterns are looked for in an agent's terminal output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusProfile {
+    /// Claude's TUI: its input box, "esc to interrupt" and tool output markers
+    Claude,
+    /// Only patterns common to terminal tools: cursors, errors and progress words
+    Generic,
+}
+
 /// Claude session status based on output analysis
 #[derive(Debug, Clone, PartialEq)]
 pub enum ClaudeStatus {
@@ -26,6 +39,17 @@ impl ClaudeStatus {
         }
     }
 
+    /// Stable lowercase identifier for filtering and machine-readable output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClaudeStatus::WaitingForInput => "waiting",
+            ClaudeStatus::Processing => "processing",
+            ClaudeStatus::Error => "error",
+            ClaudeStatus::Idle => "idle",
+            ClaudeStatus::NotRunning => "not_running",
+        }
+    }
+
     pub fn display_icon(&self) -> &str {
         match self {
             ClaudeStatus::WaitingForInput => "⏸",
@@ -48,14 +72,28 @@ impl ClaudeStatus {
     }
 }
 
+/// Detect the status of the agent in a worktree's running dashboard tmux session
+pub fn detect_session_status(
+    tmux: &TmuxManager,
+    worktree_name: &str,
+    profile: StatusProfile,
+) -> ClaudeStatus {
+    match tmux.capture_pane(worktree_name, 100) {
+        Ok(output) => ClaudeStatusDetector::with_profile(profile).analyze_output(&output),
+        Err(_) => ClaudeStatus::NotRunning,
+    }
+}
+
 pub struct ClaudeStatusDetector {
+    profile: StatusProfile,
     last_analysis: Option<Instant>,
     cache: Option<(String, ClaudeStatus)>,
 }
 
 impl ClaudeStatusDetector {
-    pub fn new() -> Self {
+    pub fn with_profile(profile: StatusProfile) -> Self {
         Self {
+            profile,
             last_analysis: None,
             cache: None,
         }
@@ -113,6 +151,15 @@ impl ClaudeStatusDetector {
     }
 
     fn is_waiting_for_input(&self, last_lines: &[&&str], full_output: &str) -> bool {
+        if self.profile == StatusProfile::Claude && self.has_claude_prompt(last_lines) {
+            return true;
+        }
+
+        // Check for traditional cursor at the end
+        full_output.ends_with("▌") || full_output.ends_with("█")
+    }
+
+    fn has_claude_prompt(&self, last_lines: &[&&str]) -> bool {
         // Check for Claude TUI input box pattern
         // The input box looks like: │ >                   │
         // It appears at the bottom of the screen when waiting for input
@@ -131,11 +178,6 @@ impl ClaudeStatusDetector {
             }
         }
 
-        // Check for traditional cursor at the end
-        if full_output.ends_with("▌") || full_output.ends_with("█") {
-            return true;
-        }
-
         // Check for Human: prompt (older Claude versions)
         for line in last_lines {
             let trimmed = line.trim();
@@ -165,18 +207,7 @@ impl ClaudeStatusDetector {
 
     fn is_processing(&self, last_lines: &[&&str]) -> bool {
         for line in last_lines {
-            // Most reliable: "(esc to interrupt)" indicator
-            if line.contains("(esc to interrupt)") {
-                return true;
-            }
-
-            // Active tool output with ⎿ symbol and "Running" or ellipsis
-            if line.contains("⎿") && (line.contains("Running") || line.contains("…")) {
-                return true;
-            }
-
-            // Check for working indicators
-            if line.contains("✽") && (line.contains("Working") || line.contains("Processing")) {
+            if self.profile == StatusProfile::Claude && self.is_claude_processing(line) {
                 return true;
             }
 
@@ -213,6 +244,21 @@ impl ClaudeStatusDetector {
 
         false
     }
+
+    fn is_claude_processing(&self, line: &str) -> bool {
+        // Most reliable: "(esc to interrupt)" indicator
+        if line.contains("(esc to interrupt)") {
+            return true;
+        }
+
+        // Active tool output with ⎿ symbol and "Running" or ellipsis
+        if line.contains("⎿") && (line.contains("Running") || line.contains("…")) {
+            return true;
+        }
+
+        // Check for working indicators
+        line.contains("✽") && (line.contains("Working") || line.contains("Processing"))
+    }
 }
 
 #[cfg(test)]
@@ -221,7 +267,7 @@ mod tests {
 
     #[test]
     fn test_waiting_for_input() {
-        let mut detector = ClaudeStatusDetector::new();
+        let mut detector = ClaudeStatusDetector::with_profile(StatusProfile::Claude);
 
         // Test cursor pattern
         let output = "Assistant: I've completed the task.\n\nHuman: ▌";
@@ -240,7 +286,7 @@ mod tests {
 
     #[test]
     fn test_error_detection() {
-        let mut detector = ClaudeStatusDetector::new();
+        let mut detector = ClaudeStatusDetector::with_profile(StatusProfile::Claude);
 
         let output = "Running command...\nError: Command failed\nPlease fix the issue";
         assert_eq!(detector.analyze_output(output), ClaudeStatus::Error);
@@ -248,9 +294,26 @@ mod tests {
 
     #[test]
     fn test_processing_detection() {
-        let mut detector = ClaudeStatusDetector::new();
+        let mut detector = ClaudeStatusDetector::with_profile(StatusProfile::Claude);
 
         let output = "Let me analyze this code...\nChecking the files\nProcessing...";
         assert_eq!(detector.analyze_output(output), ClaudeStatus::Processing);
     }
+
+    #[test]
+    fn test_generic_profile() {
+        let mut claude = ClaudeStatusDetector::with_profile(StatusProfile::Claude);
+        let mut generic = ClaudeStatusDetector::with_profile(StatusProfile::Generic);
+
+        // Claude's markers mean nothing to other tools
+        let output = "Reading files\n\n✽ Working… (esc to interrupt)\n";
+        assert_eq!(claude.analyze_output(output), ClaudeStatus::Processing);
+        assert_eq!(
+            generic.analyze_output(output),
+            ClaudeStatus::WaitingForInput
+        );
+
+        let output = "> add tests\nCompiling xlaude v0.4.0\n";
+        assert_eq!(generic.analyze_output(output), ClaudeStatus::Processing);
+    }
 }
@@ -3,16 +3,22 @@ use chrono::Utc;
 use colored::Colorize;
 
 use crate::git::{get_current_branch, get_repo_name, is_in_worktree};
+use crate::output::{self, ErrorCode, coded, say};
 use crate::state::{WorktreeInfo, XlaudeState};
 use crate::utils::sanitize_branch_name;
 
 pub fn handle_add(name: Option<String>) -> Result<()> {
     // Check if we're in a git repository
-    let repo_name = get_repo_name().context("Not in a git repository")?;
+    let repo_name =
+        get_repo_name().context(coded(ErrorCode::NotInRepository, "Not in a git repository"))?;
 
     // Check if we're in a worktree
     if !is_in_worktree()? {
-        anyhow::bail!("Current directory is not a git worktree");
+        return Err(coded(
+            ErrorCode::InvalidState,
+            "Current directory is not a git worktree",
+        )
+        .into());
     }
 
     // Get current branch name
@@ -31,19 +37,25 @@ pub fn handle_add(name: Option<String>) -> Result<()> {
     let mut state = XlaudeState::load()?;
     let key = XlaudeState::make_key(&repo_name, &worktree_name);
     if state.worktrees.contains_key(&key) {
-        anyhow::bail!(
-            "Worktree '{}/{}' is already managed by xlaude",
-            repo_name,
-            worktree_name
-        );
+        return Err(coded(
+            ErrorCode::AlreadyExists,
+            format!("Worktree '{repo_name}/{worktree_name}' is already managed by xlaude"),
+        )
+        .into());
     }
 
-    println!(
+    say!(
         "{} Adding worktree '{}' to xlaude management...",
         "➕".green(),
         worktree_name.cyan()
     );
 
+    output::set("name", &worktree_name);
+    output::set("repo", &repo_name);
+    output::set("branch", &current_branch);
+    output::set("path", &current_dir);
+    output::action(format!("added worktree {key}"));
+
     // Add to state
     state.worktrees.insert(
         key,
@@ -53,16 +65,20 @@ pub fn handle_add(name: Option<String>) -> Result<()> {
             path: current_dir.clone(),
             repo_name,
             created_at: Utc::now(),
+            tags: Vec::new(),
+            tool: None,
+            profile: None,
+            last_run: None,
         },
     );
     state.save()?;
 
-    println!(
+    say!(
         "{} Worktree '{}' added successfully",
         "✅".green(),
         worktree_name.cyan()
     );
-    println!("  {} {}", "Path:".bright_black(), current_dir.display());
+    say!("  {} {}", "Path:".bright_black(), current_dir.display());
 
     Ok(())
 }
@@ -1,24 +1,102 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
 use colored::Colorize;
+use serde_json::json;
 use std::collections::HashSet;
 use std::path::PathBuf;
 
-use crate::git::list_worktrees;
-use crate::state::XlaudeState;
-use crate::utils::execute_in_dir;
+use crate::commands::delete::delete_worktree_unattended;
+use crate::git::{
+    count_local_only_commits, has_unpushed_commits, is_working_tree_clean, last_commit_date,
+    list_worktrees,
+};
+use crate::git_status::{self, MergedVia};
+use crate::input::smart_confirm;
+use crate::output::{self, say};
+use crate::session_provider::get_sessions;
+use crate::state::{RetentionPolicy, WorktreeInfo, XlaudeState};
+use crate::utils::{execute_in_dir, format_duration_short, parse_duration};
 
-pub fn handle_clean() -> Result<()> {
+/// Criteria for pruning worktrees, from CLI flags or the retention policy
+#[derive(Debug, Default)]
+pub struct PruneOptions {
+    pub older_than: Option<Duration>,
+    pub idle: Option<Duration>,
+    pub merged: bool,
+    pub force: bool,
+    pub dry_run: bool,
+}
+
+impl PruneOptions {
+    pub fn from_policy(policy: &RetentionPolicy, force: bool, dry_run: bool) -> Result<Self> {
+        Ok(Self {
+            older_than: policy
+                .older_than
+                .as_deref()
+                .map(parse_duration)
+                .transpose()?,
+            idle: policy.idle.as_deref().map(parse_duration).transpose()?,
+            merged: policy.merged,
+            force,
+            dry_run,
+        })
+    }
+
+    fn has_criteria(&self) -> bool {
+        self.older_than.is_some() || self.idle.is_some() || self.merged
+    }
+}
+
+/// A worktree matching the prune criteria
+struct PruneCandidate {
+    key: String,
+    info: WorktreeInfo,
+    age: Duration,
+    idle: Duration,
+    merged: Option<bool>,
+    /// Reason the worktree is kept despite matching (dirty, unpushed)
+    blocker: Option<&'static str>,
+}
+
+pub fn handle_clean(options: PruneOptions) -> Result<()> {
     let mut state = XlaudeState::load()?;
+    output::set("dry_run", options.dry_run);
 
     if state.worktrees.is_empty() {
-        println!("{} No worktrees in state", "✨".green());
+        say!("{} No worktrees in state", "✨".green());
         return Ok(());
     }
 
-    println!("{} Checking for invalid worktrees...", "🔍".cyan());
+    remove_invalid_worktrees(&mut state, options.dry_run)?;
+
+    if options.has_criteria() {
+        prune_worktrees(&mut state, &options)?;
+    }
+
+    Ok(())
+}
+
+/// Apply the retention policy from the xlaude config
+pub fn handle_gc(force: bool, dry_run: bool) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let policy = state.retention.as_ref().context(
+        "No retention policy configured. Add a \"retention\" section to state.json, \
+         e.g. {\"older_than\": \"14d\", \"idle\": \"3d\", \"merged\": true}",
+    )?;
+
+    let options = PruneOptions::from_policy(policy, force, dry_run)?;
+    if !options.has_criteria() {
+        anyhow::bail!("Retention policy has no criteria (set older_than, idle or merged)");
+    }
+
+    handle_clean(options)
+}
+
+fn remove_invalid_worktrees(state: &mut XlaudeState, dry_run: bool) -> Result<()> {
+    say!("{} Checking for invalid worktrees...", "🔍".cyan());
 
     // Collect all actual worktrees from all repositories
-    let actual_worktrees = collect_all_worktrees(&state)?;
+    let actual_worktrees = collect_all_worktrees(state)?;
 
     // Find and remove invalid worktrees
     let mut removed_count = 0;
@@ -27,7 +105,7 @@ pub fn handle_clean() -> Result<()> {
         .iter()
         .filter_map(|(name, info)| {
             if !actual_worktrees.contains(&info.path) {
-                println!(
+                say!(
                     "  {} Found invalid worktree: {} ({})",
                     "❌".red(),
                     name.yellow(),
@@ -41,26 +119,250 @@ pub fn handle_clean() -> Result<()> {
         })
         .collect();
 
+    if dry_run {
+        output::set("invalid_removed", Vec::<String>::new());
+        output::set("invalid_found", &worktrees_to_remove);
+        if removed_count > 0 {
+            say!(
+                "{} Dry run: {} invalid worktree(s) would be removed",
+                "ℹ️".blue(),
+                removed_count
+            );
+        } else {
+            say!("{} All worktrees are valid", "✨".green());
+        }
+        return Ok(());
+    }
+
     // Remove invalid worktrees from state
-    for name in worktrees_to_remove {
-        state.worktrees.remove(&name);
+    for name in &worktrees_to_remove {
+        state.worktrees.remove(name);
+        output::action(format!("removed invalid worktree {name}"));
     }
+    output::set("invalid_removed", &worktrees_to_remove);
 
     if removed_count > 0 {
         state.save()?;
-        println!(
+        say!(
             "{} Removed {} invalid worktree{}",
             "✅".green(),
             removed_count,
             if removed_count == 1 { "" } else { "s" }
         );
     } else {
-        println!("{} All worktrees are valid", "✨".green());
+        say!("{} All worktrees are valid", "✨".green());
     }
 
     Ok(())
 }
 
+fn prune_worktrees(state: &mut XlaudeState, options: &PruneOptions) -> Result<()> {
+    say!();
+    say!(
+        "{} Evaluating {} worktree(s) against prune criteria...",
+        "🔍".cyan(),
+        state.worktrees.len()
+    );
+
+    let now = Utc::now();
+    let mut candidates: Vec<PruneCandidate> = state
+        .worktrees
+        .iter()
+        .filter_map(|(key, info)| evaluate_worktree(key, info, options, now))
+        .collect();
+    candidates.sort_by(|a, b| a.key.cmp(&b.key));
+    output::set(
+        "candidates",
+        candidates
+            .iter()
+            .map(|c| c.to_json(options.force))
+            .collect::<Vec<_>>(),
+    );
+    output::set("pruned", Vec::<String>::new());
+
+    if candidates.is_empty() {
+        say!("{} No worktrees match the prune criteria", "✨".green());
+        return Ok(());
+    }
+
+    print_preview_table(&candidates, options.force);
+
+    let to_remove: Vec<&PruneCandidate> = candidates
+        .iter()
+        .filter(|c| c.will_remove(options.force))
+        .collect();
+
+    if to_remove.is_empty() {
+        say!(
+            "{} All matching worktrees have pending work (use --force to remove anyway)",
+            "ℹ️".blue()
+        );
+        return Ok(());
+    }
+
+    if options.dry_run {
+        say!(
+            "{} Dry run: {} worktree(s) would be removed",
+            "ℹ️".blue(),
+            to_remove.len()
+        );
+        return Ok(());
+    }
+
+    if !smart_confirm(&format!("Remove {} worktree(s)?", to_remove.len()), false)? {
+        say!("{} Cancelled", "❌".red());
+        return Ok(());
+    }
+
+    let mut removed = Vec::new();
+    for candidate in to_remove {
+        say!();
+        say!("{} Pruning '{}'...", "🗑️ ".yellow(), candidate.key.cyan());
+        let force_branch = options.force || candidate.merged == Some(true);
+        match delete_worktree_unattended(&candidate.info, force_branch) {
+            Ok(()) => {
+                state.worktrees.remove(&candidate.key);
+                removed.push(candidate.key.clone());
+            }
+            Err(e) => {
+                say!("{} Failed to prune '{}': {}", "⚠️ ".red(), candidate.key, e);
+                output::warn(format!("Failed to prune '{}': {e:#}", candidate.key));
+            }
+        }
+    }
+
+    state.save()?;
+    say!();
+    say!("{} Pruned {} worktree(s)", "✅".green(), removed.len());
+    output::set("pruned", &removed);
+
+    Ok(())
+}
+
+impl PruneCandidate {
+    /// Whether the candidate gets removed, given `--force`
+    fn will_remove(&self, force: bool) -> bool {
+        force || self.blocker.is_none()
+    }
+
+    fn to_json(&self, force: bool) -> serde_json::Value {
+        json!({
+            "key": self.key,
+            "name": self.info.name,
+            "path": self.info.path,
+            "age_seconds": self.age.num_seconds(),
+            "idle_seconds": self.idle.num_seconds(),
+            "merged": self.merged,
+            "blocker": self.blocker,
+            "remove": self.will_remove(force),
+        })
+    }
+}
+
+/// Check a single worktree against the criteria, returning it if it matches
+fn evaluate_worktree(
+    key: &str,
+    info: &WorktreeInfo,
+    options: &PruneOptions,
+    now: DateTime<Utc>,
+) -> Option<PruneCandidate> {
+    if !info.path.exists() {
+        return None;
+    }
+
+    let age = now.signed_duration_since(info.created_at);
+    if options.older_than.is_some_and(|min| age < min) {
+        return None;
+    }
+
+    // Last activity is the latest of creation, last commit and last agent session
+    let last_commit = execute_in_dir(&info.path, last_commit_date).ok();
+    let last_session = get_sessions(&info.path)
+        .into_iter()
+        .filter_map(|s| s.last_timestamp)
+        .max();
+    let last_activity = [Some(info.created_at), last_commit, last_session]
+        .into_iter()
+        .flatten()
+        .max()
+        .unwrap_or(info.created_at);
+    let idle = now.signed_duration_since(last_activity);
+    if options.idle.is_some_and(|min| idle < min) {
+        return None;
+    }
+
+    let merged_via = if options.merged {
+        Some(git_status::merged_via(info)?)
+    } else {
+        None
+    };
+    let merged = merged_via.map(|_| true);
+    // A squashed or rebased pull request leaves the commits of the branch
+    // unknown to the base, and to the remote once its branch is deleted
+    let merged_by_pr = merged_via == Some(MergedVia::PullRequest);
+
+    let blocker = execute_in_dir(&info.path, || {
+        if !is_working_tree_clean()? {
+            return Ok(Some("dirty"));
+        }
+        if !merged_by_pr
+            && (has_unpushed_commits() || count_local_only_commits(&info.branch).unwrap_or(0) > 0)
+        {
+            return Ok(Some("unpushed"));
+        }
+        Ok(None)
+    })
+    .unwrap_or(Some("unknown"));
+
+    Some(PruneCandidate {
+        key: key.to_string(),
+        info: info.clone(),
+        age,
+        idle,
+        merged,
+        blocker,
+    })
+}
+
+fn print_preview_table(candidates: &[PruneCandidate], force: bool) {
+    let name_width = candidates
+        .iter()
+        .map(|c| c.key.len())
+        .max()
+        .unwrap_or(0)
+        .max("WORKTREE".len());
+
+    say!();
+    say!(
+        "  {:<name_width$}  {:>5}  {:>5}  {:<6}  ACTION",
+        "WORKTREE",
+        "AGE",
+        "IDLE",
+        "MERGED"
+    );
+    for candidate in candidates {
+        let merged = match candidate.merged {
+            Some(true) => "yes",
+            Some(false) => "no",
+            None => "-",
+        };
+        let action = match candidate.blocker {
+            Some(reason) if !force => format!("keep ({reason})").yellow(),
+            Some(reason) => format!("remove (forced, {reason})").red(),
+            None => "remove".red(),
+        };
+        say!(
+            "  {:<name_width$}  {:>5}  {:>5}  {:<6}  {}",
+            candidate.key,
+            format_duration_short(candidate.age),
+            format_duration_short(candidate.idle),
+            merged,
+            action
+        );
+    }
+    say!();
+}
+
 fn collect_all_worktrees(state: &XlaudeState) -> Result<HashSet<PathBuf>> {
     let mut all_worktrees = HashSet::new();
 
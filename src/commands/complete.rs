@@ -1,7 +1,7 @@
 use anyhow::Result;
 use std::path::Path;
 
-use crate::claude::get_claude_sessions;
+use crate::session_provider::get_sessions;
 use crate::state::{WorktreeInfo, XlaudeState};
 
 pub fn handle_complete_worktrees(format: &str) -> Result<()> {
@@ -65,5 +65,5 @@ pub fn handle_complete_worktrees(format: &str) -> Result<()> {
 
 // Safe wrapper for counting sessions that won't fail
 fn count_sessions_safe(worktree_path: &Path) -> usize {
-    get_claude_sessions(worktree_path).len()
+    get_sessions(worktree_path).len()
 }
@@ -4,20 +4,26 @@ use colored::Colorize;
 use std::fs;
 use std::path::PathBuf;
 
-use crate::commands::open::handle_open;
+use crate::commands::open::{OpenOptions, handle_open};
 use crate::git::{
     execute_git, extract_repo_name_from_url, get_repo_name, list_worktrees, update_submodules,
 };
 use crate::input::{get_command_arg, smart_confirm};
+use crate::output::{self, ErrorCode, coded, say};
 use crate::state::{WorktreeInfo, XlaudeState};
 use crate::utils::{generate_random_name, sanitize_branch_name};
 
-pub fn handle_create(name: Option<String>) -> Result<()> {
-    handle_create_in_dir(name, None)
+/// Create a worktree, opening it with `prompt` as the agent's first message if given
+pub fn handle_create(name: Option<String>, prompt: Option<String>) -> Result<()> {
+    handle_create_in_dir(name, None, prompt)
 }
 
-pub fn handle_create_in_dir(name: Option<String>, repo_path: Option<PathBuf>) -> Result<()> {
-    handle_create_in_dir_quiet(name, repo_path, false)?;
+pub fn handle_create_in_dir(
+    name: Option<String>,
+    repo_path: Option<PathBuf>,
+    prompt: Option<String>,
+) -> Result<()> {
+    handle_create_in_dir_quiet(name, repo_path, false, prompt)?;
     Ok(())
 }
 
@@ -26,6 +32,7 @@ pub fn handle_create_in_dir_quiet(
     name: Option<String>,
     repo_path: Option<PathBuf>,
     quiet: bool,
+    prompt: Option<String>,
 ) -> Result<String> {
     // Helper to execute git in the right directory using git -C
     let exec_git = |args: &[&str]| -> Result<String> {
@@ -53,7 +60,7 @@ pub fn handle_create_in_dir_quiet(
                 .context("Failed to get repository name")?
         }
     } else {
-        get_repo_name().context("Not in a git repository")?
+        get_repo_name().context(coded(ErrorCode::NotInRepository, "Not in a git repository"))?
     };
 
     // Only check base branch if no repo_path is provided (i.e., running from CLI in current directory)
@@ -68,10 +75,13 @@ pub fn handle_create_in_dir_quiet(
 
         let base_branches = ["main", "master", "develop", &default_branch];
         if !base_branches.contains(&current_branch.as_str()) {
-            anyhow::bail!(
-                "Must be on a base branch (main, master, or develop) to create a new worktree. Current branch: {}",
-                current_branch
-            );
+            return Err(coded(
+                ErrorCode::InvalidState,
+                format!(
+                    "Must be on a base branch (main, master, or develop) to create a new worktree. Current branch: {current_branch}"
+                ),
+            )
+            .into());
         }
     }
 
@@ -88,11 +98,13 @@ pub fn handle_create_in_dir_quiet(
     let state = XlaudeState::load()?;
     let key = XlaudeState::make_key(&repo_name, &worktree_name);
     if state.worktrees.contains_key(&key) {
-        anyhow::bail!(
-            "A worktree named '{}' already exists for repository '{}' (tracked by xlaude). Please choose a different name.",
-            worktree_name,
-            repo_name
-        );
+        return Err(coded(
+            ErrorCode::AlreadyExists,
+            format!(
+                "A worktree named '{worktree_name}' already exists for repository '{repo_name}' (tracked by xlaude). Please choose a different name."
+            ),
+        )
+        .into());
     }
 
     // Check if the worktree directory will be created
@@ -109,10 +121,14 @@ pub fn handle_create_in_dir_quiet(
 
     // Check if the directory already exists
     if worktree_dir_path.exists() {
-        anyhow::bail!(
-            "Directory '{}' already exists. Please choose a different name or remove the existing directory.",
-            worktree_dir_path.display()
-        );
+        return Err(coded(
+            ErrorCode::AlreadyExists,
+            format!(
+                "Directory '{}' already exists. Please choose a different name or remove the existing directory.",
+                worktree_dir_path.display()
+            ),
+        )
+        .into());
     }
 
     // Check if a git worktree already exists at this path
@@ -138,10 +154,14 @@ pub fn handle_create_in_dir_quiet(
     };
 
     if existing_worktrees.iter().any(|w| w == &worktree_dir_path) {
-        anyhow::bail!(
-            "A git worktree already exists at '{}'. Please choose a different name or remove the existing worktree.",
-            worktree_dir_path.display()
-        );
+        return Err(coded(
+            ErrorCode::AlreadyExists,
+            format!(
+                "A git worktree already exists at '{}'. Please choose a different name or remove the existing worktree.",
+                worktree_dir_path.display()
+            ),
+        )
+        .into());
     }
 
     // Check if the branch already exists
@@ -154,7 +174,7 @@ pub fn handle_create_in_dir_quiet(
 
     if branch_already_exists {
         if !quiet {
-            println!(
+            say!(
                 "{} Creating worktree '{}' from existing branch '{}'...",
                 "✨".green(),
                 worktree_name.cyan(),
@@ -163,7 +183,7 @@ pub fn handle_create_in_dir_quiet(
         }
     } else {
         if !quiet {
-            println!(
+            say!(
                 "{} Creating worktree '{}' with new branch '{}'...",
                 "✨".green(),
                 worktree_name.cyan(),
@@ -191,6 +211,7 @@ pub fn handle_create_in_dir_quiet(
             // Create branch from current branch (original behavior for CLI)
             exec_git(&["branch", &branch_name]).context("Failed to create branch")?;
         }
+        output::action(format!("created branch {branch_name}"));
     }
 
     // Create worktree with sanitized directory name
@@ -210,10 +231,13 @@ pub fn handle_create_in_dir_quiet(
             .join(format!("{repo_name}-{worktree_name}"))
     };
 
+    output::action(format!("created worktree at {}", worktree_path.display()));
+
     // Update submodules if they exist
     if let Err(e) = update_submodules(&worktree_path) {
+        output::warn(format!("Failed to update submodules: {e}"));
         if !quiet {
-            println!(
+            say!(
                 "{} Warning: Failed to update submodules: {}",
                 "⚠️".yellow(),
                 e
@@ -222,8 +246,11 @@ pub fn handle_create_in_dir_quiet(
     } else {
         // Check if submodules were actually updated
         let gitmodules = worktree_path.join(".gitmodules");
-        if gitmodules.exists() && !quiet {
-            println!("{} Updated submodules", "📦".green());
+        if gitmodules.exists() {
+            output::action("updated submodules");
+            if !quiet {
+                say!("{} Updated submodules", "📦".green());
+            }
         }
     }
 
@@ -236,8 +263,9 @@ pub fn handle_create_in_dir_quiet(
     if claude_local_md.exists() {
         let target_path = worktree_path.join("CLAUDE.local.md");
         fs::copy(claude_local_md, &target_path).context("Failed to copy CLAUDE.local.md")?;
+        output::action("copied CLAUDE.local.md");
         if !quiet {
-            println!("{} Copied CLAUDE.local.md to worktree", "📄".green());
+            say!("{} Copied CLAUDE.local.md to worktree", "📄".green());
         }
     }
 
@@ -250,14 +278,24 @@ pub fn handle_create_in_dir_quiet(
             name: worktree_name.clone(),
             branch: branch_name.clone(),
             path: worktree_path.clone(),
-            repo_name,
+            repo_name: repo_name.clone(),
             created_at: Utc::now(),
+            tags: Vec::new(),
+            tool: None,
+            profile: None,
+            last_run: None,
         },
     );
     state.save()?;
 
+    output::set("name", &worktree_name);
+    output::set("repo", &repo_name);
+    output::set("branch", &branch_name);
+    output::set("branch_created", !branch_already_exists);
+    output::set("path", &worktree_path);
+
     if !quiet {
-        println!(
+        say!(
             "{} Worktree created at: {}",
             "✅".green(),
             worktree_path.display()
@@ -267,12 +305,21 @@ pub fn handle_create_in_dir_quiet(
     // Ask if user wants to open the worktree (skip in quiet mode)
     if !quiet {
         // Skip opening in test mode, when explicitly disabled, or in non-interactive mode with mock Claude
-        let should_open = if std::env::var("XLAUDE_TEST_MODE").is_ok()
+        // Automation reading JSON output never wants an interactive session
+        let should_open = if output::is_json()
+            || std::env::var("XLAUDE_TEST_MODE").is_ok()
             || std::env::var("XLAUDE_NO_AUTO_OPEN").is_ok()
             || (std::env::var("XLAUDE_NON_INTERACTIVE").is_ok()
                 && std::env::var("XLAUDE_CLAUDE_CMD").as_deref() == Ok("true"))
         {
-            println!(
+            if prompt.is_some() {
+                say!(
+                    "{} Not opening the worktree, the prompt was not sent",
+                    "⚠️".yellow()
+                );
+                output::warn("worktree not opened, the prompt was not sent");
+            }
+            say!(
                 "  {} To open it, run: {} {}",
                 "💡".cyan(),
                 "xlaude open".cyan(),
@@ -280,13 +327,18 @@ pub fn handle_create_in_dir_quiet(
             );
             false
         } else {
-            smart_confirm("Would you like to open the worktree now?", true)?
+            // A prompt means the agent should get to work right away
+            prompt.is_some() || smart_confirm("Would you like to open the worktree now?", true)?
         };
 
         if should_open {
-            handle_open(Some(worktree_name.clone()))?;
+            let options = OpenOptions {
+                prompt,
+                ..Default::default()
+            };
+            handle_open(Some(worktree_name.clone()), options)?;
         } else if std::env::var("XLAUDE_NON_INTERACTIVE").is_err() {
-            println!(
+            say!(
                 "  {} To open it later, run: {} {}",
                 "💡".cyan(),
                 "xlaude open".cyan(),
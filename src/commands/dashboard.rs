@@ -15,9 +15,16 @@ use ratatui::{
 use std::io;
 use std::time::Duration;
 
-use crate::claude_status::{ClaudeStatus, ClaudeStatusDetector};
-use crate::state::XlaudeState;
+use crate::ai_tools::{
+    DASHBOARD_TOOL, SessionResume, get_tool, preferred_profile, preferred_tool,
+    worktree_permission_profile, worktree_status_profile,
+};
+use crate::claude_status::{ClaudeStatus, ClaudeStatusDetector, StatusProfile};
+use crate::commands::delete::{TranscriptAction, delete_worktree};
+use crate::session_provider::{SessionInfo as AgentSession, get_sessions, provider, sort_sessions};
+use crate::state::{WorktreeInfo, XlaudeState};
 use crate::tmux::{SessionInfo, TmuxManager};
+use crate::usage::{PriceTable, UsageTotals};
 
 pub struct Dashboard {
     tmux: TmuxManager,
@@ -34,10 +41,24 @@ pub struct Dashboard {
     create_repo: Option<String>, // Repository context for creating worktree
     status_message: Option<String>, // Status message to display
     status_message_timer: u8,    // Timer to clear status message
-    status_detector: ClaudeStatusDetector,
     claude_statuses: std::collections::HashMap<String, ClaudeStatus>,
     config_mode: bool,
     config_editor_input: String,
+    delete_target: Option<String>, // Key of the worktree awaiting delete confirmation
+    usage_totals: std::collections::HashMap<String, UsageTotals>, // Token usage by worktree key
+    session_history: std::collections::HashMap<String, Vec<AgentSession>>, // Agent sessions of all tools by worktree key
+    start_target: Option<StartChoice>, // Stopped worktree awaiting new/continue/resume choice
+}
+
+/// Earlier sessions of the worktree's tool offered when starting a stopped worktree
+struct StartChoice {
+    name: String,
+    /// Name of the tool that will be started
+    tool: String,
+    /// Whether the tool can continue its most recent conversation
+    can_continue: bool,
+    /// Sessions that can be resumed, empty when the tool can't resume by id
+    sessions: Vec<AgentSession>,
 }
 
 struct WorktreeDisplay {
@@ -46,6 +67,8 @@ struct WorktreeDisplay {
     key: String,
     has_session: bool,
     claude_status: ClaudeStatus,
+    /// Permission profile the worktree's tool is started with
+    profile: Option<String>,
 }
 
 impl Dashboard {
@@ -69,10 +92,13 @@ impl Dashboard {
             create_repo: None,
             status_message: None,
             status_message_timer: 0,
-            status_detector: ClaudeStatusDetector::new(),
             claude_statuses: std::collections::HashMap::new(),
             config_mode: false,
             config_editor_input: String::new(),
+            delete_target: None,
+            usage_totals: std::collections::HashMap::new(),
+            session_history: std::collections::HashMap::new(),
+            start_target: None,
         };
 
         dashboard.refresh_worktrees();
@@ -145,6 +171,7 @@ impl Dashboard {
                     .get(&info.name)
                     .cloned()
                     .unwrap_or(ClaudeStatus::NotRunning),
+                profile: worktree_permission_profile(&self.state, info),
             });
         }
 
@@ -170,20 +197,20 @@ impl Dashboard {
         self.claude_statuses.clear();
         for session in &self.sessions {
             // Find the original worktree name
-            let worktree_name = self
-                .state
-                .worktrees
-                .values()
-                .find(|w| {
-                    let safe_name = w.name.replace(['-', '.'], "_");
-                    safe_name == session.project || w.name == session.project
-                })
+            let worktree = self.state.worktrees.values().find(|w| {
+                let safe_name = w.name.replace(['-', '.'], "_");
+                safe_name == session.project || w.name == session.project
+            });
+            let worktree_name = worktree
                 .map(|w| w.name.clone())
                 .unwrap_or(session.project.clone());
+            let profile = worktree.map_or(StatusProfile::Claude, |w| {
+                worktree_status_profile(&self.state, w)
+            });
 
             // Capture pane output and analyze status
             if let Ok(output) = self.tmux.capture_pane(&worktree_name, 100) {
-                let status = self.status_detector.analyze_output(&output);
+                let status = ClaudeStatusDetector::with_profile(profile).analyze_output(&output);
                 self.claude_statuses.insert(worktree_name.clone(), status);
 
                 // Cache preview for inactive sessions
@@ -193,9 +220,35 @@ impl Dashboard {
             }
         }
 
+        self.refresh_history();
+
         Ok(())
     }
 
+    /// Update the session history and token usage of the selected worktree
+    fn refresh_history(&mut self) {
+        let Some((key, info)) = self
+            .list_index_map
+            .get(self.selected)
+            .and_then(|idx| *idx)
+            .and_then(|idx| self.worktrees.get(idx))
+            .and_then(|worktree| self.state.worktrees.get_key_value(&worktree.key))
+        else {
+            return;
+        };
+
+        let sessions = get_sessions(&info.path);
+        let prices = PriceTable::from_state(&self.state);
+        let mut totals = UsageTotals::default();
+        for session in &sessions {
+            for bucket in &session.usage {
+                totals.add(&prices, bucket);
+            }
+        }
+        self.usage_totals.insert(key.clone(), totals);
+        self.session_history.insert(key.clone(), sessions);
+    }
+
     pub fn run(&mut self) -> Result<()> {
         // Setup terminal
         enable_raw_mode()?;
@@ -235,14 +288,14 @@ impl Dashboard {
                 if let Event::Key(key) = event::read()? {
                     match self.handle_input(key)? {
                         InputResult::Exit => break,
-                        InputResult::Attach(project) => {
+                        InputResult::Attach(project, resume) => {
                             // Clean up terminal before attaching
                             disable_raw_mode()?;
                             execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
                             terminal.show_cursor()?;
 
                             // Attach to tmux session
-                            if let Err(e) = self.attach_to_project(&project) {
+                            if let Err(e) = self.attach_to_project(&project, resume) {
                                 eprintln!("Failed to attach: {}", e);
                             }
 
@@ -257,6 +310,33 @@ impl Dashboard {
                             // Refresh state after returning
                             self.refresh()?;
                         }
+                        InputResult::DeleteWorktree(key, transcripts) => {
+                            // Run the deletion in the normal terminal so safety
+                            // checks and prompts are visible
+                            disable_raw_mode()?;
+                            execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+                            terminal.show_cursor()?;
+
+                            self.status_message =
+                                Some(self.delete_from_dashboard(&key, transcripts));
+                            self.status_message_timer = 5;
+
+                            println!();
+                            println!("Press Enter to return to the dashboard...");
+                            let mut line = String::new();
+                            io::stdin().read_line(&mut line)?;
+
+                            enable_raw_mode()?;
+                            execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+                            terminal.hide_cursor()?;
+                            terminal.clear()?;
+
+                            self.refresh()?;
+                            if self.selected >= self.list_index_map.len() {
+                                self.selected = 0;
+                                self.list_state.select(Some(0));
+                            }
+                        }
                         InputResult::CreateWorktree(name, repo) => {
                             // Find the repo path if specified
                             let repo_path = if let Some(repo_name) = &repo {
@@ -285,7 +365,7 @@ impl Dashboard {
                             // Create the worktree quietly in background
                             let created_name =
                                 match crate::commands::create::handle_create_in_dir_quiet(
-                                    name, repo_path, true,
+                                    name, repo_path, true, None,
                                 ) {
                                     Ok(name) => name,
                                     Err(e) => {
@@ -369,6 +449,45 @@ impl Dashboard {
             return Ok(InputResult::Continue);
         }
 
+        // Handle the new/continue/resume choice for a stopped worktree
+        if let Some(choice) = self.start_target.take() {
+            let resume = match key.code {
+                KeyCode::Enter | KeyCode::Char('n' | 'N') => None,
+                KeyCode::Char('c' | 'C') if choice.can_continue => Some(SessionResume::Continue),
+                KeyCode::Char(c @ '1'..='9') => {
+                    let idx = c as usize - '1' as usize;
+                    match choice.sessions.get(idx) {
+                        Some(session) => Some(SessionResume::Resume(Some(session.id.clone()))),
+                        None => {
+                            self.start_target = Some(choice);
+                            return Ok(InputResult::Continue);
+                        }
+                    }
+                }
+                // Any other key cancels
+                _ => return Ok(InputResult::Continue),
+            };
+            return Ok(InputResult::Attach(choice.name, resume));
+        }
+
+        // Handle delete confirmation input
+        if let Some(target) = self.delete_target.take() {
+            match key.code {
+                KeyCode::Char('y' | 'Y') => {
+                    return Ok(InputResult::DeleteWorktree(target, TranscriptAction::Keep));
+                }
+                KeyCode::Char('a' | 'A') => {
+                    return Ok(InputResult::DeleteWorktree(
+                        target,
+                        TranscriptAction::Archive,
+                    ));
+                }
+                // Any other key cancels
+                _ => {}
+            }
+            return Ok(InputResult::Continue);
+        }
+
         // Handle create mode input
         if self.create_mode {
             match key.code {
@@ -396,11 +515,9 @@ impl Dashboard {
                 KeyCode::Backspace => {
                     self.create_input.pop();
                 }
-                KeyCode::Char(c) => {
-                    // Only allow alphanumeric, dash, and underscore
-                    if c.is_alphanumeric() || c == '-' || c == '_' {
-                        self.create_input.push(c);
-                    }
+                // Only allow alphanumeric, dash, and underscore
+                KeyCode::Char(c) if c.is_alphanumeric() || c == '-' || c == '_' => {
+                    self.create_input.push(c);
                 }
                 _ => {}
             }
@@ -414,22 +531,20 @@ impl Dashboard {
             KeyCode::Char('?' | 'h') => {
                 self.show_help = true;
             }
-            KeyCode::Up | KeyCode::Char('k') => {
-                // Move up, skipping repository headers
-                if self.selected > 0 {
-                    let mut prev = self.selected - 1;
-                    loop {
-                        if self.list_index_map[prev].is_some() {
-                            // Found a selectable item
-                            self.selected = prev;
-                            self.list_state.select(Some(self.selected));
-                            break;
-                        }
-                        if prev == 0 {
-                            break;
-                        }
-                        prev -= 1;
+            // Move up, skipping repository headers
+            KeyCode::Up | KeyCode::Char('k') if self.selected > 0 => {
+                let mut prev = self.selected - 1;
+                loop {
+                    if self.list_index_map[prev].is_some() {
+                        // Found a selectable item
+                        self.selected = prev;
+                        self.list_state.select(Some(self.selected));
+                        break;
                     }
+                    if prev == 0 {
+                        break;
+                    }
+                    prev -= 1;
                 }
             }
             KeyCode::Down | KeyCode::Char('j') => {
@@ -450,7 +565,15 @@ impl Dashboard {
                 if let Some(Some(worktree_idx)) = self.list_index_map.get(self.selected)
                     && let Some(worktree) = self.worktrees.get(*worktree_idx)
                 {
-                    return Ok(InputResult::Attach(worktree.name.clone()));
+                    // Offer earlier conversations when starting a stopped session
+                    if !worktree.has_session
+                        && let Some(info) = self.state.worktrees.get(&worktree.key)
+                        && let Some(choice) = self.start_choice(&worktree.name, info)
+                    {
+                        self.start_target = Some(choice);
+                        return Ok(InputResult::Continue);
+                    }
+                    return Ok(InputResult::Attach(worktree.name.clone(), None));
                 }
             }
             KeyCode::Char('n' | 'N') => {
@@ -480,6 +603,14 @@ impl Dashboard {
                     self.refresh()?;
                 }
             }
+            KeyCode::Char('x' | 'X') => {
+                // Ask for confirmation before deleting the worktree
+                if let Some(Some(worktree_idx)) = self.list_index_map.get(self.selected)
+                    && let Some(worktree) = self.worktrees.get(*worktree_idx)
+                {
+                    self.delete_target = Some(worktree.key.clone());
+                }
+            }
             KeyCode::Char('r' | 'R') => {
                 self.refresh()?;
             }
@@ -494,7 +625,40 @@ impl Dashboard {
         Ok(InputResult::Continue)
     }
 
-    fn attach_to_project(&mut self, project: &str) -> Result<()> {
+    /// The choice offered when starting `info`, if its tool has earlier
+    /// sessions there that it can pick up
+    fn start_choice(&self, name: &str, info: &WorktreeInfo) -> Option<StartChoice> {
+        let id = preferred_tool(&self.state, info).unwrap_or_else(|| DASHBOARD_TOOL.to_string());
+        let tool = get_tool(&self.state.tools, &id).ok()?;
+        let can_continue = tool.continue_flag.is_some();
+        if !can_continue && tool.resume_flag.is_none() {
+            return None;
+        }
+
+        let mut sessions = tool
+            .sessions
+            .as_deref()
+            .and_then(provider)
+            .map(|provider| provider.sessions(&info.path))
+            .unwrap_or_default();
+        if sessions.is_empty() {
+            return None;
+        }
+        sort_sessions(&mut sessions);
+        sessions.truncate(9);
+        if tool.resume_flag.is_none() {
+            sessions.clear();
+        }
+
+        Some(StartChoice {
+            name: name.to_string(),
+            tool: tool.name,
+            can_continue,
+            sessions,
+        })
+    }
+
+    fn attach_to_project(&mut self, project: &str, resume: Option<SessionResume>) -> Result<()> {
         // Get worktree info
         let worktree = self
             .worktrees
@@ -502,10 +666,12 @@ impl Dashboard {
             .find(|w| w.name == project)
             .context("Worktree not found")?;
 
+        let key = worktree.key.clone();
         let info = self
             .state
             .worktrees
-            .get(&worktree.key)
+            .get(&key)
+            .cloned()
             .context("Worktree info not found")?;
 
         // Check if path exists
@@ -515,8 +681,29 @@ impl Dashboard {
 
         // Create session if it doesn't exist
         if !self.tmux.session_exists(project) {
+            let id =
+                preferred_tool(&self.state, &info).unwrap_or_else(|| DASHBOARD_TOOL.to_string());
+            let profile = preferred_profile(&self.state, &info);
+            let tool = get_tool(&self.state.tools, &id)?.with_profile(profile.as_deref())?;
             println!("Creating new tmux session for {}...", project);
-            self.tmux.create_session(project, &info.path)?;
+            let args = match &resume {
+                Some(resume) => tool.resume_args(resume)?,
+                None => Vec::new(),
+            };
+            self.tmux.create_session(
+                project,
+                &info.path,
+                &tool.command_line(&args, &info.path),
+                &tool.environment(),
+                tool.profile.as_deref(),
+            )?;
+            // Reopen with the same tool next time
+            if info.tool.as_deref() != Some(tool.id.as_str())
+                && let Some(info) = self.state.worktrees.get_mut(&key)
+            {
+                info.tool = Some(tool.id.clone());
+                self.state.save()?;
+            }
             // Give tmux time to initialize
             std::thread::sleep(std::time::Duration::from_millis(500));
         }
@@ -527,6 +714,19 @@ impl Dashboard {
         Ok(())
     }
 
+    /// Delete a worktree and return a status message for the dashboard
+    fn delete_from_dashboard(&self, key: &str, transcripts: TranscriptAction) -> String {
+        let Some(info) = self.state.worktrees.get(key) else {
+            return format!("Worktree {} not found", key);
+        };
+
+        match delete_worktree(key, info, transcripts) {
+            Ok(true) => format!("🗑️  Deleted worktree: {}", info.name),
+            Ok(false) => format!("Kept worktree: {}", info.name),
+            Err(e) => format!("Failed to delete worktree: {}", e),
+        }
+    }
+
     fn render(&mut self, f: &mut Frame) {
         if self.show_help {
             self.render_help(f);
@@ -590,6 +790,8 @@ impl Dashboard {
                 Span::raw(" New  "),
                 Span::styled("d", Style::default().fg(Color::Yellow)),
                 Span::raw(" Stop  "),
+                Span::styled("x", Style::default().fg(Color::Yellow)),
+                Span::raw(" Delete  "),
                 Span::styled("c", Style::default().fg(Color::Yellow)),
                 Span::raw(" Config  "),
                 Span::styled("r", Style::default().fg(Color::Yellow)),
@@ -611,6 +813,8 @@ impl Dashboard {
                 Span::raw(" New  "),
                 Span::styled("d", Style::default().fg(Color::Yellow)),
                 Span::raw(" Stop  "),
+                Span::styled("x", Style::default().fg(Color::Yellow)),
+                Span::raw(" Delete  "),
                 Span::styled("c", Style::default().fg(Color::Yellow)),
                 Span::raw(" Config  "),
                 Span::styled("r", Style::default().fg(Color::Yellow)),
@@ -633,6 +837,16 @@ impl Dashboard {
         if self.config_mode {
             self.render_config_dialog(f);
         }
+
+        // Render delete confirmation if a worktree is selected for deletion
+        if let Some(key) = &self.delete_target {
+            self.render_delete_dialog(f, key);
+        }
+
+        // Render the start choice if a stopped worktree is being opened
+        if let Some(choice) = &self.start_target {
+            self.render_start_dialog(f, choice);
+        }
     }
 
     fn render_project_list(&mut self, f: &mut Frame, area: Rect) {
@@ -706,8 +920,49 @@ impl Dashboard {
                     ),
                     Span::raw(&worktree.repo),
                 ]),
-                Line::from(""),
             ];
+            if let Some(profile) = &worktree.profile {
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        "Permissions: ",
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(profile),
+                ]));
+            }
+            if let Some(totals) = self.usage_totals.get(&worktree.key)
+                && totals.usage.total() > 0
+            {
+                lines.push(Line::from(vec![
+                    Span::styled("Cost: ", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(totals.summary()),
+                ]));
+            }
+            if let Some(history) = self.session_history.get(&worktree.key)
+                && let Some(latest) = history.first()
+            {
+                // Sessions per tool, in the order the tools were last used
+                let mut tools: Vec<(&str, usize)> = Vec::new();
+                for session in history {
+                    match tools.iter_mut().find(|(tool, _)| *tool == session.tool) {
+                        Some((_, count)) => *count += 1,
+                        None => tools.push((session.tool, 1)),
+                    }
+                }
+                let tools: Vec<String> = tools
+                    .into_iter()
+                    .map(|(tool, count)| format!("{tool} {count}"))
+                    .collect();
+                let when = latest
+                    .last_timestamp
+                    .map(|ts| format!(", last {}", SessionInfo::format_time(ts.timestamp())))
+                    .unwrap_or_default();
+                lines.push(Line::from(vec![
+                    Span::styled("History: ", Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(format!("{}{when}", tools.join(", "))),
+                ]));
+            }
+            lines.push(Line::from(""));
 
             // Add session info (match by safe name)
             let safe_name = worktree.name.replace(['-', '.'], "_");
@@ -818,7 +1073,7 @@ impl Dashboard {
             Line::from(vec![
                 Span::raw("  "),
                 Span::styled("Enter", Style::default().fg(Color::Yellow)),
-                Span::raw("  Open selected project"),
+                Span::raw("  Open selected project (new, continue or resume if stopped)"),
             ]),
             Line::from(""),
             Line::from(vec![Span::styled(
@@ -835,6 +1090,11 @@ impl Dashboard {
                 Span::styled("d", Style::default().fg(Color::Yellow)),
                 Span::raw("      Stop Claude session"),
             ]),
+            Line::from(vec![
+                Span::raw("  "),
+                Span::styled("x", Style::default().fg(Color::Yellow)),
+                Span::raw("      Delete worktree, branch and session"),
+            ]),
             Line::from(vec![
                 Span::raw("  "),
                 Span::styled("r", Style::default().fg(Color::Yellow)),
@@ -933,6 +1193,115 @@ impl Dashboard {
         f.render_widget(dialog, area);
     }
 
+    fn render_start_dialog(&self, f: &mut Frame, choice: &StartChoice) {
+        // Calculate dialog area (centered, 70% width, 60% height)
+        let area = centered_rect(70, 60, f.area());
+
+        // Clear the dialog area
+        let clear = ratatui::widgets::Clear;
+        f.render_widget(clear, area);
+
+        let mut lines = vec![
+            Line::from(""),
+            Line::from(Span::styled(
+                format!("Start {} in {}", choice.tool, choice.name),
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+        ];
+        let mut keys = vec![
+            Span::styled("n", Style::default().fg(Color::Green)),
+            Span::raw(" new conversation"),
+        ];
+        if choice.can_continue {
+            keys.push(Span::styled("  c", Style::default().fg(Color::Green)));
+            keys.push(Span::raw(" continue the most recent one"));
+        }
+        lines.push(Line::from(keys));
+        lines.push(Line::from(""));
+        for (i, session) in choice.sessions.iter().enumerate() {
+            let when = session
+                .last_timestamp
+                .map(|ts| SessionInfo::format_time(ts.timestamp()))
+                .unwrap_or_else(|| "unknown".to_string());
+            let message: String = session
+                .last_user_message
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .chars()
+                .take(50)
+                .collect();
+            lines.push(Line::from(vec![
+                Span::styled(format!("{} ", i + 1), Style::default().fg(Color::Green)),
+                Span::styled(format!("{when:>10} "), Style::default().fg(Color::DarkGray)),
+                Span::raw(message),
+            ]));
+        }
+        lines.push(Line::from(""));
+        let mut keys = Vec::new();
+        if !choice.sessions.is_empty() {
+            keys.push(Span::styled("1-9", Style::default().fg(Color::Green)));
+            keys.push(Span::raw(" resume that session  "));
+        }
+        keys.push(Span::styled("Esc", Style::default().fg(Color::Red)));
+        keys.push(Span::raw(" to cancel"));
+        lines.push(Line::from(keys));
+
+        let dialog = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Start Session ")
+                    .border_style(Style::default().fg(Color::Blue)),
+            )
+            .wrap(Wrap { trim: true });
+
+        f.render_widget(dialog, area);
+    }
+
+    fn render_delete_dialog(&self, f: &mut Frame, key: &str) {
+        // Calculate dialog area (centered, 50% width, 30% height)
+        let area = centered_rect(50, 30, f.area());
+
+        // Clear the dialog area
+        let clear = ratatui::widgets::Clear;
+        f.render_widget(clear, area);
+
+        let lines = vec![
+            Line::from(""),
+            Line::from(Span::styled(
+                format!("Delete worktree {}?", key),
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+            Line::from("This stops the session and removes the worktree and branch."),
+            Line::from("Safety checks still run before anything is removed."),
+            Line::from(""),
+            Line::from(""),
+            Line::from(vec![
+                Span::styled("y", Style::default().fg(Color::Green)),
+                Span::raw(" to delete  "),
+                Span::styled("a", Style::default().fg(Color::Green)),
+                Span::raw(" to delete and archive transcripts  "),
+                Span::styled("Esc", Style::default().fg(Color::Red)),
+                Span::raw(" to cancel"),
+            ]),
+        ];
+
+        let dialog = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Delete Worktree ")
+                    .border_style(Style::default().fg(Color::Red)),
+            )
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+
+        f.render_widget(dialog, area);
+    }
+
     fn render_config_dialog(&self, f: &mut Frame) {
         // Calculate dialog area (centered, 60% width, 40% height)
         let area = centered_rect(60, 40, f.area());
@@ -992,8 +1361,9 @@ impl Dashboard {
 
 enum InputResult {
     Exit,
-    Attach(String),
+    Attach(String, Option<SessionResume>), // worktree name and conversation to pick up
     CreateWorktree(Option<String>, Option<String>), // optional name and optional repo context
+    DeleteWorktree(String, TranscriptAction), // worktree key and transcript handling
     Continue,
 }
 
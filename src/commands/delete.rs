@@ -1,15 +1,37 @@
 use anyhow::{Context, Result};
+use clap::ValueEnum;
 use colored::Colorize;
-
-use crate::git::{execute_git, has_unpushed_commits, is_working_tree_clean};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::ai_tools::known_tool_commands;
+use crate::claude::claude_project_dir;
+use crate::forge::{PrState, find_pull_request};
+use crate::git::{
+    count_local_only_commits, execute_git, has_unpushed_commits, is_working_tree_clean,
+    list_ignored_files, list_stashes_for_branch,
+};
 use crate::input::{get_command_arg, smart_confirm};
-use crate::state::{WorktreeInfo, XlaudeState};
-use crate::utils::execute_in_dir;
+use crate::output::{self, ErrorCode, coded, say};
+use crate::state::{WorktreeInfo, XlaudeState, get_config_dir};
+use crate::tmux::TmuxManager;
+use crate::utils::{ProcessInfo, copy_dir_all, execute_in_dir, find_processes_in_dir, glob_match};
+
+/// Ignored files matching these patterns are reported before deletion
+/// unless `protected_patterns` is set in the xlaude config
+const DEFAULT_PROTECTED_PATTERNS: &[&str] = &[".env", ".env.*", "*.pem", "*.key"];
 
 /// Represents the result of various checks performed before deletion
 struct DeletionChecks {
     has_uncommitted_changes: bool,
     has_unpushed_commits: bool,
+    local_only_commits: usize,
+    branch_stashes: Vec<String>,
+    protected_files: Vec<String>,
+    tmux_session_running: bool,
+    agent_processes: Vec<ProcessInfo>,
+    is_shell_cwd: bool,
     branch_merged_via_git: bool,
     branch_merged_via_pr: bool,
 }
@@ -20,8 +42,68 @@ impl DeletionChecks {
     }
 
     fn has_pending_work(&self) -> bool {
-        self.has_uncommitted_changes || self.has_unpushed_commits
+        self.has_uncommitted_changes
+            || self.has_unpushed_commits
+            || self.local_only_commits > 0
+            || !self.branch_stashes.is_empty()
+            || !self.protected_files.is_empty()
+    }
+
+    fn has_running_agent(&self) -> bool {
+        self.tmux_session_running || !self.agent_processes.is_empty()
     }
+
+    fn has_risks(&self) -> bool {
+        self.has_pending_work() || self.has_running_agent() || self.is_shell_cwd
+    }
+
+    /// One plain-text line per risk, for machine-readable output
+    fn warnings(&self, worktree_info: &WorktreeInfo) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.has_uncommitted_changes {
+            warnings.push("Uncommitted changes".to_string());
+        }
+        if self.has_unpushed_commits {
+            warnings.push("Unpushed commits".to_string());
+        }
+        if self.local_only_commits > 0 {
+            warnings.push(format!(
+                "{} commit(s) on '{}' are not on any remote or other branch",
+                self.local_only_commits, worktree_info.branch
+            ));
+        }
+        for stash in &self.branch_stashes {
+            warnings.push(format!("Stash references branch: {stash}"));
+        }
+        for file in &self.protected_files {
+            warnings.push(format!("Protected ignored file: {file}"));
+        }
+        if self.tmux_session_running {
+            warnings.push("tmux session is running".to_string());
+        }
+        for process in &self.agent_processes {
+            warnings.push(format!(
+                "Agent process '{}' (pid {}) is running",
+                process.command, process.pid
+            ));
+        }
+        if self.is_shell_cwd {
+            warnings.push("Shell is inside this worktree".to_string());
+        }
+        warnings
+    }
+}
+
+/// What to do with the Claude transcripts of a deleted worktree
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum TranscriptAction {
+    /// Leave transcripts in the Claude projects directory
+    #[default]
+    Keep,
+    /// Move transcripts into the xlaude archive directory
+    Archive,
+    /// Delete transcripts permanently
+    Remove,
 }
 
 /// Configuration for deletion behavior
@@ -29,29 +111,81 @@ struct DeletionConfig {
     is_interactive: bool,
     worktree_exists: bool,
     is_current_directory: bool,
+    /// Force delete the branch without asking when it is not fully merged
+    force_branch_delete: bool,
+    transcripts: TranscriptAction,
 }
 
 impl DeletionConfig {
-    fn from_env(worktree_info: &WorktreeInfo) -> Result<Self> {
+    fn from_env(worktree_info: &WorktreeInfo, transcripts: TranscriptAction) -> Result<Self> {
         let current_dir = std::env::current_dir()?;
 
         Ok(Self {
             is_interactive: std::env::var("XLAUDE_NON_INTERACTIVE").is_err(),
             worktree_exists: worktree_info.path.exists(),
-            is_current_directory: current_dir == worktree_info.path,
+            is_current_directory: current_dir.starts_with(&worktree_info.path),
+            force_branch_delete: false,
+            transcripts,
         })
     }
 }
 
-pub fn handle_delete(name: Option<String>) -> Result<()> {
-    let mut state = XlaudeState::load()?;
+/// How tearing down a single artifact went
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum ArtifactStatus {
+    Done,
+    Skipped,
+    Failed,
+}
+
+/// Result of tearing down one artifact of a worktree
+#[derive(Serialize)]
+struct ArtifactResult {
+    artifact: &'static str,
+    status: ArtifactStatus,
+    detail: String,
+}
+
+impl ArtifactResult {
+    fn new(artifact: &'static str, status: ArtifactStatus, detail: impl Into<String>) -> Self {
+        Self {
+            artifact,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+pub fn handle_delete(name: Option<String>, transcripts: TranscriptAction) -> Result<()> {
+    let state = XlaudeState::load()?;
 
     // Get name from CLI args or pipe
     let target_name = get_command_arg(name)?;
     let (key, worktree_info) = find_worktree_to_delete(&state, target_name)?;
-    let config = DeletionConfig::from_env(&worktree_info)?;
 
-    println!(
+    delete_worktree(&key, &worktree_info, transcripts)?;
+    Ok(())
+}
+
+/// Run the safety checks, confirm and delete a worktree with all its artifacts.
+///
+/// Returns `false` if the user cancelled.
+pub fn delete_worktree(
+    key: &str,
+    worktree_info: &WorktreeInfo,
+    transcripts: TranscriptAction,
+) -> Result<bool> {
+    let mut state = XlaudeState::load()?;
+    let config = DeletionConfig::from_env(worktree_info, transcripts)?;
+
+    output::set("name", &worktree_info.name);
+    output::set("repo", &worktree_info.repo_name);
+    output::set("branch", &worktree_info.branch);
+    output::set("path", &worktree_info.path);
+    output::set("deleted", false);
+
+    say!(
         "{} Checking worktree '{}'...",
         "🔍".yellow(),
         worktree_info.name.cyan()
@@ -59,39 +193,64 @@ pub fn handle_delete(name: Option<String>) -> Result<()> {
 
     // Handle case where worktree directory doesn't exist
     if !config.worktree_exists {
-        if !handle_missing_worktree(&worktree_info, &config)? {
-            println!("{} Cancelled", "❌".red());
-            return Ok(());
+        if !handle_missing_worktree(worktree_info, &config)? {
+            say!("{} Cancelled", "❌".red());
+            return Ok(false);
         }
     } else {
         // Check branch status first (for output consistency)
-        println!(
+        say!(
             "{} Checking branch '{}'...",
             "🔍".yellow(),
             worktree_info.branch
         );
 
         // Perform deletion checks
-        let checks = perform_deletion_checks(&worktree_info)?;
+        let checks = perform_deletion_checks(worktree_info, &state, &config)?;
 
-        if !confirm_deletion(&worktree_info, &checks, &config)? {
-            println!("{} Cancelled", "❌".red());
-            return Ok(());
+        if !confirm_deletion(worktree_info, &checks, &config)? {
+            say!("{} Cancelled", "❌".red());
+            return Ok(false);
         }
     }
 
     // Execute deletion
-    perform_deletion(&worktree_info, &config)?;
+    let results = perform_deletion(worktree_info, &config)?;
 
     // Update state
-    state.worktrees.remove(&key);
+    state.worktrees.remove(key);
     state.save()?;
 
-    println!(
+    output::set("deleted", true);
+    output::set("artifacts", &results);
+    show_artifact_results(&results);
+    say!(
         "{} Worktree '{}' deleted successfully",
         "✅".green(),
         worktree_info.name.cyan()
     );
+    Ok(true)
+}
+
+/// Delete a worktree and its branch without safety prompts.
+///
+/// Callers are responsible for deciding that the worktree is safe to remove
+/// and for updating the state afterwards.
+pub fn delete_worktree_unattended(
+    worktree_info: &WorktreeInfo,
+    force_branch_delete: bool,
+) -> Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let config = DeletionConfig {
+        is_interactive: false,
+        worktree_exists: worktree_info.path.exists(),
+        is_current_directory: current_dir.starts_with(&worktree_info.path),
+        force_branch_delete,
+        transcripts: TranscriptAction::Keep,
+    };
+
+    let results = perform_deletion(worktree_info, &config)?;
+    show_artifact_results(&results);
     Ok(())
 }
 
@@ -107,7 +266,10 @@ fn find_worktree_to_delete(
             .iter()
             .find(|(_, w)| w.name == n)
             .map(|(k, w)| (k.clone(), w.clone()))
-            .context(format!("Worktree '{n}' not found"))
+            .context(coded(
+                ErrorCode::NotFound,
+                format!("Worktree '{n}' not found"),
+            ))
     } else {
         // Find worktree by current directory
         find_current_worktree(state)
@@ -127,17 +289,24 @@ fn find_current_worktree(state: &XlaudeState) -> Result<(String, WorktreeInfo)>
         .iter()
         .find(|(_, w)| w.path.file_name().and_then(|n| n.to_str()) == Some(dir_name))
         .map(|(k, w)| (k.clone(), w.clone()))
-        .context("Current directory is not a managed worktree")
+        .context(coded(
+            ErrorCode::NotFound,
+            "Current directory is not a managed worktree",
+        ))
 }
 
 /// Handle the case where worktree directory doesn't exist
 fn handle_missing_worktree(worktree_info: &WorktreeInfo, _config: &DeletionConfig) -> Result<bool> {
-    println!(
+    output::warn(format!(
+        "Worktree directory not found at {}",
+        worktree_info.path.display()
+    ));
+    say!(
         "{} Worktree directory not found at {}",
         "⚠️ ".yellow(),
         worktree_info.path.display()
     );
-    println!(
+    say!(
         "  {} The worktree may have been manually deleted",
         "ℹ️".blue()
     );
@@ -146,10 +315,31 @@ fn handle_missing_worktree(worktree_info: &WorktreeInfo, _config: &DeletionConfi
 }
 
 /// Perform all checks needed before deletion
-fn perform_deletion_checks(worktree_info: &WorktreeInfo) -> Result<DeletionChecks> {
+fn perform_deletion_checks(
+    worktree_info: &WorktreeInfo,
+    state: &XlaudeState,
+    config: &DeletionConfig,
+) -> Result<DeletionChecks> {
+    let patterns: Vec<String> = state.protected_patterns.clone().unwrap_or_else(|| {
+        DEFAULT_PROTECTED_PATTERNS
+            .iter()
+            .map(|p| p.to_string())
+            .collect()
+    });
+
     execute_in_dir(&worktree_info.path, || {
         let has_uncommitted_changes = !is_working_tree_clean()?;
         let has_unpushed_commits = has_unpushed_commits();
+        let local_only_commits = count_local_only_commits(&worktree_info.branch).unwrap_or(0);
+        let branch_stashes = list_stashes_for_branch(&worktree_info.branch).unwrap_or_default();
+        let protected_files = find_protected_files(&patterns);
+
+        let tmux_session_running = TmuxManager::new().session_exists(&worktree_info.name);
+        let agent_commands = known_tool_commands(&state.tools);
+        let agent_processes = find_processes_in_dir(&worktree_info.path)
+            .into_iter()
+            .filter(|p| agent_commands.contains(&p.command))
+            .collect();
 
         // Check branch merge status in main repo
         let main_repo_path = get_main_repo_path(worktree_info)?;
@@ -159,12 +349,37 @@ fn perform_deletion_checks(worktree_info: &WorktreeInfo) -> Result<DeletionCheck
         Ok(DeletionChecks {
             has_uncommitted_changes,
             has_unpushed_commits,
+            local_only_commits,
+            branch_stashes,
+            protected_files,
+            tmux_session_running,
+            agent_processes,
+            is_shell_cwd: config.is_current_directory,
             branch_merged_via_git,
             branch_merged_via_pr,
         })
     })
 }
 
+/// Find ignored files in the current worktree that match any protected pattern
+fn find_protected_files(patterns: &[String]) -> Vec<String> {
+    if patterns.is_empty() {
+        return vec![];
+    }
+
+    list_ignored_files()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|path| {
+            let trimmed = path.trim_end_matches('/');
+            let file_name = trimmed.rsplit('/').next().unwrap_or(trimmed);
+            patterns
+                .iter()
+                .any(|pattern| glob_match(pattern, trimmed) || glob_match(pattern, file_name))
+        })
+        .collect()
+}
+
 /// Check if branch is merged via git or PR
 fn check_branch_merge_status(
     main_repo_path: &std::path::Path,
@@ -182,38 +397,26 @@ fn check_branch_merge_status(
             .lines()
             .any(|line| line.trim().trim_start_matches('*').trim() == branch);
 
-        // Check if merged via PR (works for squash merge)
-        let is_merged_pr = check_branch_merged_via_pr(branch);
+        // Check if merged via PR on the repository's forge (works for squash merge)
+        let is_merged_pr =
+            find_pull_request(main_repo_path, branch).is_some_and(|pr| pr.state == PrState::Merged);
 
         Ok((is_merged_git, is_merged_pr))
     })
 }
 
-/// Check if branch was merged via GitHub PR
-fn check_branch_merged_via_pr(branch: &str) -> bool {
-    std::process::Command::new("gh")
-        .args([
-            "pr", "list", "--state", "merged", "--head", branch, "--json", "number",
-        ])
-        .output()
-        .ok()
-        .filter(|output| output.status.success())
-        .and_then(|output| String::from_utf8(output.stdout).ok())
-        .and_then(|json| serde_json::from_str::<Vec<serde_json::Value>>(&json).ok())
-        .map(|prs| !prs.is_empty())
-        .unwrap_or(false)
-}
-
 /// Confirm deletion with the user based on checks
 fn confirm_deletion(
     worktree_info: &WorktreeInfo,
     checks: &DeletionChecks,
     _config: &DeletionConfig,
 ) -> Result<bool> {
-    // Show warnings for pending work
-    if checks.has_pending_work() {
-        show_pending_work_warnings(checks);
+    if checks.has_risks() {
+        show_risk_summary(worktree_info, checks);
+    }
 
+    // Work could be lost or an agent is still running, default to no
+    if checks.has_pending_work() || checks.has_running_agent() {
         return smart_confirm("Are you sure you want to delete this worktree?", false);
     }
 
@@ -221,37 +424,113 @@ fn confirm_deletion(
     if !checks.branch_is_merged() {
         show_unmerged_branch_warning(worktree_info);
     } else if checks.branch_merged_via_pr && !checks.branch_merged_via_git {
-        println!("  {} Branch was merged via PR", "ℹ️".blue());
+        say!("  {} Branch was merged via PR", "ℹ️".blue());
     }
 
     // Ask for confirmation
     smart_confirm(&format!("Delete worktree '{}'?", worktree_info.name), true)
 }
 
-/// Show warnings for uncommitted changes or unpushed commits
-fn show_pending_work_warnings(checks: &DeletionChecks) {
-    println!();
+/// Show every risk found by the deletion checks in one summary
+fn show_risk_summary(worktree_info: &WorktreeInfo, checks: &DeletionChecks) {
+    for warning in checks.warnings(worktree_info) {
+        output::warn(warning);
+    }
+
+    say!();
     if checks.has_uncommitted_changes {
-        println!("{} You have uncommitted changes", "⚠️ ".red());
+        say!("{} You have uncommitted changes", "⚠️ ".red());
     }
     if checks.has_unpushed_commits {
-        println!("{} You have unpushed commits", "⚠️ ".red());
+        say!("{} You have unpushed commits", "⚠️ ".red());
+    }
+    if checks.local_only_commits > 0 {
+        say!(
+            "{} {} commit(s) on '{}' are not on any remote or other branch",
+            "⚠️ ".red(),
+            checks.local_only_commits,
+            worktree_info.branch
+        );
+    }
+    if !checks.branch_stashes.is_empty() {
+        say!(
+            "{} {} stash entr{} reference branch '{}'",
+            "⚠️ ".red(),
+            checks.branch_stashes.len(),
+            if checks.branch_stashes.len() == 1 {
+                "y"
+            } else {
+                "ies"
+            },
+            worktree_info.branch
+        );
+        for stash in &checks.branch_stashes {
+            say!("    {}", stash.bright_black());
+        }
+    }
+    if !checks.protected_files.is_empty() {
+        say!(
+            "{} Ignored files matching protected patterns will be removed:",
+            "⚠️ ".red()
+        );
+        for file in &checks.protected_files {
+            say!("    {}", file.bright_black());
+        }
+    }
+    if checks.tmux_session_running {
+        say!(
+            "{} A tmux session is still running for '{}'",
+            "⚠️ ".yellow(),
+            worktree_info.name
+        );
+    }
+    for process in &checks.agent_processes {
+        say!(
+            "{} Agent process '{}' (pid {}) is running in this worktree",
+            "⚠️ ".yellow(),
+            process.command,
+            process.pid
+        );
+    }
+    if checks.is_shell_cwd {
+        say!(
+            "  {} Your shell is inside this worktree; run 'cd {}' afterwards",
+            "ℹ️".blue(),
+            worktree_info
+                .path
+                .parent()
+                .map_or_else(|| "..".to_string(), |p| p.display().to_string())
+        );
     }
 }
 
 /// Show warning for unmerged branch
 fn show_unmerged_branch_warning(worktree_info: &WorktreeInfo) {
-    println!(
+    output::warn(format!(
+        "Branch '{}' is not fully merged",
+        worktree_info.branch
+    ));
+    say!(
         "{} Branch '{}' is not fully merged",
         "⚠️ ".yellow(),
         worktree_info.branch.cyan()
     );
-    println!("  {} No merged PR found for this branch", "ℹ️".blue());
+    say!("  {} No merged PR found for this branch", "ℹ️".blue());
 }
 
-/// Perform the actual deletion of worktree and branch
-fn perform_deletion(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Result<()> {
+/// Perform the actual deletion of the worktree, its branch and other artifacts
+fn perform_deletion(
+    worktree_info: &WorktreeInfo,
+    config: &DeletionConfig,
+) -> Result<Vec<ArtifactResult>> {
     let main_repo_path = get_main_repo_path(worktree_info)?;
+    let mut results = Vec::new();
+
+    // Stop the agent before its working directory disappears
+    results.push(stop_tmux_session(worktree_info));
+
+    // Resolve the transcript directory while the worktree path still exists
+    let transcript_dir = claude_project_dir(&worktree_info.path).filter(|dir| dir.exists());
 
     // Change to main repo if we're deleting current directory
     if config.is_current_directory {
@@ -261,26 +540,118 @@ fn perform_deletion(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Re
 
     execute_in_dir(&main_repo_path, || {
         // Remove or prune worktree
-        remove_worktree(worktree_info, config)?;
+        results.push(remove_worktree(worktree_info, config)?);
 
         // Delete branch
-        delete_branch(worktree_info, config)?;
+        results.push(delete_branch(worktree_info, config)?);
 
         Ok(())
-    })
+    })?;
+
+    results.push(handle_transcripts(
+        worktree_info,
+        transcript_dir.as_deref(),
+        config.transcripts,
+    ));
+
+    Ok(results)
+}
+
+/// Kill the dashboard tmux session of the worktree if it is running
+fn stop_tmux_session(worktree_info: &WorktreeInfo) -> ArtifactResult {
+    let tmux = TmuxManager::new();
+    if !tmux.session_exists(&worktree_info.name) {
+        return ArtifactResult::new("tmux session", ArtifactStatus::Skipped, "not running");
+    }
+
+    match tmux.kill_session(&worktree_info.name) {
+        Ok(()) => ArtifactResult::new("tmux session", ArtifactStatus::Done, "stopped"),
+        Err(e) => ArtifactResult::new("tmux session", ArtifactStatus::Failed, e.to_string()),
+    }
+}
+
+/// Keep, archive or remove the Claude project directory of the worktree
+fn handle_transcripts(
+    worktree_info: &WorktreeInfo,
+    transcript_dir: Option<&Path>,
+    action: TranscriptAction,
+) -> ArtifactResult {
+    let Some(dir) = transcript_dir else {
+        return ArtifactResult::new("transcripts", ArtifactStatus::Skipped, "none found");
+    };
+
+    let result = match action {
+        TranscriptAction::Keep => {
+            return ArtifactResult::new(
+                "transcripts",
+                ArtifactStatus::Skipped,
+                format!("kept at {}", dir.display()),
+            );
+        }
+        TranscriptAction::Archive => archive_transcripts(worktree_info, dir)
+            .map(|archive| format!("archived to {}", archive.display())),
+        TranscriptAction::Remove => fs::remove_dir_all(dir)
+            .map(|()| "removed".to_string())
+            .context("Failed to remove transcripts"),
+    };
+
+    match result {
+        Ok(detail) => ArtifactResult::new("transcripts", ArtifactStatus::Done, detail),
+        Err(e) => ArtifactResult::new("transcripts", ArtifactStatus::Failed, format!("{e:#}")),
+    }
+}
+
+/// Move a Claude project directory into `<config>/archive/transcripts`
+fn archive_transcripts(worktree_info: &WorktreeInfo, dir: &Path) -> Result<PathBuf> {
+    let archive_root = get_config_dir()?.join("archive").join("transcripts");
+    fs::create_dir_all(&archive_root).context("Failed to create archive directory")?;
+
+    let archive = archive_root.join(format!(
+        "{}-{}-{}",
+        worktree_info.repo_name,
+        worktree_info.name.replace('/', "-"),
+        chrono::Utc::now().format("%Y%m%d%H%M%S")
+    ));
+
+    // Renaming fails across filesystems, fall back to copying
+    if fs::rename(dir, &archive).is_err() {
+        copy_dir_all(dir, &archive).context("Failed to archive transcripts")?;
+        fs::remove_dir_all(dir).context("Failed to remove archived transcripts")?;
+    }
+
+    Ok(archive)
+}
+
+/// Print what happened to each artifact of the deleted worktree
+fn show_artifact_results(results: &[ArtifactResult]) {
+    say!("{} Cleanup results:", "📋".cyan());
+    for result in results {
+        let icon = match result.status {
+            ArtifactStatus::Done => "✅".green(),
+            ArtifactStatus::Skipped => "➖".normal(),
+            ArtifactStatus::Failed => "❌".red(),
+        };
+        say!("  {} {}: {}", icon, result.artifact, result.detail);
+        if result.status == ArtifactStatus::Done {
+            output::action(format!("{}: {}", result.artifact, result.detail));
+        }
+    }
 }
 
 /// Remove the worktree from git
-fn remove_worktree(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Result<()> {
+fn remove_worktree(
+    worktree_info: &WorktreeInfo,
+    config: &DeletionConfig,
+) -> Result<ArtifactResult> {
     if config.worktree_exists {
-        println!("{} Removing worktree...", "🗑️ ".yellow());
+        say!("{} Removing worktree...", "🗑️ ".yellow());
 
         // First attempt: try normal removal
         let result = execute_git(&["worktree", "remove", worktree_info.path.to_str().unwrap()]);
 
         // If failed, might be due to submodules - try with force flag
         if result.is_err() {
-            println!(
+            say!(
                 "{} Standard removal failed, trying force removal...",
                 "⚠️ ".yellow()
             );
@@ -292,16 +663,25 @@ fn remove_worktree(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Res
             ])
             .context("Failed to force remove worktree")?;
         }
+        Ok(ArtifactResult::new(
+            "worktree",
+            ArtifactStatus::Done,
+            "removed",
+        ))
     } else {
-        println!("{} Pruning non-existent worktree...", "🗑️ ".yellow());
+        say!("{} Pruning non-existent worktree...", "🗑️ ".yellow());
         execute_git(&["worktree", "prune"]).context("Failed to prune worktree")?;
+        Ok(ArtifactResult::new(
+            "worktree",
+            ArtifactStatus::Done,
+            "pruned",
+        ))
     }
-    Ok(())
 }
 
 /// Delete the branch from git
-fn delete_branch(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Result<()> {
-    println!(
+fn delete_branch(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Result<ArtifactResult> {
+    say!(
         "{} Deleting branch '{}'...",
         "🗑️ ".yellow(),
         worktree_info.branch
@@ -309,31 +689,53 @@ fn delete_branch(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Resul
 
     // First try safe delete
     if execute_git(&["branch", "-d", &worktree_info.branch]).is_ok() {
-        println!("{} Branch deleted", "✅".green());
-        return Ok(());
+        return Ok(ArtifactResult::new(
+            "branch",
+            ArtifactStatus::Done,
+            "deleted",
+        ));
     }
 
-    // Branch is not fully merged, ask for force delete
-    if !config.is_interactive {
-        println!("{} Branch kept (not fully merged)", "ℹ️ ".blue());
-        return Ok(());
-    }
-
-    let force_delete = smart_confirm("Branch is not fully merged. Force delete?", false)?;
+    // Branch is not fully merged, force delete if requested or confirmed
+    let force_delete = config.force_branch_delete
+        || (config.is_interactive
+            && smart_confirm("Branch is not fully merged. Force delete?", false)?);
 
     if force_delete {
         execute_git(&["branch", "-D", &worktree_info.branch])
             .context("Failed to force delete branch")?;
-        println!("{} Branch force deleted", "✅".green());
+        Ok(ArtifactResult::new(
+            "branch",
+            ArtifactStatus::Done,
+            "force deleted",
+        ))
     } else {
-        println!("{} Branch kept", "ℹ️ ".blue());
+        Ok(ArtifactResult::new(
+            "branch",
+            ArtifactStatus::Skipped,
+            "kept (not fully merged)",
+        ))
     }
-
-    Ok(())
 }
 
 /// Get the path to the main repository from worktree info
-fn get_main_repo_path(worktree_info: &WorktreeInfo) -> Result<std::path::PathBuf> {
+///
+/// Asks git for the common git directory of the worktree, so worktrees
+/// anywhere on disk are handled. Falls back to the sibling directory xlaude
+/// creates worktrees next to when the worktree is gone.
+pub fn get_main_repo_path(worktree_info: &WorktreeInfo) -> Result<std::path::PathBuf> {
+    if let Some(path) = worktree_info.path.to_str()
+        && let Ok(common_dir) = execute_git(&["-C", path, "rev-parse", "--git-common-dir"])
+    {
+        // The common dir is relative to the worktree unless it is elsewhere
+        let common_dir = worktree_info.path.join(common_dir);
+        if common_dir.file_name().is_some_and(|name| name == ".git")
+            && let Some(repo) = common_dir.parent()
+        {
+            return Ok(repo.to_path_buf());
+        }
+    }
+
     let parent = worktree_info
         .path
         .parent()
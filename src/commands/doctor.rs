@@ -0,0 +1,568 @@
+use anyhow::Result;
+use colored::Colorize;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+use crate::ai_tools::all_tools;
+use crate::claude::{claude_config_dir, claude_project_dir};
+use crate::executables::{find_executable, probe_version};
+use crate::output::{self, ErrorCode, coded, say};
+use crate::state::{XlaudeState, get_config_dir};
+use crate::tmux::TmuxManager;
+
+/// Oldest git with every `git worktree` subcommand xlaude uses (`remove`)
+const MIN_GIT_VERSION: (u32, u32) = (2, 17);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Level {
+    Ok,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Level {
+    fn icon(self) -> colored::ColoredString {
+        match self {
+            Level::Ok => "✅".green(),
+            Level::Info => "ℹ️".blue(),
+            Level::Warning => "⚠️".yellow(),
+            Level::Error => "❌".red(),
+        }
+    }
+}
+
+/// One finding, with what to do about it when something is wrong
+#[derive(Debug, Serialize)]
+struct Check {
+    section: &'static str,
+    name: String,
+    level: Level,
+    detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fix: Option<String>,
+}
+
+#[derive(Default)]
+struct Checks(Vec<Check>);
+
+impl Checks {
+    fn add(
+        &mut self,
+        section: &'static str,
+        level: Level,
+        name: impl Into<String>,
+        detail: impl Into<String>,
+        fix: Option<String>,
+    ) {
+        self.0.push(Check {
+            section,
+            name: name.into(),
+            level,
+            detail: detail.into(),
+            fix,
+        });
+    }
+
+    fn count(&self, level: Level) -> usize {
+        self.0.iter().filter(|check| check.level == level).count()
+    }
+}
+
+pub fn handle_doctor() -> Result<()> {
+    let mut checks = Checks::default();
+
+    check_git(&mut checks);
+    let tmux = check_tmux(&mut checks);
+    // Later checks need the state, which may be what is broken
+    let state = check_config(&mut checks);
+    if let Some(state) = &state {
+        check_tools(&mut checks, state);
+        check_claude(&mut checks, state);
+        check_orphans(&mut checks, state, tmux);
+    }
+
+    let errors = checks.count(Level::Error);
+    let warnings = checks.count(Level::Warning);
+    output::set("checks", &checks.0);
+    output::set("errors", errors);
+    output::set("warnings", warnings);
+
+    let mut section = "";
+    for check in &checks.0 {
+        if check.section != section {
+            section = check.section;
+            say!();
+            say!("{}", section.bold());
+        }
+        say!("  {} {}: {}", check.level.icon(), check.name, check.detail);
+        if let Some(fix) = &check.fix {
+            say!("     {} {}", "→".bright_black(), fix);
+        }
+    }
+    say!();
+
+    if errors > 0 {
+        return Err(coded(
+            ErrorCode::InvalidState,
+            format!("{errors} problem(s) and {warnings} warning(s) found"),
+        )
+        .into());
+    }
+    if warnings > 0 {
+        say!("{} No problems, {} warning(s)", "⚠️".yellow(), warnings);
+    } else {
+        say!("{} Everything looks good", "✅".green());
+    }
+    Ok(())
+}
+
+fn check_git(checks: &mut Checks) {
+    const SECTION: &str = "Git";
+    let Some(git) = find_executable("git") else {
+        checks.add(
+            SECTION,
+            Level::Error,
+            "git",
+            "not found in PATH",
+            Some("Install git 2.17 or newer".to_string()),
+        );
+        return;
+    };
+
+    let version = probe_version(&git, "--version");
+    checks.add(
+        SECTION,
+        Level::Ok,
+        "git",
+        format!(
+            "{} ({})",
+            version.as_deref().unwrap_or("version unknown"),
+            git.display()
+        ),
+        None,
+    );
+
+    match version.as_deref().and_then(parse_git_version) {
+        Some(found) if found < MIN_GIT_VERSION => checks.add(
+            SECTION,
+            Level::Error,
+            "worktrees",
+            format!("git {}.{} lacks `git worktree remove`", found.0, found.1),
+            Some("Upgrade git to 2.17 or newer".to_string()),
+        ),
+        Some(_) => checks.add(SECTION, Level::Ok, "worktrees", "supported", None),
+        None => checks.add(
+            SECTION,
+            Level::Warning,
+            "worktrees",
+            "could not tell the git version",
+            Some("Check that `git --version` works".to_string()),
+        ),
+    }
+}
+
+/// Major and minor version from `git version 2.43.0` and the like
+fn parse_git_version(version: &str) -> Option<(u32, u32)> {
+    let number = version.strip_prefix("git version ")?;
+    let mut parts = number.split(|c: char| !c.is_ascii_digit());
+    Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+}
+
+/// Whether tmux is available
+fn check_tmux(checks: &mut Checks) -> bool {
+    const SECTION: &str = "tmux";
+    match find_executable("tmux") {
+        Some(tmux) => {
+            let version = probe_version(&tmux, "-V");
+            checks.add(
+                SECTION,
+                Level::Ok,
+                "tmux",
+                format!(
+                    "{} ({})",
+                    version.as_deref().unwrap_or("version unknown"),
+                    tmux.display()
+                ),
+                None,
+            );
+            true
+        }
+        None => {
+            checks.add(
+                SECTION,
+                Level::Warning,
+                "tmux",
+                "not found in PATH",
+                Some("Install tmux to use `xlaude dashboard`".to_string()),
+            );
+            false
+        }
+    }
+}
+
+/// The parsed state, if it can be read
+fn check_config(checks: &mut Checks) -> Option<XlaudeState> {
+    const SECTION: &str = "Configuration";
+    let dir = match get_config_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            checks.add(
+                SECTION,
+                Level::Error,
+                "config directory",
+                format!("{e:#}"),
+                Some("Set XLAUDE_CONFIG_DIR to a writable directory".to_string()),
+            );
+            return None;
+        }
+    };
+    checks.add(
+        SECTION,
+        Level::Ok,
+        "config directory",
+        dir.display().to_string(),
+        None,
+    );
+
+    let path = dir.join("state.json");
+    if !path.exists() {
+        checks.add(
+            SECTION,
+            Level::Info,
+            "state",
+            format!("{} does not exist yet", path.display()),
+            None,
+        );
+        return Some(XlaudeState::default());
+    }
+
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => {
+            checks.add(
+                SECTION,
+                Level::Error,
+                "state",
+                format!("cannot read {}: {e}", path.display()),
+                Some("Check the permissions of the file".to_string()),
+            );
+            return None;
+        }
+    };
+    match serde_json::from_str::<XlaudeState>(&content) {
+        Ok(state) => {
+            checks.add(
+                SECTION,
+                Level::Ok,
+                "state",
+                format!("{} ({} worktree(s))", path.display(), state.worktrees.len()),
+                None,
+            );
+            Some(state)
+        }
+        Err(e) => {
+            checks.add(
+                SECTION,
+                Level::Error,
+                "state",
+                format!("{} cannot be parsed: {e}", path.display()),
+                Some("Fix the file, or move it aside to start with an empty state".to_string()),
+            );
+            None
+        }
+    }
+}
+
+fn check_tools(checks: &mut Checks, state: &XlaudeState) {
+    const SECTION: &str = "AI tools";
+    let tools = all_tools(&state.tools);
+    let mut available = 0;
+
+    for tool in &tools {
+        let name = format!("{} ({})", tool.name, tool.id);
+        if tool.disabled {
+            checks.add(SECTION, Level::Info, name, "disabled", None);
+            continue;
+        }
+
+        if let Some(command) = tool.command_override() {
+            match find_executable(&command) {
+                Some(path) => {
+                    available += 1;
+                    checks.add(
+                        SECTION,
+                        Level::Ok,
+                        name,
+                        format!(
+                            "{} from {}{}",
+                            path.display(),
+                            tool.env_var,
+                            version_of(&path)
+                        ),
+                        None,
+                    );
+                }
+                None => checks.add(
+                    SECTION,
+                    Level::Error,
+                    name,
+                    format!(
+                        "{} is set to '{command}', which was not found",
+                        tool.env_var
+                    ),
+                    Some(format!("Fix or unset {}", tool.env_var)),
+                ),
+            }
+            continue;
+        }
+
+        if let Some(detect) = &tool.detect {
+            if tool.is_installed() {
+                available += 1;
+                checks.add(
+                    SECTION,
+                    Level::Ok,
+                    name,
+                    format!("detected by `{}`", detect.join(" ")),
+                    None,
+                );
+            } else {
+                checks.add(
+                    SECTION,
+                    Level::Info,
+                    name,
+                    format!("not detected by `{}`", detect.join(" ")),
+                    None,
+                );
+            }
+            continue;
+        }
+
+        match find_executable(&tool.command) {
+            Some(path) => {
+                available += 1;
+                checks.add(
+                    SECTION,
+                    Level::Ok,
+                    name,
+                    format!("{}{}", path.display(), version_of(&path)),
+                    None,
+                );
+            }
+            None => checks.add(
+                SECTION,
+                Level::Info,
+                name,
+                format!("not installed ('{}' not in PATH)", tool.command),
+                None,
+            ),
+        }
+    }
+
+    if available == 0 {
+        checks.add(
+            SECTION,
+            Level::Error,
+            "available tools",
+            "none of the enabled tools is installed",
+            Some(
+                "Install OpenCode, Qwen Code or Claude, or add a tool under `tools` in state.json"
+                    .to_string(),
+            ),
+        );
+    }
+
+    // Tools chosen earlier that no longer exist are silently replaced when opening
+    let known = |id: &str| tools.iter().any(|tool| tool.id == id);
+    for (repo, config) in &state.repos {
+        if let Some(id) = &config.default_tool
+            && !known(id)
+        {
+            checks.add(
+                SECTION,
+                Level::Warning,
+                format!("repository {repo}"),
+                format!("default_tool '{id}' is not a known tool"),
+                Some(format!("Change repos.{repo}.default_tool in state.json")),
+            );
+        }
+    }
+    for info in state.worktrees.values() {
+        if let Some(id) = &info.tool
+            && !known(id)
+        {
+            checks.add(
+                SECTION,
+                Level::Warning,
+                format!("worktree {}/{}", info.repo_name, info.name),
+                format!("remembered tool '{id}' is not a known tool"),
+                Some(format!("Run `xlaude open {} --tool <id>`", info.name)),
+            );
+        }
+    }
+}
+
+/// `, <version>` of a program, if it tells
+fn version_of(path: &Path) -> String {
+    probe_version(path, "--version")
+        .map(|version| format!(", {version}"))
+        .unwrap_or_default()
+}
+
+fn check_claude(checks: &mut Checks, state: &XlaudeState) {
+    const SECTION: &str = "Claude";
+    let source = if std::env::var_os("CLAUDE_CONFIG_DIR").is_some_and(|dir| !dir.is_empty()) {
+        "from CLAUDE_CONFIG_DIR"
+    } else if state.claude_config_dir.is_some() {
+        "from claude_config_dir in state.json"
+    } else {
+        "default"
+    };
+    let Some(projects) = claude_config_dir().map(|dir| dir.join("projects")) else {
+        checks.add(
+            SECTION,
+            Level::Warning,
+            "projects directory",
+            "cannot be determined without HOME",
+            Some("Set CLAUDE_CONFIG_DIR".to_string()),
+        );
+        return;
+    };
+
+    if !projects.is_dir() {
+        checks.add(
+            SECTION,
+            Level::Warning,
+            "projects directory",
+            format!("{} ({source}) does not exist", projects.display()),
+            Some(
+                "Set CLAUDE_CONFIG_DIR or claude_config_dir in state.json if Claude keeps its data elsewhere"
+                    .to_string(),
+            ),
+        );
+        return;
+    }
+    checks.add(
+        SECTION,
+        Level::Ok,
+        "projects directory",
+        format!("{} ({source})", projects.display()),
+        None,
+    );
+
+    let with_history = state
+        .worktrees
+        .values()
+        .filter(|info| claude_project_dir(&info.path).is_some_and(|dir| dir.is_dir()))
+        .count();
+    checks.add(
+        SECTION,
+        Level::Info,
+        "transcripts",
+        format!(
+            "found for {with_history} of {} worktree(s)",
+            state.worktrees.len()
+        ),
+        None,
+    );
+}
+
+fn check_orphans(checks: &mut Checks, state: &XlaudeState, tmux: bool) {
+    const SECTION: &str = "Orphaned artifacts";
+    let before = checks.0.len();
+
+    let mut missing: Vec<_> = state
+        .worktrees
+        .iter()
+        .filter(|(_, info)| !info.path.exists())
+        .collect();
+    missing.sort_by_key(|(key, _)| key.as_str());
+    for (key, info) in missing {
+        checks.add(
+            SECTION,
+            Level::Warning,
+            format!("worktree {key}"),
+            format!("{} no longer exists", info.path.display()),
+            Some("Run `xlaude clean` to forget it".to_string()),
+        );
+    }
+
+    if tmux {
+        let sessions = TmuxManager::new().list_sessions().unwrap_or_default();
+        for session in sessions {
+            if !state
+                .worktrees
+                .values()
+                .any(|info| session.belongs_to(&info.name))
+            {
+                checks.add(
+                    SECTION,
+                    Level::Warning,
+                    format!("tmux session xlaude_{}", session.project),
+                    "belongs to no worktree",
+                    Some(format!(
+                        "Run `tmux kill-session -t xlaude_{}`",
+                        session.project
+                    )),
+                );
+            }
+        }
+    }
+
+    // Run logs are named `<name>-<time>.log` under a directory per repository
+    if let Ok(logs) = get_config_dir().map(|dir| dir.join("logs"))
+        && let Ok(repos) = fs::read_dir(&logs)
+    {
+        let mut orphaned = Vec::new();
+        for repo in repos.flatten() {
+            let repo_name = repo.file_name().to_string_lossy().to_string();
+            for log in fs::read_dir(repo.path()).into_iter().flatten().flatten() {
+                let file = log.file_name().to_string_lossy().to_string();
+                if !state.worktrees.values().any(|info| {
+                    info.repo_name == repo_name && file.starts_with(&format!("{}-", info.name))
+                }) {
+                    orphaned.push(log.path());
+                }
+            }
+        }
+        if !orphaned.is_empty() {
+            checks.add(
+                SECTION,
+                Level::Warning,
+                "run logs",
+                format!(
+                    "{} log(s) of deleted worktrees in {}",
+                    orphaned.len(),
+                    logs.display()
+                ),
+                Some("Delete them if they are no longer needed".to_string()),
+            );
+        }
+    }
+
+    if checks.0.len() == before {
+        checks.add(SECTION, Level::Ok, "artifacts", "none found", None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_version() {
+        assert_eq!(parse_git_version("git version 2.43.0"), Some((2, 43)));
+        assert_eq!(
+            parse_git_version("git version 2.39.3 (Apple Git-146)"),
+            Some((2, 39))
+        );
+        assert_eq!(
+            parse_git_version("git version 2.45.1.windows.1"),
+            Some((2, 45))
+        );
+        assert_eq!(parse_git_version("hub version 2.14.2"), None);
+    }
+}
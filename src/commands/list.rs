@@ -1,14 +1,24 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use clap::ValueEnum;
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::io::Write;
 
-use crate::claude::get_claude_sessions;
-use crate::state::XlaudeState;
+use crate::ai_tools::worktree_status_profile;
+use crate::claude_status::{ClaudeStatus, StatusProfile, detect_session_status};
+use crate::git_status::{WorktreeStatus, collect_statuses};
+use crate::output;
+use crate::session_provider::{SessionInfo, get_sessions};
+use crate::state::{WorktreeInfo, XlaudeState};
+use crate::tmux::{SessionInfo as TmuxSession, TmuxManager};
+use crate::utils::{expand_template, format_duration_short};
 
 #[derive(Debug, Serialize, Deserialize)]
 struct JsonSessionInfo {
+    #[serde(default)]
+    tool: String,
     last_user_message: String,
     last_timestamp: Option<DateTime<Utc>>,
     time_ago: String,
@@ -21,7 +31,29 @@ struct JsonWorktreeInfo {
     path: String,
     repo_name: String,
     created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Id of the AI tool the worktree was last opened with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool: Option<String>,
+    /// Permission profile the worktree was last opened with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    profile: Option<String>,
     sessions: Vec<JsonSessionInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<WorktreeStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    runtime: Option<JsonRuntimeInfo>,
+}
+
+/// Live state of the worktree's dashboard tmux session
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonRuntimeInfo {
+    session_running: bool,
+    attached: bool,
+    last_activity: Option<DateTime<Utc>>,
+    /// Detected agent state: waiting, processing, error, idle or not_running
+    status: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -29,160 +61,542 @@ struct JsonOutput {
     worktrees: Vec<JsonWorktreeInfo>,
 }
 
-pub fn handle_list(json: bool) -> Result<()> {
+/// States `list --status` can filter on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StatusFilter {
+    /// Agent is waiting for input
+    Waiting,
+    /// Agent is working
+    Processing,
+    /// Worktree has uncommitted changes
+    Dirty,
+    /// Branch is merged into its base or via a pull request
+    Merged,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    /// Repository, then worktree name
+    #[default]
+    Name,
+    /// Newest worktree first
+    Created,
+    /// Most recent commit or agent session first
+    Activity,
+}
+
+#[derive(Debug, Default)]
+pub struct ListOptions {
+    pub json: bool,
+    /// Show git status for each worktree
+    pub show_status: bool,
+    pub status_filter: Option<StatusFilter>,
+    pub repo: Option<String>,
+    pub tag: Option<String>,
+    pub sort: SortKey,
+    /// Template with `{field}` placeholders, one line per worktree
+    pub format: Option<String>,
+    pub names_only: bool,
+    /// Print names separated by NUL instead of newlines
+    pub null_delimited: bool,
+}
+
+impl ListOptions {
+    fn template_uses(&self, field: &str) -> bool {
+        self.format
+            .as_ref()
+            .is_some_and(|f| f.contains(&format!("{{{field}}}")))
+    }
+
+    fn needs_git_status(&self) -> bool {
+        self.needs_pull_requests()
+            || self.status_filter == Some(StatusFilter::Dirty)
+            || self.sort == SortKey::Activity
+    }
+
+    /// Whether merge or pull request state is shown or filtered on, which
+    /// takes a forge query per worktree
+    fn needs_pull_requests(&self) -> bool {
+        self.displays_status() || self.status_filter == Some(StatusFilter::Merged)
+    }
+
+    fn displays_status(&self) -> bool {
+        let plain_names = self.names_only || self.null_delimited;
+        (self.show_status && !plain_names && self.format.is_none()) || self.template_uses("status")
+    }
+
+    fn needs_runtime(&self) -> bool {
+        let plain_names = self.names_only || self.null_delimited;
+        (!plain_names && self.format.is_none())
+            || matches!(
+                self.status_filter,
+                Some(StatusFilter::Waiting | StatusFilter::Processing)
+            )
+            || self.template_uses("status")
+            || self.template_uses("activity")
+            || self.sort == SortKey::Activity
+    }
+
+    fn needs_sessions(&self) -> bool {
+        let plain_names = self.names_only || self.null_delimited;
+        (!plain_names && self.format.is_none())
+            || self.template_uses("sessions")
+            || self.template_uses("activity")
+            || self.sort == SortKey::Activity
+    }
+}
+
+/// A worktree with everything collected for listing it
+struct ListEntry<'a> {
+    key: &'a str,
+    info: &'a WorktreeInfo,
+    sessions: Vec<SessionInfo>,
+    git: Option<WorktreeStatus>,
+    runtime: Option<Runtime>,
+}
+
+/// The worktree's dashboard tmux session and the agent running in it
+struct Runtime {
+    session: Option<TmuxSession>,
+    status: ClaudeStatus,
+}
+
+impl Runtime {
+    fn collect(
+        tmux: &TmuxManager,
+        sessions: &[TmuxSession],
+        worktree_name: &str,
+        profile: StatusProfile,
+    ) -> Self {
+        let session = sessions
+            .iter()
+            .find(|s| s.belongs_to(worktree_name))
+            .cloned();
+        let status = if session.is_some() {
+            detect_session_status(tmux, worktree_name, profile)
+        } else {
+            ClaudeStatus::NotRunning
+        };
+        Self { session, status }
+    }
+
+    fn last_activity(&self) -> Option<DateTime<Utc>> {
+        self.session
+            .as_ref()
+            .and_then(|s| DateTime::from_timestamp(s.last_activity, 0))
+    }
+}
+
+impl ListEntry<'_> {
+    /// Latest of creation, last commit, last agent session and tmux activity
+    fn last_activity(&self) -> DateTime<Utc> {
+        let last_commit = self
+            .git
+            .as_ref()
+            .and_then(|g| g.last_commit.as_ref())
+            .map(|c| c.committed_at);
+        let last_session = self.sessions.iter().filter_map(|s| s.last_timestamp).max();
+        let last_tmux = self.runtime.as_ref().and_then(Runtime::last_activity);
+
+        [
+            Some(self.info.created_at),
+            last_commit,
+            last_session,
+            last_tmux,
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+        .unwrap_or(self.info.created_at)
+    }
+
+    fn agent(&self) -> Option<&ClaudeStatus> {
+        self.runtime.as_ref().map(|r| &r.status)
+    }
+
+    fn matches(&self, filter: StatusFilter) -> bool {
+        match filter {
+            StatusFilter::Waiting => self.agent() == Some(&ClaudeStatus::WaitingForInput),
+            StatusFilter::Processing => self.agent() == Some(&ClaudeStatus::Processing),
+            StatusFilter::Dirty => self.git.as_ref().is_some_and(WorktreeStatus::is_dirty),
+            StatusFilter::Merged => self.git.as_ref().is_some_and(|g| g.merged),
+        }
+    }
+
+    /// Comma separated status labels for `{status}` in templates
+    fn status_labels(&self) -> String {
+        let mut labels = Vec::new();
+        if let Some(agent) = self.agent()
+            && *agent != ClaudeStatus::NotRunning
+        {
+            labels.push(agent.as_str());
+        }
+        if let Some(git) = &self.git {
+            if git.is_dirty() {
+                labels.push("dirty");
+            }
+            if git.merged {
+                labels.push("merged");
+            }
+        }
+
+        if labels.is_empty() {
+            "-".to_string()
+        } else {
+            labels.join(",")
+        }
+    }
+
+    fn field(&self, field: &str) -> Option<String> {
+        Some(match field {
+            "name" => self.info.name.clone(),
+            "repo" => self.info.repo_name.clone(),
+            "key" => self.key.to_string(),
+            "branch" => self.info.branch.clone(),
+            "path" => self.info.path.display().to_string(),
+            "created" => self.info.created_at.to_rfc3339(),
+            "activity" => self.last_activity().to_rfc3339(),
+            "tags" => self.info.tags.join(","),
+            "tool" => self.info.tool.clone().unwrap_or_default(),
+            "profile" => self.info.profile.clone().unwrap_or_default(),
+            "sessions" => self.sessions.len().to_string(),
+            "status" => self.status_labels(),
+            _ => return None,
+        })
+    }
+}
+
+pub fn handle_list(options: ListOptions) -> Result<()> {
     let state = XlaudeState::load()?;
 
     if state.worktrees.is_empty() {
-        if json {
-            let output = JsonOutput { worktrees: vec![] };
-            println!("{}", serde_json::to_string_pretty(&output)?);
-        } else {
+        if options.json {
+            print_json(vec![], false)?;
+        } else if options.format.is_none() && !options.names_only && !options.null_delimited {
             println!("{} No active worktrees", "📭".yellow());
         }
         return Ok(());
     }
 
-    if json {
-        // JSON output
-        let mut worktrees = Vec::new();
+    let entries = collect_entries(&state, &options);
+
+    if options.json {
+        // Status collected only to filter or sort on lacks the pull request
+        return print_json(entries, options.displays_status());
+    }
+
+    if options.names_only || options.null_delimited {
+        let separator = if options.null_delimited { '\0' } else { '\n' };
+        let mut stdout = std::io::stdout().lock();
+        for entry in &entries {
+            write!(stdout, "{}{}", entry.info.name, separator)?;
+        }
+        return Ok(());
+    }
+
+    if let Some(template) = &options.format {
+        for entry in &entries {
+            println!("{}", expand_template(template, |field| entry.field(field)));
+        }
+        return Ok(());
+    }
+
+    print_text(&entries, &options);
+    Ok(())
+}
 
-        for info in state.worktrees.values() {
-            let sessions = get_claude_sessions(&info.path);
-            let json_sessions: Vec<JsonSessionInfo> = sessions
+/// Filter, collect status for and sort the worktrees to list
+fn collect_entries<'a>(state: &'a XlaudeState, options: &ListOptions) -> Vec<ListEntry<'a>> {
+    let selected: Vec<(&String, &WorktreeInfo)> = state
+        .worktrees
+        .iter()
+        .filter(|(_, info)| options.repo.as_ref().is_none_or(|r| info.repo_name == *r))
+        .filter(|(_, info)| options.tag.as_ref().is_none_or(|t| info.tags.contains(t)))
+        .collect();
+
+    // Git status is collected up front, concurrently across worktrees
+    let git_statuses = if options.needs_git_status() {
+        let infos: Vec<&WorktreeInfo> = selected.iter().map(|(_, info)| *info).collect();
+        collect_statuses(&infos, options.needs_pull_requests())
+            .into_iter()
+            .map(Some)
+            .collect()
+    } else {
+        vec![None; selected.len()]
+    };
+
+    // One tmux call for all sessions, panes are only captured for running ones
+    let tmux = TmuxManager::new();
+    let tmux_sessions = options
+        .needs_runtime()
+        .then(|| tmux.list_sessions().unwrap_or_default());
+
+    let mut entries: Vec<ListEntry> = selected
+        .into_iter()
+        .zip(git_statuses)
+        .map(|((key, info), git)| ListEntry {
+            key,
+            info,
+            sessions: if options.needs_sessions() {
+                get_sessions(&info.path)
+            } else {
+                Vec::new()
+            },
+            git,
+            runtime: tmux_sessions.as_ref().map(|sessions| {
+                let profile = worktree_status_profile(state, info);
+                Runtime::collect(&tmux, sessions, &info.name, profile)
+            }),
+        })
+        .collect();
+
+    if let Some(filter) = options.status_filter {
+        entries.retain(|entry| entry.matches(filter));
+    }
+
+    match options.sort {
+        SortKey::Name => entries.sort_by(|a, b| {
+            a.info
+                .repo_name
+                .cmp(&b.info.repo_name)
+                .then_with(|| a.info.name.cmp(&b.info.name))
+        }),
+        SortKey::Created => entries.sort_by_key(|e| std::cmp::Reverse(e.info.created_at)),
+        SortKey::Activity => entries.sort_by_cached_key(|e| std::cmp::Reverse(e.last_activity())),
+    }
+
+    entries
+}
+
+fn print_json(entries: Vec<ListEntry>, show_status: bool) -> Result<()> {
+    let worktrees = entries
+        .into_iter()
+        .map(|entry| {
+            let sessions = entry
+                .sessions
                 .into_iter()
-                .map(|session| {
-                    let time_ago = session.last_timestamp.as_ref().map_or_else(
-                        || "unknown".to_string(),
-                        |ts| {
-                            let now = Utc::now();
-                            let diff = now.signed_duration_since(*ts);
-
-                            if diff.num_minutes() < 60 {
-                                format!("{}m ago", diff.num_minutes())
-                            } else if diff.num_hours() < 24 {
-                                format!("{}h ago", diff.num_hours())
-                            } else {
-                                format!("{}d ago", diff.num_days())
-                            }
-                        },
-                    );
-
-                    JsonSessionInfo {
-                        last_user_message: session.last_user_message,
-                        last_timestamp: session.last_timestamp,
-                        time_ago,
-                    }
+                .map(|session| JsonSessionInfo {
+                    tool: session.tool.to_string(),
+                    time_ago: format_time_ago(session.last_timestamp.as_ref()),
+                    last_user_message: session.last_user_message,
+                    last_timestamp: session.last_timestamp,
                 })
                 .collect();
 
-            worktrees.push(JsonWorktreeInfo {
-                name: info.name.clone(),
-                branch: info.branch.clone(),
-                path: info.path.display().to_string(),
-                repo_name: info.repo_name.clone(),
-                created_at: info.created_at,
-                sessions: json_sessions,
-            });
+            JsonWorktreeInfo {
+                name: entry.info.name.clone(),
+                branch: entry.info.branch.clone(),
+                path: entry.info.path.display().to_string(),
+                repo_name: entry.info.repo_name.clone(),
+                created_at: entry.info.created_at,
+                tags: entry.info.tags.clone(),
+                tool: entry.info.tool.clone(),
+                profile: entry.info.profile.clone(),
+                sessions,
+                status: entry.git.filter(|_| show_status),
+                runtime: entry.runtime.map(|runtime| JsonRuntimeInfo {
+                    session_running: runtime.session.is_some(),
+                    attached: runtime.session.as_ref().is_some_and(|s| s.is_attached),
+                    last_activity: runtime.last_activity(),
+                    status: runtime.status.as_str().to_string(),
+                }),
+            }
+        })
+        .collect();
+
+    // With `--output json` the list becomes the result of the common envelope
+    if output::is_json() {
+        output::set("worktrees", worktrees);
+        return Ok(());
+    }
+
+    let json = JsonOutput { worktrees };
+    println!("{}", serde_json::to_string_pretty(&json)?);
+    Ok(())
+}
+
+fn print_text(entries: &[ListEntry], options: &ListOptions) {
+    if entries.is_empty() {
+        println!("{} No matching worktrees", "📭".yellow());
+        return;
+    }
+
+    println!("{} Active worktrees:", "📋".cyan());
+    println!();
+
+    if options.sort != SortKey::Name {
+        // Keep the requested order instead of grouping by repository
+        for entry in entries {
+            let title = format!("{}/{}", entry.info.repo_name, entry.info.name);
+            print_entry(entry, &title, options.show_status);
         }
+        println!();
+        return;
+    }
 
-        // Sort worktrees by repo name and then by name
-        worktrees.sort_by(|a, b| {
-            a.repo_name
-                .cmp(&b.repo_name)
-                .then_with(|| a.name.cmp(&b.name))
-        });
+    // Group worktrees by repository, entries are already sorted by name
+    let mut grouped: BTreeMap<&str, Vec<&ListEntry>> = BTreeMap::new();
+    for entry in entries {
+        grouped
+            .entry(entry.info.repo_name.as_str())
+            .or_default()
+            .push(entry);
+    }
 
-        let output = JsonOutput { worktrees };
-        println!("{}", serde_json::to_string_pretty(&output)?);
-    } else {
-        // Original colored output
-        println!("{} Active worktrees:", "📋".cyan());
+    // Display grouped by repository
+    for (repo_name, worktrees) in grouped {
+        println!("  {} {}", "📦".blue(), repo_name.bold());
+
+        for entry in worktrees {
+            print_entry(entry, &entry.info.name, options.show_status);
+        }
         println!();
+    }
+}
 
-        // Group worktrees by repository
-        let mut grouped: BTreeMap<String, Vec<_>> = BTreeMap::new();
-        for info in state.worktrees.values() {
-            grouped
-                .entry(info.repo_name.clone())
-                .or_default()
-                .push(info);
+fn print_entry(entry: &ListEntry, title: &str, show_status: bool) {
+    let info = entry.info;
+    println!("    {} {}", "•".green(), title.cyan());
+    println!("      {} {}", "Path:".bright_black(), info.path.display());
+    println!(
+        "      {} {}",
+        "Created:".bright_black(),
+        info.created_at.format("%Y-%m-%d %H:%M:%S")
+    );
+
+    if !info.tags.is_empty() {
+        println!("      {} {}", "Tags:".bright_black(), info.tags.join(", "));
+    }
+
+    if let Some(tool) = &info.tool {
+        match &info.profile {
+            Some(profile) => println!("      {} {} ({})", "Tool:".bright_black(), tool, profile),
+            None => println!("      {} {}", "Tool:".bright_black(), tool),
         }
+    }
 
-        // Display grouped by repository
-        for (repo_name, mut worktrees) in grouped {
-            println!("  {} {}", "📦".blue(), repo_name.bold());
-
-            // Sort worktrees within each repo by name
-            worktrees.sort_by_key(|w| &w.name);
-
-            for info in worktrees {
-                println!("    {} {}", "•".green(), info.name.cyan());
-                println!("      {} {}", "Path:".bright_black(), info.path.display());
-                println!(
-                    "      {} {}",
-                    "Created:".bright_black(),
-                    info.created_at.format("%Y-%m-%d %H:%M:%S")
-                );
-
-                // Get Claude sessions for this worktree
-                let sessions = get_claude_sessions(&info.path);
-                if !sessions.is_empty() {
-                    println!(
-                        "      {} {} session(s):",
-                        "Claude:".bright_black(),
-                        sessions.len()
-                    );
-                    for session in sessions.iter().take(3) {
-                        // Format time
-                        let time_str = session.last_timestamp.as_ref().map_or_else(
-                            || "unknown".to_string(),
-                            |ts| {
-                                let now = Utc::now();
-                                let diff = now.signed_duration_since(*ts);
-
-                                if diff.num_minutes() < 60 {
-                                    format!("{}m ago", diff.num_minutes())
-                                } else if diff.num_hours() < 24 {
-                                    format!("{}h ago", diff.num_hours())
-                                } else {
-                                    format!("{}d ago", diff.num_days())
-                                }
-                            },
-                        );
-
-                        // Truncate message if too long
-                        let message = if session.last_user_message.len() > 60 {
-                            let mut truncated = String::new();
-                            for ch in session.last_user_message.chars() {
-                                if truncated.len() + ch.len_utf8() > 57 {
-                                    break;
-                                }
-                                truncated.push(ch);
-                            }
-                            format!("{truncated}...")
-                        } else {
-                            session.last_user_message.clone()
-                        };
-
-                        println!(
-                            "        {} {} {}",
-                            "-".bright_black(),
-                            time_str.bright_black(),
-                            message.bright_black()
-                        );
-                    }
-                    if sessions.len() > 3 {
-                        println!(
-                            "        {} ... and {} more",
-                            "-".bright_black(),
-                            sessions.len() - 3
-                        );
+    if show_status && let Some(status) = &entry.git {
+        print_status(status);
+    }
+
+    if let Some(runtime) = &entry.runtime
+        && let Some(session) = &runtime.session
+    {
+        let attached = if session.is_attached {
+            "attached".green()
+        } else {
+            "detached".bright_black()
+        };
+        println!(
+            "      {} {} {} · {} · active {}",
+            "Runtime:".bright_black(),
+            runtime.status.display_icon(),
+            runtime.status.display_text(),
+            attached,
+            TmuxSession::format_time(session.last_activity)
+        );
+    }
+
+    // Agent sessions of all tools for this worktree
+    let sessions = &entry.sessions;
+    if !sessions.is_empty() {
+        println!(
+            "      {} {} session(s):",
+            "Agents:".bright_black(),
+            sessions.len()
+        );
+        for session in sessions.iter().take(3) {
+            let time_str = format_time_ago(session.last_timestamp.as_ref());
+
+            // Truncate message if too long
+            let message = if session.last_user_message.len() > 60 {
+                let mut truncated = String::new();
+                for ch in session.last_user_message.chars() {
+                    if truncated.len() + ch.len_utf8() > 57 {
+                        break;
                     }
+                    truncated.push(ch);
                 }
-            }
-            println!();
+                format!("{truncated}...")
+            } else {
+                session.last_user_message.clone()
+            };
+
+            println!(
+                "        {} {} {} {}",
+                "-".bright_black(),
+                time_str.bright_black(),
+                format!("[{}]", session.tool).bright_black(),
+                message.bright_black()
+            );
+        }
+        if sessions.len() > 3 {
+            println!(
+                "        {} ... and {} more",
+                "-".bright_black(),
+                sessions.len() - 3
+            );
         }
     }
+}
 
-    Ok(())
+fn format_time_ago(timestamp: Option<&DateTime<Utc>>) -> String {
+    timestamp.map_or_else(
+        || "unknown".to_string(),
+        |ts| {
+            let now = Utc::now();
+            let diff = now.signed_duration_since(*ts);
+
+            if diff.num_minutes() < 60 {
+                format!("{}m ago", diff.num_minutes())
+            } else if diff.num_hours() < 24 {
+                format!("{}h ago", diff.num_hours())
+            } else {
+                format!("{}d ago", diff.num_days())
+            }
+        },
+    )
+}
+
+/// Print the git status lines of a worktree for `list --status`
+fn print_status(status: &WorktreeStatus) {
+    let mut parts = Vec::new();
+
+    if status.is_dirty() {
+        parts.push(format!("{} dirty", status.dirty_files).yellow().to_string());
+    } else {
+        parts.push("clean".green().to_string());
+    }
+
+    match status.upstream {
+        Some(upstream) => parts.push(format!("↑{} ↓{} upstream", upstream.ahead, upstream.behind)),
+        None => parts.push("no upstream".bright_black().to_string()),
+    }
+
+    if let (Some(base_branch), Some(base)) = (&status.base_branch, status.base) {
+        parts.push(format!("↑{} ↓{} {}", base.ahead, base.behind, base_branch));
+    }
+
+    if status.merged {
+        parts.push("merged".green().to_string());
+    }
+
+    if let Some(pr) = &status.pull_request {
+        parts.push(format!(
+            "PR #{} {} ({}, {})",
+            pr.number, pr.state, pr.review, pr.checks
+        ));
+    }
+
+    println!("      {} {}", "Status:".bright_black(), parts.join(", "));
+
+    if let Some(commit) = &status.last_commit {
+        let age = Utc::now().signed_duration_since(commit.committed_at);
+        println!(
+            "      {} {} ago {}",
+            "Last commit:".bright_black(),
+            format_duration_short(age),
+            commit.subject
+        );
+    }
 }
@@ -5,17 +5,35 @@ pub mod create;
 pub mod dashboard;
 pub mod delete;
 pub mod dir;
+pub mod doctor;
 pub mod list;
 pub mod open;
 pub mod rename;
+pub mod run;
+pub mod search;
+pub mod sessions;
+pub mod show;
+pub mod status;
+pub mod tag;
+pub mod transcript;
+pub mod usage;
 
 pub use add::handle_add;
-pub use clean::handle_clean;
+pub use clean::{PruneOptions, handle_clean, handle_gc};
 pub use complete::handle_complete_worktrees;
 pub use create::handle_create;
 pub use dashboard::handle_dashboard;
-pub use delete::handle_delete;
+pub use delete::{TranscriptAction, handle_delete};
 pub use dir::handle_dir;
-pub use list::handle_list;
-pub use open::handle_open;
+pub use doctor::handle_doctor;
+pub use list::{ListOptions, SortKey, StatusFilter, handle_list};
+pub use open::{OpenOptions, handle_open};
 pub use rename::handle_rename;
+pub use run::{RunOptions, handle_run};
+pub use search::{SearchOptions, handle_search};
+pub use sessions::{SessionsAction, handle_sessions, handle_sessions_relocate};
+pub use show::handle_show;
+pub use status::handle_status;
+pub use tag::handle_tag;
+pub use transcript::{DEFAULT_MAX_OUTPUT, TranscriptFormat, TranscriptOptions, handle_transcript};
+pub use usage::{UsageGroup, handle_usage};
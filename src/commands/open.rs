@@ -1,14 +1,33 @@
 use anyhow::{Context, Result};
 use chrono::Utc;
 use colored::Colorize;
+use std::path::Path;
 
-use crate::ai_tools::{find_available_tool, launch_ai_tool_with_path, StdinMode};
+use crate::ai_tools::{find_available_tool, launch_ai_tool_with_path, preferred_profile, preferred_tool, resolve_tool, AiTool, SessionResume, StdinMode};
 use crate::git::{get_current_branch, get_repo_name, is_base_branch, is_in_worktree};
-use crate::input::{drain_stdin, get_command_arg, is_piped_input, smart_confirm, smart_select};
+use crate::input::{drain_stdin, is_piped_input, select_worktree, smart_confirm, smart_select};
+use crate::output::{self, coded, say, ErrorCode};
+use crate::session_provider::{provider, sort_sessions};
 use crate::state::{WorktreeInfo, XlaudeState};
-use crate::utils::sanitize_branch_name;
+use crate::tmux::TmuxManager;
+use crate::utils::{format_duration_short, sanitize_branch_name};
 
-pub fn handle_open(name: Option<String>) -> Result<()> {
+/// How to start the AI tool when opening a worktree
+#[derive(Debug, Default)]
+pub struct OpenOptions {
+    /// Continue or resume a conversation
+    pub resume: Option<SessionResume>,
+    /// Id of the tool to use instead of the remembered or first available one
+    pub tool: Option<String>,
+    /// Permission profile to use instead of the remembered or configured one
+    pub profile: Option<String>,
+    /// Extra arguments passed to the tool as they are
+    pub args: Vec<String>,
+    /// First message to send to the tool
+    pub prompt: Option<String>,
+}
+
+pub fn handle_open(name: Option<String>, options: OpenOptions) -> Result<()> {
     let mut state = XlaudeState::load()?;
 
     // Check if current path is a worktree when no name is provided
@@ -21,7 +40,8 @@ pub fn handle_open(name: Option<String>) -> Result<()> {
             // There's piped input, so skip current worktree detection
         } else {
             // Get current repository info
-            let repo_name = get_repo_name().context("Not in a git repository")?;
+            let repo_name = get_repo_name()
+                .context(coded(ErrorCode::NotInRepository, "Not in a git repository"))?;
             let current_branch = get_current_branch()?;
             let current_dir = std::env::current_dir()?;
 
@@ -33,7 +53,7 @@ pub fn handle_open(name: Option<String>) -> Result<()> {
 
             if state.worktrees.contains_key(&key) {
                 // Already managed, open directly
-                println!(
+                say!(
                     "{} Opening current worktree '{}/{}'...",
                     "🚀".green(),
                     repo_name,
@@ -41,17 +61,17 @@ pub fn handle_open(name: Option<String>) -> Result<()> {
                 );
             } else {
                 // Not managed, ask if user wants to add it
-                println!(
+                say!(
                     "{} Current directory is a worktree but not managed by xlaude",
                     "ℹ️".blue()
                 );
-                println!(
+                say!(
                     "  {} {}/{}",
                     "Worktree:".bright_black(),
                     repo_name,
                     current_branch
                 );
-                println!("  {} {}", "Path:".bright_black(), current_dir.display());
+                say!("  {} {}", "Path:".bright_black(), current_dir.display());
 
                 // Use smart confirm for pipe support
                 let should_add = smart_confirm(
@@ -60,11 +80,12 @@ pub fn handle_open(name: Option<String>) -> Result<()> {
                 )?;
 
                 if !should_add {
+                    output::set("opened", false);
                     return Ok(());
                 }
 
                 // Add to state
-                println!(
+                say!(
                     "{} Adding worktree '{}' to xlaude management...",
                     "➕".green(),
                     worktree_name.cyan()
@@ -78,12 +99,17 @@ pub fn handle_open(name: Option<String>) -> Result<()> {
                         path: current_dir.clone(),
                         repo_name: repo_name.clone(),
                         created_at: Utc::now(),
+                        tags: Vec::new(),
+                        tool: None,
+                        profile: None,
+                        last_run: None,
                     },
                 );
                 state.save()?;
+                output::action(format!("added worktree {key}"));
 
-                println!("{} Worktree added successfully", "✅".green());
-                println!(
+                say!("{} Worktree added successfully", "✅".green());
+                say!(
                     "{} Opening worktree '{}/{}'...",
                     "🚀".green(),
                     repo_name,
@@ -99,57 +125,15 @@ pub fn handle_open(name: Option<String>) -> Result<()> {
                 StdinMode::Inherit
             };
 
-            // Find the first available AI tool
-            if let Some(ai_tool) = find_available_tool() {
-                println!("{} Using {} as AI coding assistant", "🤖".green(), ai_tool.name.cyan());
-                launch_ai_tool_with_path(&ai_tool, stdin_mode, Some(current_dir))?;
-            } else {
-                anyhow::bail!("No AI coding tools found. Please install OpenCode, Qwen Code, or Claude CLI");
-            }
-
-            return Ok(());
+            return launch(&mut state, &key, &repo_name, &worktree_name, &current_dir, stdin_mode, options);
         }
     }
 
-    if state.worktrees.is_empty() {
-        anyhow::bail!("No worktrees found. Create one first with 'xlaude create'");
-    }
-
-    // Get the name from CLI args or pipe
-    let target_name = get_command_arg(name)?;
-
-    // Determine which worktree to open
-    let (_key, worktree_info) = if let Some(n) = target_name {
-        // Find worktree by name across all projects
-        state
-            .worktrees
-            .iter()
-            .find(|(_, w)| w.name == n)
-            .map(|(k, w)| (k.clone(), w.clone()))
-            .context(format!("Worktree '{n}' not found"))?
-    } else {
-        // Interactive selection - show repo/name format
-        let worktree_list: Vec<(String, WorktreeInfo)> = state
-            .worktrees
-            .iter()
-            .map(|(k, v)| (k.clone(), v.clone()))
-            .collect();
-
-        let selection = smart_select("Select a worktree to open", &worktree_list, |(_, info)| {
-            format!("{}/{}", info.repo_name, info.name)
-        })?;
-
-        match selection {
-            Some(idx) => worktree_list[idx].clone(),
-            None => anyhow::bail!(
-                "Interactive selection not available in non-interactive mode. Please specify a worktree name."
-            ),
-        }
-    };
+    let (key, worktree_info) = select_worktree(&state, name, "Select a worktree to open")?;
 
     let worktree_name = &worktree_info.name;
 
-    println!(
+    say!(
         "{} Opening worktree '{}/{}'...",
         "🚀".green(),
         worktree_info.repo_name,
@@ -166,14 +150,135 @@ pub fn handle_open(name: Option<String>) -> Result<()> {
     } else {
         StdinMode::Inherit
     };
-    
-    // Find and launch the first available AI tool
-    if let Some(ai_tool) = find_available_tool() {
-        println!("{} Using {} as AI coding assistant", "🤖".green(), ai_tool.name.cyan());
-        launch_ai_tool_with_path(&ai_tool, stdin_mode, Some(worktree_info.path.clone()))?;
-    } else {
-        anyhow::bail!("No AI coding tools found. Please install OpenCode, Qwen Code, or Claude CLI");
+
+    launch(&mut state, &key, &worktree_info.repo_name, worktree_name, &worktree_info.path, stdin_mode, options)
+}
+
+/// Start the chosen tool in the worktree with the arguments `options` ask for
+fn launch(state: &mut XlaudeState, key: &str, repo_name: &str, worktree_name: &str, path: &Path, stdin_mode: StdinMode, options: OpenOptions) -> Result<()> {
+    let ai_tool = choose_tool(state, key, options.tool)?;
+    let profile = options.profile.clone().or_else(|| state.worktrees.get(key).and_then(|info| preferred_profile(state, info)));
+    let ai_tool = ai_tool.with_profile(profile.as_deref())?;
+    say!("{} Using {} as AI coding assistant", "🤖".green(), ai_tool.name.cyan());
+    if let Some(profile) = &ai_tool.profile {
+        say!("{} Permission profile: {}", "🔒".blue(), profile.cyan());
+    }
+    let mut args = session_args(&ai_tool, options.resume, path)?;
+    args.extend(options.args);
+    if let Some(prompt) = &options.prompt {
+        match ai_tool.prompt_args(prompt) {
+            Some(prompt_args) => args.extend(prompt_args),
+            None => type_prompt(&ai_tool, prompt)?,
+        }
+    }
+    remember_choice(state, key, &ai_tool, options.profile.is_some())?;
+    record_open(repo_name, worktree_name, path, &ai_tool.name);
+    output::set("profile", &ai_tool.profile);
+    launch_ai_tool_with_path(&ai_tool, stdin_mode, Some(path.to_path_buf()), &args)
+}
+
+/// Deliver a prompt to a tool that cannot take one on its command line by
+/// typing it into the tmux pane it is about to start in
+fn type_prompt(tool: &AiTool, prompt: &str) -> Result<()> {
+    match std::env::var("TMUX_PANE") {
+        Ok(pane) if !pane.is_empty() => {
+            say!("{} {} will receive the prompt once it has started", "⌨️".blue(), tool.name);
+            TmuxManager::send_text_later(&pane, prompt)
+        }
+        _ => {
+            say!("{} {} does not take an initial prompt outside tmux, paste it yourself", "⚠️".yellow(), tool.name);
+            output::warn(format!("{} did not receive the prompt", tool.name));
+            Ok(())
+        }
+    }
+}
+
+/// The tool to open a worktree with: the requested one, else the one it was
+/// last opened with or its repository's default, else the first available one
+pub fn choose_tool(state: &XlaudeState, key: &str, requested: Option<String>) -> Result<AiTool> {
+    if let Some(id) = requested {
+        return resolve_tool(&state.tools, &id);
+    }
+
+    if let Some(id) = state.worktrees.get(key).and_then(|info| preferred_tool(state, info)) {
+        match resolve_tool(&state.tools, &id) {
+            Ok(tool) => return Ok(tool),
+            Err(e) => {
+                say!("{} {}, looking for another tool", "⚠️".yellow(), e);
+                output::warn(format!("{e}, another tool was used"));
+            }
+        }
     }
 
+    find_available_tool(&state.tools)
+        .context("No AI coding tools found. Please install OpenCode, Qwen Code, or Claude CLI (run `xlaude doctor` for details)")
+}
+
+/// Remember the tool, and the permission profile if one was requested, so
+/// the worktree is reopened with them
+fn remember_choice(state: &mut XlaudeState, key: &str, tool: &AiTool, profile_requested: bool) -> Result<()> {
+    let Some(info) = state.worktrees.get_mut(key) else {
+        return Ok(());
+    };
+    let profile = if profile_requested { tool.profile.clone() } else { info.profile.clone() };
+    if info.tool.as_deref() != Some(tool.id.as_str()) || info.profile != profile {
+        info.tool = Some(tool.id.clone());
+        info.profile = profile;
+        state.save()?;
+    }
     Ok(())
 }
+
+/// Record which worktree was opened with which tool for `--output json`
+fn record_open(repo_name: &str, worktree_name: &str, path: &Path, tool: &str) {
+    output::set("name", worktree_name);
+    output::set("repo", repo_name);
+    output::set("path", path);
+    output::set("tool", tool);
+    output::set("opened", true);
+    output::action(format!("launched {tool}"));
+}
+
+/// Arguments that continue or resume a conversation, selecting one of the
+/// tool's sessions interactively when `--resume` was given without an id
+fn session_args(tool: &AiTool, resume: Option<SessionResume>, path: &Path) -> Result<Vec<String>> {
+    let sessions = tool.sessions.as_deref().and_then(provider);
+    let resume = match resume {
+        None => return Ok(Vec::new()),
+        Some(SessionResume::Resume(None)) if tool.resume_flag.is_some() && sessions.is_some() => {
+            let mut sessions = sessions.map(|provider| provider.sessions(path)).unwrap_or_default();
+            sort_sessions(&mut sessions);
+            if sessions.is_empty() {
+                return Err(coded(ErrorCode::NotFound, format!("No {} sessions to resume in this worktree", tool.name)).into());
+            }
+
+            let selection = smart_select("Select a session to resume", &sessions, |session| {
+                let when = session.last_timestamp.map_or_else(
+                    || "unknown".to_string(),
+                    |ts| format!("{} ago", format_duration_short(Utc::now() - ts)),
+                );
+                let message: String = session.last_user_message.lines().next().unwrap_or_default().chars().take(60).collect();
+                format!("{} · {} · {}", session.id, when, message)
+            })?;
+
+            match selection {
+                Some(idx) => SessionResume::Resume(Some(sessions[idx].id.clone())),
+                None => {
+                    return Err(coded(
+                        ErrorCode::InputRequired,
+                        "Interactive selection not available in non-interactive mode. Pass a session id to --resume.",
+                    )
+                    .into())
+                }
+            }
+        }
+        Some(resume) => resume,
+    };
+
+    let args = tool.resume_args(&resume)?;
+    match &resume {
+        SessionResume::Continue => output::set("continued", true),
+        SessionResume::Resume(id) => output::set("session", id),
+    }
+    Ok(args)
+}
@@ -1,7 +1,8 @@
-use anyhow::{Context, Result, bail};
+use anyhow::{Context, Result};
 use colored::Colorize;
 
 use crate::git;
+use crate::output::{self, ErrorCode, coded, say};
 use crate::state::XlaudeState;
 
 pub fn handle_rename(old_name: String, new_name: String) -> Result<()> {
@@ -12,15 +13,19 @@ pub fn handle_rename(old_name: String, new_name: String) -> Result<()> {
     let new_key = XlaudeState::make_key(&repo, &new_name);
 
     if !state.worktrees.contains_key(&old_key) {
-        bail!("Worktree '{}' not found in repository '{}'", old_name, repo);
+        return Err(coded(
+            ErrorCode::NotFound,
+            format!("Worktree '{old_name}' not found in repository '{repo}'"),
+        )
+        .into());
     }
 
     if state.worktrees.contains_key(&new_key) {
-        bail!(
-            "Worktree '{}' already exists in repository '{}'",
-            new_name,
-            repo
-        );
+        return Err(coded(
+            ErrorCode::AlreadyExists,
+            format!("Worktree '{new_name}' already exists in repository '{repo}'"),
+        )
+        .into());
     }
 
     let mut worktree_data = state
@@ -31,10 +36,15 @@ pub fn handle_rename(old_name: String, new_name: String) -> Result<()> {
     // Update the name field in the worktree info
     worktree_data.name = new_name.clone();
 
-    state.worktrees.insert(new_key, worktree_data);
+    state.worktrees.insert(new_key.clone(), worktree_data);
     state.save()?;
 
-    println!(
+    output::set("old_name", &old_name);
+    output::set("new_name", &new_name);
+    output::set("repo", &repo);
+    output::action(format!("renamed {old_key} to {new_key}"));
+
+    say!(
         "{} {} {} {} {} {}",
         "✓".green(),
         "Renamed worktree".green(),
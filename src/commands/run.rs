@@ -0,0 +1,190 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use colored::Colorize;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use crate::ai_tools::{preferred_profile, run_ai_tool_headless};
+use crate::commands::open::choose_tool;
+use crate::input::select_worktree;
+use crate::output::{self, ErrorCode, coded, say};
+use crate::state::{RunRecord, RunStatus, XlaudeState, get_config_dir};
+
+/// Exit status of a run stopped by its timeout, as with timeout(1)
+const TIMEOUT_EXIT_CODE: i32 = 124;
+
+/// Longest final message kept in the state
+const MAX_FINAL_MESSAGE: usize = 2000;
+
+/// Options for a headless agent run
+#[derive(Debug)]
+pub struct RunOptions {
+    pub name: String,
+    pub prompt: String,
+    /// Id of the AI tool to run instead of the worktree's own
+    pub tool: Option<String>,
+    /// Permission profile to run with instead of the worktree's own
+    pub profile: Option<String>,
+    /// Kill the tool after this long
+    pub timeout: Option<chrono::Duration>,
+}
+
+pub fn handle_run(options: RunOptions) -> Result<()> {
+    let state = XlaudeState::load()?;
+
+    let (key, info) = select_worktree(&state, Some(options.name), "Select a worktree")?;
+    if !info.path.exists() {
+        return Err(coded(
+            ErrorCode::InvalidState,
+            format!("Worktree directory {} does not exist", info.path.display()),
+        )
+        .into());
+    }
+
+    let profile = options.profile.or_else(|| preferred_profile(&state, &info));
+    let tool = choose_tool(&state, &key, options.tool)?.with_profile(profile.as_deref())?;
+    let args = tool.headless_args(&options.prompt).ok_or_else(|| {
+        coded(
+            ErrorCode::InvalidState,
+            format!(
+                "{} cannot run without a terminal; set headless_args for it in the tools configuration",
+                tool.name
+            ),
+        )
+    })?;
+    let timeout = options
+        .timeout
+        .map(|timeout| timeout.to_std())
+        .transpose()
+        .context("Invalid timeout")?;
+
+    let started_at = Utc::now();
+    let log = log_path(&info.repo_name, &info.name, started_at)?;
+    say!(
+        "{} Running {}{} in '{}/{}', logging to {}",
+        "🤖".green(),
+        tool.name.cyan(),
+        tool.profile
+            .as_ref()
+            .map(|profile| format!(" ({profile})"))
+            .unwrap_or_default(),
+        info.repo_name,
+        info.name.cyan(),
+        log.display()
+    );
+
+    let started = Instant::now();
+    let run = run_ai_tool_headless(&tool, &info.path, &args, &log, timeout)?;
+    let status = if run.timed_out {
+        RunStatus::TimedOut
+    } else if run.exit_code == Some(0) {
+        RunStatus::Succeeded
+    } else {
+        RunStatus::Failed
+    };
+    let record = RunRecord {
+        tool: tool.id.clone(),
+        profile: tool.profile.clone(),
+        started_at,
+        duration_secs: started.elapsed().as_secs(),
+        status,
+        exit_code: run.exit_code,
+        log,
+        final_message: final_message(&run.output),
+    };
+
+    // The run may have taken a long time; reload so changes made meanwhile,
+    // e.g. by other runs, are kept
+    let mut state = XlaudeState::load()?;
+    if let Some(info) = state.worktrees.get_mut(&key) {
+        info.last_run = Some(record.clone());
+        state.save()?;
+    }
+
+    output::set("name", &info.name);
+    output::set("repo", &info.repo_name);
+    output::set("profile", &tool.profile);
+    output::set("run", &record);
+    output::action(format!("ran {}", tool.name));
+
+    let elapsed = format!("{}s", record.duration_secs);
+    match status {
+        RunStatus::Succeeded => {
+            say!("{} {} finished in {}", "✅".green(), tool.name, elapsed);
+            Ok(())
+        }
+        RunStatus::Failed => {
+            let code = run.exit_code.unwrap_or(1);
+            output::set_exit_code(code);
+            let status = run.exit_code.map_or_else(
+                || "was killed".to_string(),
+                |code| format!("exited with status {code}"),
+            );
+            Err(coded(
+                ErrorCode::AgentFailed,
+                format!(
+                    "{} {} after {}, see {}",
+                    tool.name,
+                    status,
+                    elapsed,
+                    record.log.display()
+                ),
+            )
+            .into())
+        }
+        RunStatus::TimedOut => {
+            output::set_exit_code(TIMEOUT_EXIT_CODE);
+            Err(coded(
+                ErrorCode::Timeout,
+                format!(
+                    "{} was stopped after {}, see {}",
+                    tool.name,
+                    elapsed,
+                    record.log.display()
+                ),
+            )
+            .into())
+        }
+    }
+}
+
+/// New log file for a run, `logs/<repo>/<name>-<time>.log` in the config directory
+///
+/// The file is created here so runs started in the same second get a
+/// numbered name instead of sharing one.
+fn log_path(repo_name: &str, name: &str, started_at: chrono::DateTime<Utc>) -> Result<PathBuf> {
+    let dir = get_config_dir()?.join("logs").join(repo_name);
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create log directory {}", dir.display()))?;
+    let stem = format!("{}-{}", name, started_at.format("%Y%m%d-%H%M%S"));
+    for attempt in 1.. {
+        let path = match attempt {
+            1 => dir.join(format!("{stem}.log")),
+            n => dir.join(format!("{stem}-{n}.log")),
+        };
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(_) => return Ok(path),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to create log file {}", path.display()));
+            }
+        }
+    }
+    unreachable!("attempts are unbounded")
+}
+
+/// The last paragraph of the tool's output, where print modes put the answer
+fn final_message(output: &str) -> Option<String> {
+    let paragraph = output
+        .trim()
+        .rsplit("\n\n")
+        .map(str::trim)
+        .find(|paragraph| !paragraph.is_empty())?;
+    Some(paragraph.chars().take(MAX_FINAL_MESSAGE).collect())
+}
@@ -0,0 +1,228 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Local, Utc};
+use colored::Colorize;
+use regex::RegexBuilder;
+use serde::Serialize;
+
+use crate::claude::{claude_project_dir, transcript_files};
+use crate::output::{self, ErrorCode, coded};
+use crate::search::{Role, SearchCache};
+use crate::state::XlaudeState;
+
+/// Characters of context shown on each side of a match
+const SNIPPET_CONTEXT: usize = 60;
+
+pub struct SearchOptions {
+    pub query: String,
+    pub repo: Option<String>,
+    pub since: Option<Duration>,
+    pub regex: bool,
+    pub case_sensitive: bool,
+    pub limit: usize,
+}
+
+#[derive(Serialize)]
+struct SearchMatch {
+    repo: String,
+    worktree: String,
+    session: String,
+    timestamp: Option<DateTime<Utc>>,
+    role: Role,
+    snippet: String,
+    /// Byte range of the match within `snippet`
+    #[serde(skip)]
+    highlight: (usize, usize),
+}
+
+pub fn handle_search(options: SearchOptions) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let pattern = if options.regex {
+        options.query.clone()
+    } else {
+        regex::escape(&options.query)
+    };
+    let matcher = RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .build()
+        .with_context(|| {
+            coded(
+                ErrorCode::Error,
+                format!("Invalid search pattern '{}'", options.query),
+            )
+        })?;
+    let cutoff = options.since.map(|since| Utc::now() - since);
+
+    let mut cache = SearchCache::load();
+    let mut matches = Vec::new();
+    for info in state.worktrees.values() {
+        if options.repo.as_ref().is_some_and(|r| info.repo_name != *r) {
+            continue;
+        }
+        let Some(project_dir) = claude_project_dir(&info.path) else {
+            continue;
+        };
+        for path in transcript_files(&project_dir) {
+            let session = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            for message in cache.messages(&path, cutoff) {
+                if cutoff.is_some_and(|cutoff| message.timestamp.is_none_or(|ts| ts < cutoff)) {
+                    continue;
+                }
+                let Some(found) = matcher.find(&message.text) else {
+                    continue;
+                };
+                let (snippet, highlight) = snippet(&message.text, found.start(), found.end());
+                matches.push(SearchMatch {
+                    repo: info.repo_name.clone(),
+                    worktree: info.name.clone(),
+                    session: session.clone(),
+                    timestamp: message.timestamp,
+                    role: message.role,
+                    snippet,
+                    highlight,
+                });
+            }
+        }
+    }
+    cache.prune();
+    cache.save();
+
+    // Most recent first
+    matches.sort_by_key(|m| std::cmp::Reverse(m.timestamp));
+    let total = matches.len();
+    if options.limit > 0 {
+        matches.truncate(options.limit);
+    }
+
+    if output::is_json() {
+        output::set("query", &options.query);
+        output::set("total", total);
+        output::set("matches", &matches);
+        return Ok(());
+    }
+
+    if matches.is_empty() {
+        println!("{} No messages match '{}'", "🔍".yellow(), options.query);
+        return Ok(());
+    }
+
+    for m in &matches {
+        print_match(m);
+    }
+    if total > matches.len() {
+        println!();
+        println!(
+            "  {} Showing {} of {} matches, use --limit to see more",
+            "*".yellow(),
+            matches.len(),
+            total
+        );
+    }
+    Ok(())
+}
+
+fn print_match(m: &SearchMatch) {
+    let timestamp = m.timestamp.map_or_else(
+        || "unknown".to_string(),
+        |ts| {
+            ts.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        },
+    );
+    let role = match m.role {
+        Role::User => "user",
+        Role::Assistant => "assistant",
+    };
+    println!(
+        "{} {}/{} {} {} {}",
+        "•".green(),
+        m.repo,
+        m.worktree.cyan(),
+        short_id(&m.session).bright_black(),
+        timestamp.bright_black(),
+        role.bright_black(),
+    );
+
+    let (start, end) = m.highlight;
+    let line = format!(
+        "{}{}{}",
+        &m.snippet[..start],
+        m.snippet[start..end].yellow().bold(),
+        &m.snippet[end..]
+    );
+    println!("    {line}");
+}
+
+fn short_id(id: &str) -> &str {
+    id.get(..8).unwrap_or(id)
+}
+
+/// The text around a match on a single line, with the match's byte range in it
+fn snippet(text: &str, start: usize, end: usize) -> (String, (usize, usize)) {
+    let before: String = {
+        let chars: Vec<char> = text[..start].chars().collect();
+        let skip = chars.len().saturating_sub(SNIPPET_CONTEXT);
+        let mut before: String = chars[skip..].iter().collect();
+        if skip > 0 {
+            before.insert_str(0, "...");
+        }
+        before
+    };
+    let after: String = {
+        let mut chars = text[end..].chars();
+        let mut after: String = chars.by_ref().take(SNIPPET_CONTEXT).collect();
+        if chars.next().is_some() {
+            after.push_str("...");
+        }
+        after
+    };
+
+    let flatten = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ");
+    let leading = if before.ends_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+    let trailing = if after.starts_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+    let before = flatten(&before) + leading;
+    let found = flatten(&text[start..end]);
+    let after = trailing.to_string() + &flatten(&after);
+
+    let range = (before.len(), before.len() + found.len());
+    (format!("{before}{found}{after}"), range)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippet() {
+        let text = format!(
+            "{}\nrefactor the  auth middleware\n{}",
+            "a".repeat(100),
+            "b".repeat(100)
+        );
+        let (snippet, (from, to)) = snippet_of(&text, "auth");
+        assert!(snippet.starts_with("..."));
+        assert!(snippet.ends_with("..."));
+        assert!(snippet.contains(" refactor the auth middleware "));
+        assert_eq!(&snippet[from..to], "auth");
+
+        let (snippet, (from, to)) = snippet_of("Fix the Auth bug", "Auth");
+        assert_eq!(snippet, "Fix the Auth bug");
+        assert_eq!(&snippet[from..to], "Auth");
+    }
+
+    fn snippet_of(text: &str, needle: &str) -> (String, (usize, usize)) {
+        let start = text.find(needle).unwrap();
+        snippet(text, start, start + needle.len())
+    }
+}
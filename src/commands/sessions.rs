@@ -0,0 +1,250 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, Utc};
+use clap::Subcommand;
+use colored::Colorize;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use crate::claude::{claude_project_dir, transcript_files};
+use crate::commands::delete::get_main_repo_path;
+use crate::git::find_worktree_for_branch;
+use crate::input::select_worktree;
+use crate::output::{self, ErrorCode, coded, say};
+use crate::session_provider::{relocate_sessions, same_path};
+use crate::state::{WorktreeInfo, XlaudeState};
+use crate::transcript::{SessionDetails, read_transcript};
+use crate::utils::{execute_in_dir, format_duration_short};
+
+#[derive(Subcommand)]
+pub enum SessionsAction {
+    /// Move session history recorded at an old path to the worktree's current path
+    Relocate {
+        /// Path the worktree had when the sessions were recorded
+        old_path: PathBuf,
+        /// Name of the worktree
+        name: String,
+    },
+}
+
+pub fn handle_sessions(name: Option<String>) -> Result<()> {
+    let state = XlaudeState::load()?;
+
+    let (_, info) = select_worktree(&state, name, "Select a worktree")?;
+
+    let sessions = collect_sessions(&info);
+
+    if output::is_json() {
+        output::set("name", &info.name);
+        output::set("sessions", &sessions);
+        return Ok(());
+    }
+
+    if sessions.is_empty() {
+        println!(
+            "{} No Claude sessions found for '{}'",
+            "📭".yellow(),
+            info.name.cyan()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{} Claude sessions for {}/{} ({})",
+        "💬".green(),
+        info.repo_name,
+        info.name.cyan().bold(),
+        sessions.len()
+    );
+    for session in &sessions {
+        print_session(session);
+    }
+    Ok(())
+}
+
+pub fn handle_sessions_relocate(old_path: PathBuf, name: String) -> Result<()> {
+    let mut state = XlaudeState::load()?;
+    let (key, info) = state
+        .worktrees
+        .iter()
+        .find(|(_, w)| w.name == name)
+        .map(|(key, info)| (key.clone(), info.clone()))
+        .context(coded(
+            ErrorCode::NotFound,
+            format!("Worktree '{name}' not found"),
+        ))?;
+    let old_path = absolute_path(&old_path)?;
+
+    // After `git worktree move` the recorded path is stale, ask git where the branch went
+    let new_path = if info.path.exists() {
+        info.path.clone()
+    } else {
+        let repo_path = get_main_repo_path(&info)?;
+        execute_in_dir(&repo_path, || find_worktree_for_branch(&info.branch))
+            .ok()
+            .flatten()
+            .context(coded(
+                ErrorCode::NotFound,
+                format!(
+                    "Worktree '{name}' no longer exists at {} and no worktree has branch '{}' checked out",
+                    info.path.display(),
+                    info.branch
+                ),
+            ))?
+    };
+
+    if same_path(&old_path, &new_path) {
+        return Err(coded(
+            ErrorCode::InvalidState,
+            format!(
+                "'{}' is the current path of worktree '{name}'",
+                old_path.display()
+            ),
+        )
+        .into());
+    }
+
+    if new_path != info.path {
+        if let Some(worktree) = state.worktrees.get_mut(&key) {
+            worktree.path = new_path.clone();
+        }
+        state.save()?;
+        say!(
+            "{} Updated path of '{}' to {}",
+            "📁".green(),
+            name.cyan(),
+            new_path.display()
+        );
+        output::action(format!("updated path of worktree {key}"));
+    }
+
+    output::set("name", &name);
+    output::set("old_path", &old_path);
+    output::set("new_path", &new_path);
+    let relocated = relocate_worktree_sessions(&old_path, &new_path);
+    output::set("relocated", &relocated);
+    Ok(())
+}
+
+/// Absolute form of a path that may no longer exist, with `.` and `..` resolved lexically
+fn absolute_path(path: &Path) -> Result<PathBuf> {
+    let mut absolute = PathBuf::new();
+    for component in std::path::absolute(path)?.components() {
+        match component {
+            Component::ParentDir => {
+                absolute.pop();
+            }
+            Component::CurDir => {}
+            component => absolute.push(component),
+        }
+    }
+    Ok(absolute)
+}
+
+/// Move the session history of every tool from `old` to `new`, reporting
+/// how many sessions each tool had
+pub fn relocate_worktree_sessions(old: &Path, new: &Path) -> BTreeMap<&'static str, usize> {
+    let mut relocated = BTreeMap::new();
+    for (tool, result) in relocate_sessions(old, new) {
+        match result {
+            Ok(0) => {}
+            Ok(count) => {
+                say!("  {} {}: moved {} session(s)", "✓".green(), tool, count);
+                output::action(format!("relocated {count} {tool} session(s)"));
+                relocated.insert(tool, count);
+            }
+            Err(e) => {
+                say!("  {} {}: {:#}", "⚠️".yellow(), tool, e);
+                output::warn(format!("Failed to relocate {tool} sessions: {e:#}"));
+            }
+        }
+    }
+    if relocated.is_empty() {
+        say!(
+            "{} No sessions recorded at {}",
+            "📭".yellow(),
+            old.display()
+        );
+    }
+    relocated
+}
+
+/// Details of every session of a worktree, most recent first
+fn collect_sessions(info: &WorktreeInfo) -> Vec<SessionDetails> {
+    let Some(project_dir) = claude_project_dir(&info.path) else {
+        return vec![];
+    };
+
+    let mut sessions: Vec<SessionDetails> = transcript_files(&project_dir)
+        .into_iter()
+        .filter_map(|path| {
+            let records = read_transcript(&path).ok()?;
+            let id = path.file_stem()?.to_string_lossy().to_string();
+            Some(SessionDetails::from_records(id, &records))
+        })
+        .collect();
+
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.ended_at));
+    sessions
+}
+
+fn print_session(session: &SessionDetails) {
+    println!();
+    println!("  {} {}", "•".green(), session.id.bright_black());
+    if let Some(summary) = &session.summary {
+        print_field("Summary", summary);
+    }
+
+    let started = format_time(session.started_at);
+    let duration = match (session.started_at, session.ended_at) {
+        (Some(start), Some(end)) => format!(" ({})", format_duration_short(end - start)),
+        _ => String::new(),
+    };
+    print_field(
+        "Time",
+        &format!("{started} → {}{duration}", format_time(session.ended_at)),
+    );
+    print_field(
+        "Messages",
+        &format!(
+            "{} user, {} assistant",
+            session.user_messages, session.assistant_messages
+        ),
+    );
+    if !session.tools.is_empty() {
+        let mut tools: Vec<(&String, &usize)> = session.tools.iter().collect();
+        tools.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        let tools: Vec<String> = tools
+            .into_iter()
+            .map(|(name, count)| format!("{name} ×{count}"))
+            .collect();
+        print_field("Tools", &tools.join(", "));
+    }
+    if !session.models.is_empty() {
+        let models: Vec<&str> = session.models.iter().map(String::as_str).collect();
+        print_field("Model", &models.join(", "));
+    }
+    if let Some(prompt) = &session.first_prompt {
+        let prompt = prompt.lines().next().unwrap_or_default();
+        let prompt = if prompt.chars().count() > 70 {
+            format!("{}...", prompt.chars().take(67).collect::<String>())
+        } else {
+            prompt.to_string()
+        };
+        print_field("Prompt", &prompt);
+    }
+}
+
+fn format_time(timestamp: Option<DateTime<Utc>>) -> String {
+    timestamp.map_or_else(
+        || "unknown".to_string(),
+        |ts| {
+            ts.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        },
+    )
+}
+
+fn print_field(label: &str, value: &str) {
+    println!("    {} {}", format!("{label}:").bright_black(), value);
+}
@@ -0,0 +1,260 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::ai_tools::worktree_status_profile;
+use crate::claude_status::{ClaudeStatus, StatusProfile, detect_session_status};
+use crate::git_status::{WorktreeStatus, collect_status, commits_since, diff_stat, dirty_files};
+use crate::input::select_worktree;
+use crate::output;
+use crate::session_provider::{SessionInfo, get_sessions};
+use crate::state::{WorktreeInfo, XlaudeState};
+use crate::tmux::{SessionInfo as TmuxSession, TmuxManager};
+use crate::utils::format_duration_short;
+
+/// Everything known about a single worktree
+#[derive(Serialize)]
+struct WorktreeReport {
+    key: String,
+    #[serde(flatten)]
+    info: WorktreeInfo,
+    status: WorktreeStatus,
+    commits_since_base: Vec<String>,
+    diff_stat: Option<String>,
+    dirty_files: Vec<String>,
+    sessions: Vec<SessionReport>,
+    tmux: TmuxReport,
+}
+
+#[derive(Serialize)]
+struct SessionReport {
+    tool: &'static str,
+    id: String,
+    first_user_message: String,
+    first_timestamp: Option<DateTime<Utc>>,
+    last_user_message: String,
+    last_timestamp: Option<DateTime<Utc>>,
+}
+
+impl From<SessionInfo> for SessionReport {
+    fn from(session: SessionInfo) -> Self {
+        Self {
+            tool: session.tool,
+            id: session.id,
+            first_user_message: session.first_user_message,
+            first_timestamp: session.first_timestamp,
+            last_user_message: session.last_user_message,
+            last_timestamp: session.last_timestamp,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct TmuxReport {
+    running: bool,
+    attached: bool,
+    last_activity: Option<DateTime<Utc>>,
+    /// Detected agent state, see `ClaudeStatus::as_str`
+    status: &'static str,
+}
+
+pub fn handle_show(name: Option<String>) -> Result<()> {
+    let state = XlaudeState::load()?;
+
+    let (key, info) = select_worktree(&state, name, "Select a worktree")?;
+
+    let profile = worktree_status_profile(&state, &info);
+    let report = collect_report(key, info, profile);
+
+    if output::is_json() {
+        output::set("worktree", &report);
+    } else {
+        print_report(&report);
+    }
+    Ok(())
+}
+
+fn collect_report(key: String, info: WorktreeInfo, profile: StatusProfile) -> WorktreeReport {
+    let status = collect_status(&info);
+    let (commits_since_base, diff_stat) = match &status.base_branch {
+        Some(base) if info.path.exists() => {
+            (commits_since(&info.path, base), diff_stat(&info.path, base))
+        }
+        _ => (Vec::new(), None),
+    };
+
+    let tmux = TmuxManager::new();
+    let session = tmux
+        .list_sessions()
+        .unwrap_or_default()
+        .into_iter()
+        .find(|s| s.belongs_to(&info.name));
+    let agent = if session.is_some() {
+        detect_session_status(&tmux, &info.name, profile)
+    } else {
+        ClaudeStatus::NotRunning
+    };
+
+    WorktreeReport {
+        key,
+        dirty_files: dirty_files(&info.path),
+        sessions: get_sessions(&info.path)
+            .into_iter()
+            .map(SessionReport::from)
+            .collect(),
+        tmux: TmuxReport {
+            running: session.is_some(),
+            attached: session.as_ref().is_some_and(|s| s.is_attached),
+            last_activity: session
+                .as_ref()
+                .and_then(|s| DateTime::from_timestamp(s.last_activity, 0)),
+            status: agent.as_str(),
+        },
+        status,
+        commits_since_base,
+        diff_stat,
+        info,
+    }
+}
+
+fn print_report(report: &WorktreeReport) {
+    let info = &report.info;
+    let status = &report.status;
+
+    println!(
+        "{} {}/{}",
+        "🌳".green(),
+        info.repo_name,
+        info.name.cyan().bold()
+    );
+    print_field("Branch", &info.branch);
+    print_field("Path", &info.path.display().to_string());
+    print_field(
+        "Created",
+        &info.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+    );
+    if !info.tags.is_empty() {
+        print_field("Tags", &info.tags.join(", "));
+    }
+    if let Some(run) = &info.last_run {
+        print_field(
+            "Last run",
+            &format!(
+                "{} {} ({}s, {}), log: {}",
+                run.tool,
+                run.status.as_str(),
+                run.duration_secs,
+                run.started_at.format("%Y-%m-%d %H:%M:%S"),
+                run.log.display()
+            ),
+        );
+    }
+
+    let base = match (&status.base_branch, status.base) {
+        (Some(branch), Some(base)) => format!("{branch} (↑{} ↓{})", base.ahead, base.behind),
+        (Some(branch), None) => branch.clone(),
+        _ => "unknown".to_string(),
+    };
+    print_field("Base", &base);
+    if let Some(upstream) = status.upstream {
+        print_field(
+            "Upstream",
+            &format!("↑{} ↓{}", upstream.ahead, upstream.behind),
+        );
+    }
+    print_field("Merged", if status.merged { "yes" } else { "no" });
+    if let Some(pr) = &status.pull_request {
+        let mut text = format!("#{} {} ({}, {})", pr.number, pr.state, pr.review, pr.checks);
+        if let Some(url) = &pr.url {
+            text.push_str(&format!(" {url}"));
+        }
+        print_field("Pull request", &text);
+    }
+
+    let base_name = status.base_branch.as_deref().unwrap_or("base");
+    print_section(
+        &format!("Commits since {base_name}"),
+        &report.commits_since_base,
+    );
+    let diff_stat: Vec<String> = report
+        .diff_stat
+        .as_deref()
+        .map(|stat| stat.lines().map(|l| l.trim().to_string()).collect())
+        .unwrap_or_default();
+    print_section(&format!("Diff stat vs {base_name}"), &diff_stat);
+    print_section("Dirty files", &report.dirty_files);
+
+    println!();
+    println!("  {} ({})", "Agent sessions".bold(), report.sessions.len());
+    for session in &report.sessions {
+        println!(
+            "    {} {} {}",
+            "•".green(),
+            session.tool,
+            session.id.bright_black()
+        );
+        print_message(
+            "first",
+            session.first_timestamp,
+            &session.first_user_message,
+        );
+        print_message("last", session.last_timestamp, &session.last_user_message);
+    }
+
+    println!();
+    let tmux = &report.tmux;
+    let tmux_state = if !tmux.running {
+        "not running".bright_black().to_string()
+    } else {
+        let activity = tmux
+            .last_activity
+            .map(|at| format!(", active {}", TmuxSession::format_time(at.timestamp())))
+            .unwrap_or_default();
+        format!(
+            "running ({}{activity}), agent {}",
+            if tmux.attached {
+                "attached"
+            } else {
+                "detached"
+            },
+            tmux.status
+        )
+    };
+    println!("  {} {}", "tmux session:".bold(), tmux_state);
+}
+
+fn print_field(label: &str, value: &str) {
+    println!("  {} {}", format!("{label}:").bright_black(), value);
+}
+
+fn print_section(title: &str, lines: &[String]) {
+    println!();
+    if lines.is_empty() {
+        println!("  {} {}", title.bold(), "(none)".bright_black());
+        return;
+    }
+    println!("  {}", title.bold());
+    for line in lines {
+        println!("    {line}");
+    }
+}
+
+fn print_message(label: &str, timestamp: Option<DateTime<Utc>>, message: &str) {
+    let when = timestamp.map_or_else(
+        || "unknown".to_string(),
+        |ts| format!("{} ago", format_duration_short(Utc::now() - ts)),
+    );
+    let message = message.lines().next().unwrap_or_default();
+    let message = if message.chars().count() > 70 {
+        format!("{}...", message.chars().take(67).collect::<String>())
+    } else {
+        message.to_string()
+    };
+    println!(
+        "      {} {} {}",
+        format!("{label}:").bright_black(),
+        when.bright_black(),
+        message
+    );
+}
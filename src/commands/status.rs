@@ -0,0 +1,246 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::ai_tools::{DASHBOARD_TOOL, status_profile, worktree_status_profile};
+use crate::claude_status::{ClaudeStatus, detect_session_status};
+use crate::output;
+use crate::state::{WorktreeInfo, XlaudeState, get_config_dir};
+use crate::tmux::TmuxManager;
+use crate::utils::expand_template;
+
+/// How long cached agent states are reused, overridable with `XLAUDE_STATUS_TTL`
+const DEFAULT_CACHE_TTL_SECS: i64 = 5;
+
+const CACHE_FILE: &str = "status-cache.json";
+
+/// Agent states the counts are reported for, in display order
+const COUNTED_STATES: [ClaudeStatus; 4] = [
+    ClaudeStatus::WaitingForInput,
+    ClaudeStatus::Processing,
+    ClaudeStatus::Error,
+    ClaudeStatus::Idle,
+];
+
+/// Agent state of every running xlaude tmux session, cached between prompts
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StatusCache {
+    updated_at: Option<DateTime<Utc>>,
+    /// tmux project name to `ClaudeStatus::as_str`
+    sessions: BTreeMap<String, String>,
+}
+
+impl StatusCache {
+    fn path() -> Result<PathBuf> {
+        Ok(get_config_dir()?.join(CACHE_FILE))
+    }
+
+    /// Load the cache if it is younger than the TTL, otherwise query tmux
+    fn load_or_refresh() -> Self {
+        let ttl = std::env::var("XLAUDE_STATUS_TTL")
+            .ok()
+            .and_then(|ttl| ttl.parse().ok())
+            .unwrap_or(DEFAULT_CACHE_TTL_SECS);
+
+        if let Some(cache) = Self::load()
+            && cache
+                .updated_at
+                .is_some_and(|at| Utc::now().signed_duration_since(at).num_seconds() < ttl)
+        {
+            return cache;
+        }
+
+        let cache = Self::refresh();
+        // A missing cache only makes the next prompt slower
+        let _ = cache.save();
+        cache
+    }
+
+    fn load() -> Option<Self> {
+        let content = fs::read_to_string(Self::path().ok()?).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    fn refresh() -> Self {
+        let tmux = TmuxManager::new();
+        let state = XlaudeState::load().unwrap_or_default();
+        let sessions = tmux
+            .list_sessions()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|session| {
+                let profile = match state
+                    .worktrees
+                    .values()
+                    .find(|info| session.belongs_to(&info.name))
+                {
+                    Some(info) => worktree_status_profile(&state, info),
+                    None => status_profile(&state.tools, DASHBOARD_TOOL),
+                };
+                let status = detect_session_status(&tmux, &session.project, profile);
+                (session.project, status.as_str().to_string())
+            })
+            .collect();
+
+        Self {
+            updated_at: Some(Utc::now()),
+            sessions,
+        }
+    }
+
+    fn status_of(&self, worktree_name: &str) -> &str {
+        let safe_name = worktree_name.replace(['-', '.'], "_");
+        self.sessions
+            .get(&safe_name)
+            .or_else(|| self.sessions.get(worktree_name))
+            .map_or(ClaudeStatus::NotRunning.as_str(), String::as_str)
+    }
+
+    fn count(&self, status: &ClaudeStatus) -> usize {
+        self.sessions
+            .values()
+            .filter(|s| s.as_str() == status.as_str())
+            .count()
+    }
+
+    /// Non-zero counts, e.g. `2 waiting, 1 processing`
+    fn summary(&self) -> String {
+        COUNTED_STATES
+            .iter()
+            .filter_map(|status| {
+                let count = self.count(status);
+                (count > 0).then(|| format!("{count} {}", status.as_str()))
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Find the managed worktree containing the current directory without running git
+fn current_worktree(state: &XlaudeState) -> Option<&WorktreeInfo> {
+    let cwd = std::env::current_dir().ok()?;
+    let cwd = cwd.canonicalize().unwrap_or(cwd);
+
+    state
+        .worktrees
+        .values()
+        .filter(|info| {
+            let path = info
+                .path
+                .canonicalize()
+                .unwrap_or_else(|_| info.path.clone());
+            cwd.starts_with(path)
+        })
+        // Prefer the most specific path if worktrees are nested
+        .max_by_key(|info| info.path.components().count())
+}
+
+pub fn handle_status(format: Option<String>, counts: bool) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let worktree = current_worktree(&state);
+
+    // Nothing to report outside a worktree unless counts were asked for
+    if worktree.is_none() && !counts && format.is_none() && !output::is_json() {
+        return Ok(());
+    }
+
+    let cache = StatusCache::load_or_refresh();
+    let status = worktree.map(|info| cache.status_of(&info.name));
+
+    if output::is_json() {
+        output::set(
+            "worktree",
+            worktree.map(|info| {
+                serde_json::json!({
+                    "name": info.name,
+                    "repo": info.repo_name,
+                    "branch": info.branch,
+                    "path": info.path,
+                    "status": status,
+                })
+            }),
+        );
+        output::set(
+            "counts",
+            COUNTED_STATES
+                .iter()
+                .map(|s| (s.as_str(), cache.count(s)))
+                .collect::<BTreeMap<_, _>>(),
+        );
+        return Ok(());
+    }
+
+    let line = if let Some(template) = &format {
+        expand_template(template, |field| {
+            Some(match field {
+                "name" => worktree.map(|w| w.name.clone()).unwrap_or_default(),
+                "repo" => worktree.map(|w| w.repo_name.clone()).unwrap_or_default(),
+                "branch" => worktree.map(|w| w.branch.clone()).unwrap_or_default(),
+                "status" => status.unwrap_or_default().to_string(),
+                "counts" => cache.summary(),
+                "waiting" => cache.count(&ClaudeStatus::WaitingForInput).to_string(),
+                "processing" => cache.count(&ClaudeStatus::Processing).to_string(),
+                "error" => cache.count(&ClaudeStatus::Error).to_string(),
+                "idle" => cache.count(&ClaudeStatus::Idle).to_string(),
+                "running" => cache.sessions.len().to_string(),
+                _ => return None,
+            })
+        })
+    } else {
+        let mut parts = Vec::new();
+        if let Some(info) = worktree {
+            parts.push(format!(
+                "{} ({}) {}",
+                info.name,
+                info.branch,
+                status.unwrap_or_default()
+            ));
+        }
+        if counts {
+            let summary = cache.summary();
+            if !summary.is_empty() {
+                parts.push(format!("[{summary}]"));
+            }
+        }
+        parts.join(" ")
+    };
+
+    if !line.is_empty() {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_cache_summary() {
+        let cache = StatusCache {
+            updated_at: None,
+            sessions: BTreeMap::from([
+                ("feature_x".to_string(), "waiting".to_string()),
+                ("bugfix".to_string(), "waiting".to_string()),
+                ("docs".to_string(), "processing".to_string()),
+            ]),
+        };
+
+        assert_eq!(cache.summary(), "2 waiting, 1 processing");
+        assert_eq!(cache.status_of("feature-x"), "waiting");
+        assert_eq!(cache.status_of("other"), "not_running");
+        assert_eq!(StatusCache::default().summary(), "");
+    }
+}
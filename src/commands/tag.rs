@@ -0,0 +1,39 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+use crate::output::{self, ErrorCode, coded, say};
+use crate::state::XlaudeState;
+
+pub fn handle_tag(name: String, tags: Vec<String>, remove: bool) -> Result<()> {
+    let mut state = XlaudeState::load()?;
+
+    let info = state
+        .worktrees
+        .values_mut()
+        .find(|w| w.name == name)
+        .context(coded(
+            ErrorCode::NotFound,
+            format!("Worktree '{name}' not found"),
+        ))?;
+
+    for tag in tags {
+        if remove {
+            info.tags.retain(|t| *t != tag);
+        } else if !info.tags.contains(&tag) {
+            info.tags.push(tag);
+        }
+    }
+    info.tags.sort();
+
+    output::set("name", &info.name);
+    output::set("tags", &info.tags);
+
+    let summary = if info.tags.is_empty() {
+        "no tags".dimmed().to_string()
+    } else {
+        info.tags.join(", ").cyan().to_string()
+    };
+    say!("{} {}: {}", "🏷️ ".green(), info.name.cyan(), summary);
+
+    state.save()
+}
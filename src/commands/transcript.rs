@@ -0,0 +1,529 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, Utc};
+use clap::ValueEnum;
+use regex::{Captures, Regex};
+use std::path::PathBuf;
+
+use crate::claude::{claude_project_dir, transcript_files};
+use crate::input::select_worktree;
+use crate::output::{self, ErrorCode, coded};
+use crate::state::{WorktreeInfo, XlaudeState};
+use crate::transcript::{Entry, conversation, read_transcript};
+
+/// Tool output longer than this many characters is cut by default
+pub const DEFAULT_MAX_OUTPUT: usize = 2000;
+
+/// Secrets that are always redacted. A `secret` group limits the
+/// replacement to that part of the match.
+const BUILTIN_REDACTIONS: &[&str] = &[
+    r"-----BEGIN [A-Z ]*PRIVATE KEY-----[\s\S]*?-----END [A-Z ]*PRIVATE KEY-----",
+    r"sk-ant-[A-Za-z0-9_-]{20,}",
+    r"sk-[A-Za-z0-9]{32,}",
+    r"gh[pousr]_[A-Za-z0-9]{36,}",
+    r"github_pat_[A-Za-z0-9_]{22,}",
+    r"glpat-[A-Za-z0-9_-]{20,}",
+    r"xox[abprs]-[A-Za-z0-9-]{10,}",
+    r"AKIA[0-9A-Z]{16}",
+    r#"(?i)(?:password|passwd|secret|token|api[_-]?key)["']?\s*[:=]\s*["']?(?P<secret>[^\s"']{6,})"#,
+];
+
+const REDACTED: &str = "[REDACTED]";
+
+/// JSON keys whose values are redacted whatever they look like, matched
+/// case-insensitively as the whole key or its last `_`-separated part
+const SENSITIVE_KEYS: &[&str] = &[
+    "password",
+    "passwd",
+    "token",
+    "secret",
+    "api_key",
+    "apikey",
+    "authorization",
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum TranscriptFormat {
+    #[default]
+    Md,
+    Html,
+    Json,
+}
+
+pub struct TranscriptOptions {
+    pub session: Option<String>,
+    pub format: TranscriptFormat,
+    /// Extra patterns on top of the built-in and configured ones
+    pub redact: Vec<String>,
+    /// Characters kept of each tool input and output, 0 for no limit
+    pub max_output: usize,
+}
+
+pub fn handle_transcript(name: Option<String>, options: TranscriptOptions) -> Result<()> {
+    let state = XlaudeState::load()?;
+
+    let (_, info) = select_worktree(&state, name, "Select a worktree")?;
+
+    let redactor = Redactor::new(
+        state
+            .redact_patterns
+            .iter()
+            .chain(&options.redact)
+            .map(String::as_str),
+    )?;
+
+    let path = find_session(&info, options.session.as_deref())?;
+    let id = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let records = read_transcript(&path)
+        .with_context(|| format!("Failed to read transcript {}", path.display()))?;
+    let entries: Vec<Entry> = conversation(&records)
+        .into_iter()
+        .map(|entry| sanitize(entry, &redactor, options.max_output))
+        .collect();
+
+    let export = Export {
+        id: &id,
+        worktree: &format!("{}/{}", info.repo_name, info.name),
+        entries: &entries,
+    };
+    let content = match options.format {
+        TranscriptFormat::Md => export.markdown(),
+        TranscriptFormat::Html => export.html(),
+        TranscriptFormat::Json => export.json()?,
+    };
+
+    if output::is_json() {
+        output::set("name", &info.name);
+        output::set("session", &id);
+        output::set(
+            "format",
+            options
+                .format
+                .to_possible_value()
+                .map(|v| v.get_name().to_string()),
+        );
+        output::set("content", &content);
+    } else {
+        print!("{content}");
+    }
+    Ok(())
+}
+
+/// Transcript of the given session (by id prefix), or the most recently modified one
+fn find_session(info: &WorktreeInfo, session: Option<&str>) -> Result<PathBuf> {
+    let files = claude_project_dir(&info.path)
+        .map(|dir| transcript_files(&dir))
+        .unwrap_or_default();
+    if files.is_empty() {
+        return Err(coded(
+            ErrorCode::NotFound,
+            format!("No Claude sessions found for '{}'", info.name),
+        )
+        .into());
+    }
+
+    let Some(session) = session else {
+        return Ok(files
+            .into_iter()
+            .max_by_key(|path| path.metadata().and_then(|m| m.modified()).ok())
+            .expect("files is not empty"));
+    };
+
+    let mut matches: Vec<PathBuf> = files
+        .into_iter()
+        .filter(|path| {
+            path.file_stem()
+                .is_some_and(|stem| stem.to_string_lossy().starts_with(session))
+        })
+        .collect();
+    match matches.len() {
+        0 => Err(coded(
+            ErrorCode::NotFound,
+            format!("Session '{session}' not found for '{}'", info.name),
+        )
+        .into()),
+        1 => Ok(matches.remove(0)),
+        n => Err(coded(
+            ErrorCode::InvalidState,
+            format!("Session id '{session}' is ambiguous ({n} sessions match)"),
+        )
+        .into()),
+    }
+}
+
+/// Replaces secrets in exported text
+struct Redactor {
+    patterns: Vec<Regex>,
+}
+
+impl Redactor {
+    fn new<'a>(extra: impl IntoIterator<Item = &'a str>) -> Result<Self> {
+        let patterns = BUILTIN_REDACTIONS
+            .iter()
+            .copied()
+            .chain(extra)
+            .map(|pattern| {
+                Regex::new(pattern).with_context(|| {
+                    coded(
+                        ErrorCode::Error,
+                        format!("Invalid redaction pattern '{pattern}'"),
+                    )
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { patterns })
+    }
+
+    fn apply(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for pattern in &self.patterns {
+            text = pattern
+                .replace_all(&text, |caps: &Captures| match caps.name("secret") {
+                    Some(secret) => {
+                        let whole = caps.get(0).expect("group 0 always matches");
+                        let start = secret.start() - whole.start();
+                        let end = secret.end() - whole.start();
+                        let matched = whole.as_str();
+                        format!("{}{REDACTED}{}", &matched[..start], &matched[end..])
+                    }
+                    None => REDACTED.to_string(),
+                })
+                .into_owned();
+        }
+        text
+    }
+
+    fn apply_value(&self, value: &mut serde_json::Value, max_chars: usize) {
+        match value {
+            serde_json::Value::String(text) => *text = truncate(&self.apply(text), max_chars),
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    self.apply_value(item, max_chars);
+                }
+            }
+            serde_json::Value::Object(map) => {
+                for (key, item) in map.iter_mut() {
+                    if is_sensitive_key(key) && (item.is_string() || item.is_number()) {
+                        *item = serde_json::Value::String(REDACTED.to_string());
+                    } else {
+                        self.apply_value(item, max_chars);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn is_sensitive_key(key: &str) -> bool {
+    let key = key.to_lowercase().replace('-', "_");
+    SENSITIVE_KEYS.iter().any(|sensitive| {
+        key == *sensitive
+            || key
+                .strip_suffix(sensitive)
+                .is_some_and(|prefix| prefix.ends_with('_'))
+    })
+}
+
+/// Cut text to `max_chars` characters, noting how much was left out
+fn truncate(text: &str, max_chars: usize) -> String {
+    let total = text.chars().count();
+    if max_chars == 0 || total <= max_chars {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max_chars).collect();
+    format!("{kept}\n… [truncated {} characters]", total - max_chars)
+}
+
+fn sanitize(entry: Entry, redactor: &Redactor, max_output: usize) -> Entry {
+    match entry {
+        Entry::Prompt { timestamp, text } => Entry::Prompt {
+            timestamp,
+            text: redactor.apply(&text),
+        },
+        Entry::Assistant { timestamp, text } => Entry::Assistant {
+            timestamp,
+            text: redactor.apply(&text),
+        },
+        Entry::ToolCall {
+            timestamp,
+            name,
+            mut input,
+            result,
+            is_error,
+        } => {
+            redactor.apply_value(&mut input, max_output);
+            Entry::ToolCall {
+                timestamp,
+                name,
+                input,
+                result: result.map(|result| truncate(&redactor.apply(&result), max_output)),
+                is_error,
+            }
+        }
+    }
+}
+
+/// A sanitized session ready to be rendered
+struct Export<'a> {
+    id: &'a str,
+    worktree: &'a str,
+    entries: &'a [Entry],
+}
+
+impl Export<'_> {
+    fn time_range(&self) -> (Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
+        let mut timestamps = self.entries.iter().filter_map(|entry| match entry {
+            Entry::Prompt { timestamp, .. }
+            | Entry::Assistant { timestamp, .. }
+            | Entry::ToolCall { timestamp, .. } => *timestamp,
+        });
+        let first = timestamps.next();
+        (first, timestamps.next_back().or(first))
+    }
+
+    fn markdown(&self) -> String {
+        let (started, ended) = self.time_range();
+        let mut out = format!("# Claude session {}\n\n", self.id);
+        out.push_str(&format!("- Worktree: {}\n", self.worktree));
+        out.push_str(&format!("- Started: {}\n", format_time(started)));
+        out.push_str(&format!("- Ended: {}\n", format_time(ended)));
+
+        let mut in_assistant_turn = false;
+        for entry in self.entries {
+            match entry {
+                Entry::Prompt { timestamp, text } => {
+                    in_assistant_turn = false;
+                    out.push_str(&format!("\n## 👤 User · {}\n\n", format_time(*timestamp)));
+                    out.push_str(text.trim_end());
+                    out.push('\n');
+                }
+                Entry::Assistant { text, .. } => {
+                    assistant_heading(&mut out, &mut in_assistant_turn);
+                    out.push('\n');
+                    out.push_str(text.trim_end());
+                    out.push('\n');
+                }
+                Entry::ToolCall {
+                    name,
+                    input,
+                    result,
+                    is_error,
+                    ..
+                } => {
+                    assistant_heading(&mut out, &mut in_assistant_turn);
+                    out.push_str(&format!(
+                        "\n<details>\n<summary>🔧 {}</summary>\n\n",
+                        escape_html(&tool_summary(name, input, *is_error))
+                    ));
+                    out.push_str(&fenced(&pretty_json(input), "json"));
+                    if let Some(result) = result {
+                        out.push_str(if *is_error {
+                            "\nError:\n\n"
+                        } else {
+                            "\nResult:\n\n"
+                        });
+                        out.push_str(&fenced(result, ""));
+                    }
+                    out.push_str("\n</details>\n");
+                }
+            }
+        }
+        out
+    }
+
+    fn html(&self) -> String {
+        let (started, ended) = self.time_range();
+        let mut out = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        out.push_str(&format!(
+            "<title>Claude session {}</title>\n",
+            escape_html(self.id)
+        ));
+        out.push_str(HTML_STYLE);
+        out.push_str("</head>\n<body>\n");
+        out.push_str(&format!(
+            "<h1>Claude session {}</h1>\n<p class=\"meta\">{} · {} → {}</p>\n",
+            escape_html(self.id),
+            escape_html(self.worktree),
+            format_time(started),
+            format_time(ended)
+        ));
+
+        for entry in self.entries {
+            match entry {
+                Entry::Prompt { timestamp, text } => out.push_str(&format!(
+                    "<div class=\"user\"><h2>👤 User <time>{}</time></h2><div class=\"text\">{}</div></div>\n",
+                    format_time(*timestamp),
+                    escape_html(text.trim_end())
+                )),
+                Entry::Assistant { text, .. } => out.push_str(&format!(
+                    "<div class=\"assistant\"><div class=\"text\">{}</div></div>\n",
+                    escape_html(text.trim_end())
+                )),
+                Entry::ToolCall {
+                    name,
+                    input,
+                    result,
+                    is_error,
+                    ..
+                } => {
+                    out.push_str(&format!(
+                        "<details class=\"tool{}\"><summary>🔧 {}</summary>\n<pre>{}</pre>\n",
+                        if *is_error { " error" } else { "" },
+                        escape_html(&tool_summary(name, input, *is_error)),
+                        escape_html(&pretty_json(input))
+                    ));
+                    if let Some(result) = result {
+                        out.push_str(&format!("<pre class=\"result\">{}</pre>\n", escape_html(result)));
+                    }
+                    out.push_str("</details>\n");
+                }
+            }
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    fn json(&self) -> Result<String> {
+        let value = serde_json::json!({
+            "session": self.id,
+            "worktree": self.worktree,
+            "entries": self.entries,
+        });
+        Ok(serde_json::to_string_pretty(&value)? + "\n")
+    }
+}
+
+const HTML_STYLE: &str = "<style>
+body { font-family: -apple-system, sans-serif; max-width: 960px; margin: 2em auto; padding: 0 1em; color: #24292f; }
+.meta, time { color: #6e7781; font-size: 0.9em; font-weight: normal; }
+.user { border-left: 4px solid #0969da; padding: 0 1em; margin: 1.5em 0; }
+.user h2 { font-size: 1em; }
+.assistant { padding: 0 1em; margin: 1em 0; }
+.text { white-space: pre-wrap; }
+details.tool { margin: 0.5em 1em; }
+details.tool summary { cursor: pointer; font-family: monospace; }
+details.error summary { color: #cf222e; }
+pre { background: #f6f8fa; padding: 0.75em; overflow-x: auto; white-space: pre-wrap; }
+</style>
+";
+
+fn assistant_heading(out: &mut String, in_assistant_turn: &mut bool) {
+    if !*in_assistant_turn {
+        out.push_str("\n## 🤖 Assistant\n");
+        *in_assistant_turn = true;
+    }
+}
+
+/// Tool name with the most telling argument, e.g. `Bash: cargo test`
+fn tool_summary(name: &str, input: &serde_json::Value, is_error: bool) -> String {
+    let argument = [
+        "command",
+        "file_path",
+        "path",
+        "pattern",
+        "url",
+        "description",
+    ]
+    .iter()
+    .find_map(|key| input.get(key).and_then(|v| v.as_str()))
+    .and_then(|arg| arg.lines().next())
+    .map(|arg| {
+        if arg.chars().count() > 80 {
+            format!("{}...", arg.chars().take(77).collect::<String>())
+        } else {
+            arg.to_string()
+        }
+    });
+
+    let mut summary = match argument {
+        Some(argument) => format!("{name}: {argument}"),
+        None => name.to_string(),
+    };
+    if is_error {
+        summary.push_str(" (failed)");
+    }
+    summary
+}
+
+fn pretty_json(value: &serde_json::Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
+
+/// Markdown code block with a fence longer than any backtick run in `text`
+fn fenced(text: &str, lang: &str) -> String {
+    let longest_run = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!("{fence}{lang}\n{}\n{fence}\n", text.trim_end())
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn format_time(timestamp: Option<DateTime<Utc>>) -> String {
+    timestamp.map_or_else(
+        || "unknown".to_string(),
+        |ts| {
+            ts.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redactor() {
+        let redactor = Redactor::new(["internal\\.example\\.com"]).unwrap();
+        assert_eq!(
+            redactor.apply("export ANTHROPIC_API_KEY=sk-ant-REDACTED"),
+            "export ANTHROPIC_API_KEY=[REDACTED]"
+        );
+        assert_eq!(
+            redactor.apply(r#"{"password": "hunter2hunter2"}"#),
+            r#"{"password": "[REDACTED]"}"#
+        );
+        assert_eq!(
+            redactor.apply("curl https://internal.example.com/api"),
+            "curl https://[REDACTED]/api"
+        );
+        assert_eq!(redactor.apply("nothing to see"), "nothing to see");
+        assert!(Redactor::new(["("]).is_err());
+
+        // Values under sensitive keys are redacted even when they look harmless
+        let mut input = serde_json::json!({
+            "password": "hunter2",
+            "headers": {"Authorization": "Basic abc", "X-Api-Key": 12345},
+            "github_token": "t",
+            "input_tokens": 120,
+            "command": "ls",
+        });
+        redactor.apply_value(&mut input, 0);
+        assert_eq!(
+            input,
+            serde_json::json!({
+                "password": "[REDACTED]",
+                "headers": {"Authorization": "[REDACTED]", "X-Api-Key": "[REDACTED]"},
+                "github_token": "[REDACTED]",
+                "input_tokens": 120,
+                "command": "ls",
+            })
+        );
+    }
+
+    #[test]
+    fn test_truncate_and_fence() {
+        assert_eq!(truncate("abcdef", 0), "abcdef");
+        assert_eq!(truncate("abcdef", 4), "abcd\n… [truncated 2 characters]");
+        assert_eq!(fenced("a ``` b", ""), "````\na ``` b\n````\n");
+    }
+}
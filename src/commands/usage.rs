@@ -0,0 +1,144 @@
+use anyhow::Result;
+use chrono::{Duration, Local, Utc};
+use clap::ValueEnum;
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::claude::get_claude_sessions;
+use crate::output;
+use crate::state::XlaudeState;
+use crate::usage::{PriceTable, UsageTotals, format_cost, format_tokens};
+
+/// How `xlaude usage` groups token usage
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum UsageGroup {
+    Repo,
+    #[default]
+    Worktree,
+    Session,
+    Day,
+}
+
+#[derive(Serialize)]
+struct UsageRow {
+    key: String,
+    #[serde(flatten)]
+    totals: UsageTotals,
+}
+
+pub fn handle_usage(since: Option<Duration>, by: UsageGroup) -> Result<()> {
+    let state = XlaudeState::load()?;
+    let prices = PriceTable::from_state(&state);
+    let cutoff = since.map(|since| Utc::now() - since);
+
+    let mut groups: BTreeMap<String, UsageTotals> = BTreeMap::new();
+    let mut total = UsageTotals::default();
+    let mut unpriced_models = BTreeSet::new();
+
+    for (key, info) in &state.worktrees {
+        for session in get_claude_sessions(&info.path) {
+            for bucket in &session.usage {
+                // Buckets are hourly, so keep the hour the cutoff falls into
+                if cutoff.is_some_and(|cutoff| bucket.hour + Duration::hours(1) <= cutoff) {
+                    continue;
+                }
+                let group = match by {
+                    UsageGroup::Repo => info.repo_name.clone(),
+                    UsageGroup::Worktree => key.clone(),
+                    UsageGroup::Session => format!("{key} {}", session.id),
+                    UsageGroup::Day => bucket
+                        .hour
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d")
+                        .to_string(),
+                };
+                groups.entry(group).or_default().add(&prices, bucket);
+                total.add(&prices, bucket);
+                if prices.lookup(&bucket.model).is_none() {
+                    unpriced_models.insert(bucket.model.clone());
+                }
+            }
+        }
+    }
+
+    let mut rows: Vec<UsageRow> = groups
+        .into_iter()
+        .map(|(key, totals)| UsageRow { key, totals })
+        .collect();
+    // Most expensive first, except days which read best in order
+    if by != UsageGroup::Day {
+        rows.sort_by(|a, b| b.totals.cost_usd.total_cmp(&a.totals.cost_usd));
+    }
+
+    let unpriced_models: Vec<String> = unpriced_models.into_iter().collect();
+    if !unpriced_models.is_empty() {
+        output::warn(format!(
+            "No price configured for {}; their tokens are not included in the cost",
+            unpriced_models.join(", ")
+        ));
+    }
+
+    if output::is_json() {
+        output::set(
+            "by",
+            by.to_possible_value().map(|v| v.get_name().to_string()),
+        );
+        output::set("since", cutoff);
+        output::set("groups", &rows);
+        output::set("total", &total);
+        return Ok(());
+    }
+
+    if rows.is_empty() {
+        println!("{} No token usage found", "📭".yellow());
+        return Ok(());
+    }
+
+    let width = rows
+        .iter()
+        .map(|row| row.key.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Total".len());
+    println!(
+        "  {:<width$}  {:>8}  {:>8}  {:>8}  {:>8}  {:>9}",
+        "",
+        "input".bright_black(),
+        "output".bright_black(),
+        "cache w".bright_black(),
+        "cache r".bright_black(),
+        "cost".bright_black(),
+    );
+    for row in &rows {
+        print_row(&row.key, &row.totals, width);
+    }
+    println!();
+    print_row("Total", &total, width);
+    if !unpriced_models.is_empty() {
+        println!();
+        println!(
+            "  {} No price configured for {}; set model_prices in state.json",
+            "*".yellow(),
+            unpriced_models.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn print_row(label: &str, totals: &UsageTotals, width: usize) {
+    let usage = &totals.usage;
+    let mut cost = format_cost(totals.cost_usd);
+    if totals.unpriced_tokens > 0 {
+        cost.push('*');
+    }
+    println!(
+        "  {:<width$}  {:>8}  {:>8}  {:>8}  {:>8}  {:>9}",
+        label,
+        format_tokens(usage.input_tokens),
+        format_tokens(usage.output_tokens),
+        format_tokens(usage.cache_creation_input_tokens),
+        format_tokens(usage.cache_read_input_tokens),
+        cost.green(),
+    );
+}
@@ -32,7 +32,7 @@ _xlaude() {{
     fi
 
     # Main commands
-    local commands="create open delete add rename list clean dir dashboard completions"
+    local commands="create open run delete add rename list show sessions transcript search usage tag clean gc status dir dashboard doctor completions"
 
     # Complete main commands
     if [[ $cword -eq 1 ]]; then
@@ -42,7 +42,7 @@ _xlaude() {{
 
     # Complete subcommand arguments
     case "${{words[1]}}" in
-        open|dir|delete)
+        open|run|dir|delete|show|sessions|transcript|tag)
             if [[ $cword -eq 2 ]]; then
                 # Get worktree names for completion
                 local worktrees=$(xlaude complete-worktrees 2>/dev/null)
@@ -78,13 +78,23 @@ _xlaude() {{
     commands=(
         'create:Create a new git worktree'
         'open:Open an existing worktree and launch Claude'
+        'run:Run an agent on a task without a terminal'
         'delete:Delete a worktree and clean up'
         'add:Add current worktree to xlaude management'
         'rename:Rename a worktree'
         'list:List all active Claude instances'
-        'clean:Clean up invalid worktrees from state'
+        'show:Show everything about a worktree'
+        'sessions:List Claude sessions of a worktree'
+        'transcript:Export a Claude session'
+        'search:Search the Claude transcripts of all worktrees'
+        'usage:Report Claude token usage and cost'
+        'tag:Add or remove tags on a worktree'
+        'clean:Clean up invalid worktrees and prune stale ones'
+        'gc:Prune worktrees according to the retention policy'
+        'status:Print the current worktree and agent status'
         'dir:Get the directory path of a worktree'
         'dashboard:Launch interactive dashboard for managing Claude sessions'
+        'doctor:Check git, tmux, AI tools and xlaude files for problems'
         'completions:Generate shell completions'
     )
 
@@ -96,7 +106,7 @@ _xlaude() {{
 
     # Subcommand argument completion
     case "${{words[2]}}" in
-        open|dir|delete)
+        open|run|dir|delete|show|sessions|transcript|tag)
             if (( CURRENT == 3 )); then
                 _xlaude_worktrees
             fi
@@ -172,13 +182,23 @@ complete -c xlaude -f
 # Main commands
 complete -c xlaude -n "__fish_use_subcommand" -a create -d "Create a new git worktree"
 complete -c xlaude -n "__fish_use_subcommand" -a open -d "Open an existing worktree and launch Claude"
+complete -c xlaude -n "__fish_use_subcommand" -a run -d "Run an agent on a task without a terminal"
 complete -c xlaude -n "__fish_use_subcommand" -a delete -d "Delete a worktree and clean up"
 complete -c xlaude -n "__fish_use_subcommand" -a add -d "Add current worktree to xlaude management"
 complete -c xlaude -n "__fish_use_subcommand" -a rename -d "Rename a worktree"
 complete -c xlaude -n "__fish_use_subcommand" -a list -d "List all active Claude instances"
-complete -c xlaude -n "__fish_use_subcommand" -a clean -d "Clean up invalid worktrees from state"
+complete -c xlaude -n "__fish_use_subcommand" -a show -d "Show everything about a worktree"
+complete -c xlaude -n "__fish_use_subcommand" -a sessions -d "List Claude sessions of a worktree"
+complete -c xlaude -n "__fish_use_subcommand" -a transcript -d "Export a Claude session"
+complete -c xlaude -n "__fish_use_subcommand" -a search -d "Search the Claude transcripts of all worktrees"
+complete -c xlaude -n "__fish_use_subcommand" -a usage -d "Report Claude token usage and cost"
+complete -c xlaude -n "__fish_use_subcommand" -a tag -d "Add or remove tags on a worktree"
+complete -c xlaude -n "__fish_use_subcommand" -a clean -d "Clean up invalid worktrees and prune stale ones"
+complete -c xlaude -n "__fish_use_subcommand" -a gc -d "Prune worktrees according to the retention policy"
+complete -c xlaude -n "__fish_use_subcommand" -a status -d "Print the current worktree and agent status"
 complete -c xlaude -n "__fish_use_subcommand" -a dir -d "Get the directory path of a worktree"
 complete -c xlaude -n "__fish_use_subcommand" -a dashboard -d "Launch interactive dashboard for managing Claude sessions"
+complete -c xlaude -n "__fish_use_subcommand" -a doctor -d "Check git, tmux, AI tools and xlaude files for problems"
 complete -c xlaude -n "__fish_use_subcommand" -a completions -d "Generate shell completions"
 
 # Function to get worktree completions with repo markers
@@ -201,7 +221,7 @@ function __xlaude_worktrees_simple
 end
 
 # Worktree completions for commands
-complete -c xlaude -n "__fish_seen_subcommand_from open dir delete" -a "(__xlaude_worktrees)"
+complete -c xlaude -n "__fish_seen_subcommand_from open run dir delete show sessions transcript tag" -a "(__xlaude_worktrees)"
 complete -c xlaude -n "__fish_seen_subcommand_from rename" -n "not __fish_seen_argument_from (__xlaude_worktrees_simple)" -a "(__xlaude_worktrees)"
 
 # Shell completions for completions command
@@ -0,0 +1,181 @@
+//! Locating programs without spawning `which`, and asking them their version.
+//!
+//! Lookups are cached for the life of the process: PATH does not change
+//! while xlaude runs, and a tool is often looked up several times.
+
+use std::collections::HashMap;
+use std::env;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{LazyLock, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a program may take to print its version
+const VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+
+static RESOLVED: LazyLock<Mutex<HashMap<String, Option<PathBuf>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+static VERSIONS: LazyLock<Mutex<HashMap<PathBuf, Option<String>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// The executable `command` runs: a path is checked as is, a bare name is
+/// searched for in PATH
+pub fn find_executable(command: &str) -> Option<PathBuf> {
+    let mut resolved = RESOLVED.lock().unwrap_or_else(|e| e.into_inner());
+    resolved
+        .entry(command.to_string())
+        .or_insert_with(|| resolve(command, env::var_os("PATH").as_deref()))
+        .clone()
+}
+
+fn resolve(command: &str, path: Option<&std::ffi::OsStr>) -> Option<PathBuf> {
+    if command.is_empty() {
+        return None;
+    }
+    let command = Path::new(command);
+    if command.components().count() > 1 {
+        return candidates(command)
+            .into_iter()
+            .find(|path| is_executable(path));
+    }
+    env::split_paths(path?)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .flat_map(|dir| candidates(&dir.join(command)))
+        .find(|path| is_executable(path))
+}
+
+/// Files a command may be, trying the extensions in PATHEXT on Windows
+fn candidates(path: &Path) -> Vec<PathBuf> {
+    if !cfg!(windows) || path.extension().is_some() {
+        return vec![path.to_path_buf()];
+    }
+    let extensions = env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+    extensions
+        .split(';')
+        .filter(|ext| !ext.is_empty())
+        .map(|ext| {
+            let mut file = path.as_os_str().to_os_string();
+            file.push(ext);
+            PathBuf::from(file)
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// First line `program` prints when asked for its version with `flag`,
+/// asked once per program
+pub fn probe_version(program: &Path, flag: &str) -> Option<String> {
+    let cached = VERSIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(program)
+        .cloned();
+    if let Some(version) = cached {
+        return version;
+    }
+    // The lock is not held while the program runs, so a slow program only
+    // delays its own lookup
+    let version = run_version(program, flag, VERSION_TIMEOUT);
+    VERSIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(program.to_path_buf())
+        .or_insert(version)
+        .clone()
+}
+
+/// Run `program flag`, giving up on (and killing) a program that hangs
+fn run_version(program: &Path, flag: &str, timeout: Duration) -> Option<String> {
+    let mut child = Command::new(program)
+        .arg(flag)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .ok()?;
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(10)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    };
+    if !status.success() {
+        return None;
+    }
+
+    let mut stdout = String::new();
+    let mut stderr = String::new();
+    child.stdout.take()?.read_to_string(&mut stdout).ok()?;
+    child.stderr.take()?.read_to_string(&mut stderr).ok()?;
+    // Some programs print their version on stderr
+    [stdout, stderr]
+        .iter()
+        .map(|text| text.trim())
+        .find(|text| !text.is_empty())
+        .and_then(|text| text.lines().next().map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_in_path() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        let write = |dir: &Path, name: &str, mode: u32| {
+            let file = dir.join(name);
+            std::fs::write(&file, "#!/bin/sh\necho 'tool 1.2.3'\n").unwrap();
+            std::fs::set_permissions(&file, std::fs::Permissions::from_mode(mode)).unwrap();
+            file
+        };
+        // Files that cannot be executed are skipped like a shell would
+        write(first.path(), "tool", 0o644);
+        let tool = write(second.path(), "tool", 0o755);
+
+        let path = env::join_paths([first.path(), second.path()]).unwrap();
+        assert_eq!(resolve("tool", Some(&path)), Some(tool.clone()));
+        assert_eq!(resolve("missing", Some(&path)), None);
+        assert_eq!(resolve(tool.to_str().unwrap(), None), Some(tool.clone()));
+        assert_eq!(resolve("", Some(&path)), None);
+
+        assert_eq!(
+            probe_version(&tool, "--version").as_deref(),
+            Some("tool 1.2.3")
+        );
+
+        // A program that hangs is killed instead of blocking the caller
+        let hang = first.path().join("hang");
+        std::fs::write(&hang, "#!/bin/sh\nexec sleep 30\n").unwrap();
+        std::fs::set_permissions(&hang, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let started = Instant::now();
+        assert_eq!(
+            run_version(&hang, "--version", Duration::from_millis(200)),
+            None
+        );
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,841 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::ai_tools::is_command_available;
+use crate::git::{RemoteUrl, execute_git, parse_remote_url};
+use crate::state::XlaudeState;
+
+/// Code hosting platforms xlaude knows how to query for pull request status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeKind {
+    GitHub,
+    GitLab,
+    Gitea,
+}
+
+impl ForgeKind {
+    /// Guess the forge from a well-known host name
+    fn from_host(host: &str) -> Option<Self> {
+        if host == "github.com" || host.starts_with("github.") {
+            Some(ForgeKind::GitHub)
+        } else if host.contains("gitlab") {
+            Some(ForgeKind::GitLab)
+        } else if host.contains("gitea") || host == "codeberg.org" {
+            Some(ForgeKind::Gitea)
+        } else {
+            None
+        }
+    }
+
+    fn token_env_vars(&self) -> &'static [&'static str] {
+        match self {
+            ForgeKind::GitHub => &["XLAUDE_GITHUB_TOKEN", "GH_TOKEN", "GITHUB_TOKEN"],
+            ForgeKind::GitLab => &["XLAUDE_GITLAB_TOKEN", "GITLAB_TOKEN"],
+            ForgeKind::Gitea => &["XLAUDE_GITEA_TOKEN", "GITEA_TOKEN"],
+        }
+    }
+}
+
+/// Per-host forge configuration, for self-hosted instances
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForgeHost {
+    pub kind: ForgeKind,
+    /// Base URL of the REST API (defaults to the public API layout for the host)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrState {
+    Open,
+    Merged,
+    Closed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewStatus {
+    Approved,
+    ChangesRequested,
+    Pending,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Passing,
+    Failing,
+    Pending,
+    None,
+    Unknown,
+}
+
+impl fmt::Display for PrState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PrState::Open => "open",
+            PrState::Merged => "merged",
+            PrState::Closed => "closed",
+        })
+    }
+}
+
+impl fmt::Display for ReviewStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ReviewStatus::Approved => "approved",
+            ReviewStatus::ChangesRequested => "changes requested",
+            ReviewStatus::Pending => "review pending",
+            ReviewStatus::Unknown => "review unknown",
+        })
+    }
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CheckStatus::Passing => "checks passing",
+            CheckStatus::Failing => "checks failing",
+            CheckStatus::Pending => "checks pending",
+            CheckStatus::None => "no checks",
+            CheckStatus::Unknown => "checks unknown",
+        })
+    }
+}
+
+/// Pull (or merge) request found for a branch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullRequest {
+    pub number: u64,
+    pub state: PrState,
+    pub url: Option<String>,
+    pub review: ReviewStatus,
+    pub checks: CheckStatus,
+}
+
+/// Repository on a forge, resolved from the `origin` remote
+#[derive(Debug, Clone)]
+pub struct ForgeRepo {
+    pub kind: ForgeKind,
+    pub remote: RemoteUrl,
+    pub api_url: Option<String>,
+    /// Local checkout used as working directory for CLI adapters
+    pub local_path: PathBuf,
+}
+
+/// A way of asking a forge about pull requests
+pub trait ForgeProvider {
+    /// Human readable adapter name, e.g. `gh` or `GitLab API`
+    fn name(&self) -> &str;
+
+    /// Find the most recent pull request whose head is `branch`
+    fn find_pull_request(&self, repo: &ForgeRepo, branch: &str) -> Result<Option<PullRequest>>;
+}
+
+/// Resolve the forge for the repository at `repo_path` from its `origin` remote
+///
+/// Hosts configured in `forge_hosts` take precedence over built-in host
+/// detection. Other hosts (e.g. GitHub Enterprise) must be configured there.
+pub fn resolve_repo(repo_path: &Path, state: &XlaudeState) -> Result<ForgeRepo> {
+    let path_str = repo_path
+        .to_str()
+        .context("Repository path is not valid UTF-8")?;
+    let url = execute_git(&["-C", path_str, "remote", "get-url", "origin"])?;
+    let remote = parse_remote_url(&url)
+        .with_context(|| format!("Remote '{url}' is not hosted on a forge"))?;
+
+    let (kind, api_url) = match state.forge_hosts.get(&remote.host) {
+        Some(host) => (host.kind, host.api_url.clone()),
+        None => (
+            ForgeKind::from_host(&remote.host).with_context(|| {
+                format!(
+                    "Unknown forge '{}'; add it to forge_hosts in state.json",
+                    remote.host
+                )
+            })?,
+            None,
+        ),
+    };
+
+    Ok(ForgeRepo {
+        kind,
+        remote,
+        api_url,
+        local_path: repo_path.to_path_buf(),
+    })
+}
+
+/// Pick the provider for a repository: the forge CLI when installed,
+/// otherwise the HTTP API when a token is available
+pub fn provider_for(repo: &ForgeRepo) -> Option<Box<dyn ForgeProvider>> {
+    let cli: Box<dyn ForgeProvider> = match repo.kind {
+        ForgeKind::GitHub => Box::new(GhCli),
+        ForgeKind::GitLab => Box::new(GlabCli),
+        ForgeKind::Gitea => Box::new(TeaCli),
+    };
+    if is_command_available(cli.name()) {
+        return Some(cli);
+    }
+
+    repo.kind
+        .token_env_vars()
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|t| !t.is_empty()))
+        .map(|token| Box::new(HttpForge { token }) as Box<dyn ForgeProvider>)
+}
+
+/// Look up the pull request for `branch` in the repository at `repo_path`
+///
+/// Returns `None` when the forge can't be determined, no adapter is
+/// available, or the query fails.
+pub fn find_pull_request(repo_path: &Path, branch: &str) -> Option<PullRequest> {
+    let state = XlaudeState::load().unwrap_or_default();
+    let repo = resolve_repo(repo_path, &state).ok()?;
+    let provider = provider_for(&repo)?;
+    provider.find_pull_request(&repo, branch).ok().flatten()
+}
+
+fn run_cli(program: &str, args: &[&str], dir: &Path) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .with_context(|| format!("Failed to execute {program}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("{program} failed: {}", stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// GitHub via the `gh` CLI
+pub struct GhCli;
+
+impl ForgeProvider for GhCli {
+    fn name(&self) -> &str {
+        "gh"
+    }
+
+    fn find_pull_request(&self, repo: &ForgeRepo, branch: &str) -> Result<Option<PullRequest>> {
+        let output = run_cli(
+            "gh",
+            &[
+                "pr",
+                "list",
+                "--state",
+                "all",
+                "--head",
+                branch,
+                "--limit",
+                "1",
+                "--json",
+                "number,state,url,reviewDecision,statusCheckRollup",
+            ],
+            &repo.local_path,
+        )?;
+        parse_gh_pr_list(&output)
+    }
+}
+
+/// GitLab via the `glab` CLI
+pub struct GlabCli;
+
+impl ForgeProvider for GlabCli {
+    fn name(&self) -> &str {
+        "glab"
+    }
+
+    fn find_pull_request(&self, repo: &ForgeRepo, branch: &str) -> Result<Option<PullRequest>> {
+        let output = run_cli(
+            "glab",
+            &[
+                "mr",
+                "list",
+                "--all",
+                "--source-branch",
+                branch,
+                "--output",
+                "json",
+            ],
+            &repo.local_path,
+        )?;
+        let mut mr = parse_gitlab_mr_list(&output)?;
+        if let Some(mr) = &mut mr
+            && let Ok(approvals) = run_cli(
+                "glab",
+                &[
+                    "api",
+                    &format!("projects/:id/merge_requests/{}/approvals", mr.number),
+                ],
+                &repo.local_path,
+            )
+            && let Ok(approvals) = serde_json::from_str(&approvals)
+        {
+            mr.review = summarize_gitlab_approvals(&approvals);
+        }
+        Ok(mr)
+    }
+}
+
+/// Gitea and Forgejo via the `tea` CLI
+pub struct TeaCli;
+
+impl ForgeProvider for TeaCli {
+    fn name(&self) -> &str {
+        "tea"
+    }
+
+    fn find_pull_request(&self, repo: &ForgeRepo, branch: &str) -> Result<Option<PullRequest>> {
+        let limit = GITEA_PAGE_SIZE.to_string();
+        let pr = search_pages(
+            |page| {
+                let output = run_cli(
+                    "tea",
+                    &[
+                        "pulls",
+                        "list",
+                        "--state",
+                        "all",
+                        "--limit",
+                        &limit,
+                        "--page",
+                        &page.to_string(),
+                        "--output",
+                        "json",
+                        "--fields",
+                        "index,state,head,url,ci",
+                    ],
+                    &repo.local_path,
+                )?;
+                serde_json::from_str(&output).context("Failed to parse tea output")
+            },
+            |pr| pr["head"] == branch,
+        )?;
+        Ok(pr.as_ref().map(parse_tea_pull))
+    }
+}
+
+/// Pull requests fetched per request when searching a Gitea repository.
+/// Gitea caps pages at 50 items by default, so a shorter page is the last.
+const GITEA_PAGE_SIZE: usize = 50;
+
+/// Pages searched for a branch before giving up, so a lookup stays cheap
+/// on busy repositories and ends on servers that ignore the page parameter
+const GITEA_MAX_PAGES: usize = 10;
+
+/// Search pull requests page by page for one matching `is_match`, for
+/// forges whose API can't filter by head branch
+fn search_pages(
+    mut fetch_page: impl FnMut(usize) -> Result<Vec<Value>>,
+    is_match: impl Fn(&Value) -> bool,
+) -> Result<Option<Value>> {
+    for page in 1..=GITEA_MAX_PAGES {
+        let pulls = fetch_page(page)?;
+        if let Some(pr) = pulls.iter().find(|pr| is_match(pr)) {
+            return Ok(Some(pr.clone()));
+        }
+        if pulls.len() < GITEA_PAGE_SIZE {
+            break;
+        }
+    }
+    Ok(None)
+}
+
+/// Any supported forge via its REST API, authenticated with a token
+///
+/// Requests are made with `curl` so no HTTP client is linked into xlaude.
+pub struct HttpForge {
+    token: String,
+}
+
+impl HttpForge {
+    fn api_base(&self, repo: &ForgeRepo) -> String {
+        if let Some(api_url) = &repo.api_url {
+            return api_url.trim_end_matches('/').to_string();
+        }
+        match (repo.kind, repo.remote.host.as_str()) {
+            (ForgeKind::GitHub, "github.com") => "https://api.github.com".to_string(),
+            (ForgeKind::GitHub, host) => format!("https://{host}/api/v3"),
+            (ForgeKind::GitLab, host) => format!("https://{host}/api/v4"),
+            (ForgeKind::Gitea, host) => format!("https://{host}/api/v1"),
+        }
+    }
+
+    fn get(&self, kind: ForgeKind, url: &str) -> Result<Value> {
+        let auth = match kind {
+            ForgeKind::Gitea => format!("Authorization: token {}", self.token),
+            _ => format!("Authorization: Bearer {}", self.token),
+        };
+        // The token goes through stdin (`-H @-`) so it never shows up in `ps`
+        let mut child = Command::new("curl")
+            .args(["-sSfL", "-H", "Accept: application/json", "-H", "@-", url])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to execute curl")?;
+        child
+            .stdin
+            .take()
+            .context("Failed to open curl stdin")?
+            .write_all(format!("{auth}\n").as_bytes())
+            .context("Failed to pass the token to curl")?;
+        let output = child.wait_with_output().context("Failed to execute curl")?;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "Request to {url} failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        serde_json::from_slice(&output.stdout).context("Failed to parse forge API response")
+    }
+
+    fn find_github(&self, repo: &ForgeRepo, branch: &str) -> Result<Option<PullRequest>> {
+        let base = format!("{}/repos/{}", self.api_base(repo), repo.remote.path);
+        let head = percent_encode(&format!("{}:{branch}", repo.remote.owner()));
+        let pulls = self.get(repo.kind, &format!("{base}/pulls?state=all&head={head}"))?;
+        let Some(pr) = pulls.as_array().and_then(|prs| prs.first()) else {
+            return Ok(None);
+        };
+
+        let number = pr["number"].as_u64().unwrap_or_default();
+        let state = if pr["merged_at"].is_string() {
+            PrState::Merged
+        } else if pr["state"] == "open" {
+            PrState::Open
+        } else {
+            PrState::Closed
+        };
+
+        let review = self
+            .get(repo.kind, &format!("{base}/pulls/{number}/reviews"))
+            .map_or(ReviewStatus::Unknown, |reviews| {
+                summarize_github_reviews(&reviews)
+            });
+        let checks = pr["head"]["sha"]
+            .as_str()
+            .and_then(|sha| {
+                self.get(repo.kind, &format!("{base}/commits/{sha}/status"))
+                    .ok()
+            })
+            .map_or(CheckStatus::Unknown, |status| {
+                check_status_from_str(status["state"].as_str().unwrap_or_default())
+            });
+
+        Ok(Some(PullRequest {
+            number,
+            state,
+            url: pr["html_url"].as_str().map(String::from),
+            review,
+            checks,
+        }))
+    }
+
+    fn find_gitlab(&self, repo: &ForgeRepo, branch: &str) -> Result<Option<PullRequest>> {
+        let base = format!(
+            "{}/projects/{}/merge_requests",
+            self.api_base(repo),
+            percent_encode(&repo.remote.path)
+        );
+        let mrs = self.get(
+            repo.kind,
+            &format!("{base}?state=all&source_branch={}", percent_encode(branch)),
+        )?;
+        let Some(iid) = mrs
+            .as_array()
+            .and_then(|mrs| mrs.first())
+            .and_then(|mr| mr["iid"].as_u64())
+        else {
+            return Ok(None);
+        };
+
+        // The list endpoint omits the pipeline, so fetch the single MR
+        let mr = self.get(repo.kind, &format!("{base}/{iid}"))?;
+        let mut mr = parse_gitlab_mr(&mr)?;
+        if let Ok(approvals) = self.get(repo.kind, &format!("{base}/{iid}/approvals")) {
+            mr.review = summarize_gitlab_approvals(&approvals);
+        }
+        Ok(Some(mr))
+    }
+
+    fn find_gitea(&self, repo: &ForgeRepo, branch: &str) -> Result<Option<PullRequest>> {
+        let base = format!("{}/repos/{}", self.api_base(repo), repo.remote.path);
+        // Most recently updated first, so recent branches are found early
+        let found = search_pages(
+            |page| {
+                let pulls = self.get(
+                    repo.kind,
+                    &format!(
+                        "{base}/pulls?state=all&sort=recentupdate&limit={GITEA_PAGE_SIZE}&page={page}"
+                    ),
+                )?;
+                Ok(pulls.as_array().cloned().unwrap_or_default())
+            },
+            |pr| pr["head"]["ref"] == branch,
+        )?;
+        let Some(pr) = found else {
+            return Ok(None);
+        };
+
+        let state = if pr["merged"].as_bool().unwrap_or(false) {
+            PrState::Merged
+        } else if pr["state"] == "open" {
+            PrState::Open
+        } else {
+            PrState::Closed
+        };
+        let checks = pr["head"]["sha"]
+            .as_str()
+            .and_then(|sha| {
+                self.get(repo.kind, &format!("{base}/commits/{sha}/status"))
+                    .ok()
+            })
+            .map_or(CheckStatus::Unknown, |status| {
+                check_status_from_str(status["state"].as_str().unwrap_or_default())
+            });
+
+        Ok(Some(PullRequest {
+            number: pr["number"].as_u64().unwrap_or_default(),
+            state,
+            url: pr["html_url"].as_str().map(String::from),
+            review: ReviewStatus::Unknown,
+            checks,
+        }))
+    }
+}
+
+impl ForgeProvider for HttpForge {
+    fn name(&self) -> &str {
+        "http"
+    }
+
+    fn find_pull_request(&self, repo: &ForgeRepo, branch: &str) -> Result<Option<PullRequest>> {
+        match repo.kind {
+            ForgeKind::GitHub => self.find_github(repo, branch),
+            ForgeKind::GitLab => self.find_gitlab(repo, branch),
+            ForgeKind::Gitea => self.find_gitea(repo, branch),
+        }
+    }
+}
+
+/// Parse `gh pr list --json number,state,url,reviewDecision,statusCheckRollup`
+fn parse_gh_pr_list(json: &str) -> Result<Option<PullRequest>> {
+    let prs: Vec<Value> = serde_json::from_str(json).context("Failed to parse gh output")?;
+    let Some(pr) = prs.first() else {
+        return Ok(None);
+    };
+
+    let state = match pr["state"].as_str().unwrap_or_default() {
+        "MERGED" => PrState::Merged,
+        "OPEN" => PrState::Open,
+        _ => PrState::Closed,
+    };
+    let review = match pr["reviewDecision"].as_str().unwrap_or_default() {
+        "APPROVED" => ReviewStatus::Approved,
+        "CHANGES_REQUESTED" => ReviewStatus::ChangesRequested,
+        "REVIEW_REQUIRED" => ReviewStatus::Pending,
+        _ => ReviewStatus::Unknown,
+    };
+
+    // Check runs report status/conclusion, commit statuses report state
+    let checks = pr["statusCheckRollup"]
+        .as_array()
+        .map_or(CheckStatus::Unknown, |rollup| {
+            summarize_checks(rollup.iter().map(|check| {
+                match (check["status"].as_str(), check["conclusion"].as_str()) {
+                    (Some("COMPLETED"), Some(conclusion)) => check_status_from_str(conclusion),
+                    (Some(_), _) => CheckStatus::Pending,
+                    _ => check_status_from_str(check["state"].as_str().unwrap_or_default()),
+                }
+            }))
+        });
+
+    Ok(Some(PullRequest {
+        number: pr["number"].as_u64().unwrap_or_default(),
+        state,
+        url: pr["url"].as_str().map(String::from),
+        review,
+        checks,
+    }))
+}
+
+/// Parse the merge request list printed by `glab mr list --output json`
+fn parse_gitlab_mr_list(json: &str) -> Result<Option<PullRequest>> {
+    let mrs: Vec<Value> = serde_json::from_str(json).context("Failed to parse glab output")?;
+    mrs.first().map(parse_gitlab_mr).transpose()
+}
+
+fn parse_gitlab_mr(mr: &Value) -> Result<PullRequest> {
+    let number = mr["iid"].as_u64().context("Merge request has no iid")?;
+    let state = match mr["state"].as_str().unwrap_or_default() {
+        "merged" => PrState::Merged,
+        "opened" => PrState::Open,
+        _ => PrState::Closed,
+    };
+    let checks = mr["head_pipeline"]["status"]
+        .as_str()
+        .map_or(CheckStatus::Unknown, check_status_from_str);
+    // "mergeable" is also reported when no approval is required, so only
+    // the approvals endpoint can tell that a merge request was approved
+    let review = match mr["detailed_merge_status"].as_str() {
+        Some("not_approved") => ReviewStatus::Pending,
+        _ => ReviewStatus::Unknown,
+    };
+
+    Ok(PullRequest {
+        number,
+        state,
+        url: mr["web_url"].as_str().map(String::from),
+        review,
+        checks,
+    })
+}
+
+/// Review status from a merge request's `/approvals`: approved only when
+/// somebody approved it, not merely because no approval is required
+fn summarize_gitlab_approvals(approvals: &Value) -> ReviewStatus {
+    let approved_by_anyone = approvals["approved_by"]
+        .as_array()
+        .is_some_and(|users| !users.is_empty());
+    match approvals["approved"].as_bool() {
+        Some(true) if approved_by_anyone => ReviewStatus::Approved,
+        Some(false) => ReviewStatus::Pending,
+        _ => ReviewStatus::Unknown,
+    }
+}
+
+/// Parse an entry of `tea pulls list --output json --fields index,state,head,url,ci`
+fn parse_tea_pull(pr: &Value) -> PullRequest {
+    let state = match pr["state"].as_str().unwrap_or_default() {
+        "merged" => PrState::Merged,
+        "open" => PrState::Open,
+        _ => PrState::Closed,
+    };
+    let number = pr["index"]
+        .as_u64()
+        .or_else(|| pr["index"].as_str().and_then(|i| i.parse().ok()))
+        .unwrap_or_default();
+    let checks = pr["ci"]
+        .as_str()
+        .filter(|ci| !ci.is_empty())
+        .map_or(CheckStatus::Unknown, check_status_from_str);
+
+    PullRequest {
+        number,
+        state,
+        url: pr["url"].as_str().map(String::from),
+        review: ReviewStatus::Unknown,
+        checks,
+    }
+}
+
+/// The latest review per reviewer decides the overall status
+fn summarize_github_reviews(reviews: &Value) -> ReviewStatus {
+    let mut latest = std::collections::HashMap::new();
+    for review in reviews.as_array().into_iter().flatten() {
+        if let (Some(user), Some(state)) =
+            (review["user"]["login"].as_str(), review["state"].as_str())
+            && state != "COMMENTED"
+        {
+            latest.insert(user, state);
+        }
+    }
+
+    if latest.values().any(|s| *s == "CHANGES_REQUESTED") {
+        ReviewStatus::ChangesRequested
+    } else if latest.values().any(|s| *s == "APPROVED") {
+        ReviewStatus::Approved
+    } else {
+        ReviewStatus::Pending
+    }
+}
+
+fn check_status_from_str(status: &str) -> CheckStatus {
+    match status.to_lowercase().as_str() {
+        "success" | "passed" | "neutral" | "skipped" => CheckStatus::Passing,
+        "failure" | "failed" | "error" | "cancelled" | "canceled" | "timed_out"
+        | "action_required" => CheckStatus::Failing,
+        "pending"
+        | "running"
+        | "queued"
+        | "created"
+        | "expected"
+        | "in_progress"
+        | "waiting_for_resource"
+        | "preparing"
+        | "scheduled" => CheckStatus::Pending,
+        _ => CheckStatus::Unknown,
+    }
+}
+
+fn summarize_checks(statuses: impl Iterator<Item = CheckStatus>) -> CheckStatus {
+    let statuses: Vec<_> = statuses.collect();
+    if statuses.is_empty() {
+        CheckStatus::None
+    } else if statuses.contains(&CheckStatus::Failing) {
+        CheckStatus::Failing
+    } else if statuses.contains(&CheckStatus::Pending) {
+        CheckStatus::Pending
+    } else if statuses.iter().all(|s| *s == CheckStatus::Passing) {
+        CheckStatus::Passing
+    } else {
+        CheckStatus::Unknown
+    }
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forge_kind_from_host() {
+        assert_eq!(ForgeKind::from_host("github.com"), Some(ForgeKind::GitHub));
+        assert_eq!(ForgeKind::from_host("gitlab.com"), Some(ForgeKind::GitLab));
+        assert_eq!(
+            ForgeKind::from_host("gitlab.internal.example.com"),
+            Some(ForgeKind::GitLab)
+        );
+        assert_eq!(ForgeKind::from_host("codeberg.org"), Some(ForgeKind::Gitea));
+        assert_eq!(ForgeKind::from_host("git.example.com"), None);
+    }
+
+    #[test]
+    fn test_parse_gh_pr_list() {
+        let json = r#"[{
+            "number": 42,
+            "state": "OPEN",
+            "url": "https://github.com/user/repo/pull/42",
+            "reviewDecision": "CHANGES_REQUESTED",
+            "statusCheckRollup": [
+                {"__typename": "CheckRun", "status": "COMPLETED", "conclusion": "SUCCESS"},
+                {"__typename": "StatusContext", "state": "PENDING"}
+            ]
+        }]"#;
+        let pr = parse_gh_pr_list(json).unwrap().unwrap();
+        assert_eq!(pr.number, 42);
+        assert_eq!(pr.state, PrState::Open);
+        assert_eq!(pr.review, ReviewStatus::ChangesRequested);
+        assert_eq!(pr.checks, CheckStatus::Pending);
+
+        assert!(parse_gh_pr_list("[]").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_parse_gitlab_and_tea_output() {
+        let json = r#"[{"iid": 7, "state": "merged", "web_url": "https://gitlab.com/g/r/-/merge_requests/7",
+            "head_pipeline": {"status": "failed"}}]"#;
+        let mr = parse_gitlab_mr_list(json).unwrap().unwrap();
+        assert_eq!(mr.number, 7);
+        assert_eq!(mr.state, PrState::Merged);
+        assert_eq!(mr.checks, CheckStatus::Failing);
+
+        let pr = parse_tea_pull(&serde_json::json!(
+            {"index": "5", "state": "merged", "head": "feature", "url": "u", "ci": "success"}
+        ));
+        assert_eq!(pr.number, 5);
+        assert_eq!(pr.state, PrState::Merged);
+        assert_eq!(pr.checks, CheckStatus::Passing);
+    }
+
+    #[test]
+    fn test_gitlab_review_status() {
+        // Mergeable without any approval required says nothing about review
+        let mr =
+            serde_json::json!({"iid": 1, "state": "opened", "detailed_merge_status": "mergeable"});
+        assert_eq!(parse_gitlab_mr(&mr).unwrap().review, ReviewStatus::Unknown);
+
+        let approvals = |approved: bool, by: usize| {
+            serde_json::json!({
+                "approved": approved,
+                "approved_by": (0..by).map(|i| serde_json::json!({"user": {"id": i}})).collect::<Vec<_>>(),
+            })
+        };
+        assert_eq!(
+            summarize_gitlab_approvals(&approvals(true, 1)),
+            ReviewStatus::Approved
+        );
+        assert_eq!(
+            summarize_gitlab_approvals(&approvals(true, 0)),
+            ReviewStatus::Unknown
+        );
+        assert_eq!(
+            summarize_gitlab_approvals(&approvals(false, 1)),
+            ReviewStatus::Pending
+        );
+    }
+
+    #[test]
+    fn test_search_pages() {
+        let pull = |head: &str| serde_json::json!({"head": head});
+        let full_page = || vec![pull("other"); GITEA_PAGE_SIZE];
+
+        // Found on a later page
+        let mut fetched = Vec::new();
+        let found = search_pages(
+            |page| {
+                fetched.push(page);
+                Ok(if page < 3 {
+                    full_page()
+                } else {
+                    vec![pull("feature")]
+                })
+            },
+            |pr| pr["head"] == "feature",
+        )
+        .unwrap();
+        assert_eq!(found, Some(pull("feature")));
+        assert_eq!(fetched, [1, 2, 3]);
+
+        // A short page is the last one
+        let mut fetched = 0;
+        let found = search_pages(
+            |_| {
+                fetched += 1;
+                Ok(vec![pull("other")])
+            },
+            |pr| pr["head"] == "feature",
+        )
+        .unwrap();
+        assert_eq!((found, fetched), (None, 1));
+
+        // A server ignoring the page parameter doesn't keep the search going
+        let mut fetched = 0;
+        let found = search_pages(
+            |_| {
+                fetched += 1;
+                Ok(full_page())
+            },
+            |pr| pr["head"] == "feature",
+        )
+        .unwrap();
+        assert_eq!((found, fetched), (None, GITEA_MAX_PAGES));
+    }
+
+    #[test]
+    fn test_percent_encode() {
+        assert_eq!(percent_encode("group/sub repo"), "group%2Fsub%20repo");
+        assert_eq!(percent_encode("fix-1.2_a~"), "fix-1.2_a~");
+    }
+}
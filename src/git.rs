@@ -1,4 +1,5 @@
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
@@ -40,25 +41,69 @@ pub fn get_repo_name() -> Result<String> {
 }
 
 pub fn extract_repo_name_from_url(url: &str) -> Option<String> {
-    let url = url.trim();
-
-    // Remove .git suffix if present
-    let url = url.strip_suffix(".git").unwrap_or(url);
-
-    // Handle SSH URLs (git@github.com:user/repo)
-    if url.starts_with("git@") {
-        return url
-            .split(':')
-            .nth(1)
-            .and_then(|path| path.split('/').next_back())
-            .map(|s| s.to_string());
+    RemoteUrl::parse(url).map(|remote| remote.name().to_string())
+}
+
+/// Host and repository path parsed from a remote URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteUrl {
+    /// Empty for local paths
+    pub host: String,
+    /// Repository path without `.git`, e.g. `user/repo` or `group/sub/repo`
+    pub path: String,
+}
+
+impl RemoteUrl {
+    /// Parse a remote URL or local repository path
+    ///
+    /// Supports scp-like SSH (`git@host:user/repo.git`), `ssh://`, `http(s)://`
+    /// and `file://` URLs as well as plain paths.
+    fn parse(url: &str) -> Option<Self> {
+        let url = url.trim();
+        let url = url.strip_suffix(".git").unwrap_or(url);
+
+        let (host, path) = if let Some((_, rest)) = url.split_once("://") {
+            let (authority, path) = rest.split_once('/')?;
+            // Drop user info and port
+            let host = authority.rsplit('@').next()?;
+            let host = host.split(':').next()?;
+            (host, path)
+        } else if let Some((authority, path)) = url.split_once(':')
+            && authority.contains('@')
+            && !authority.contains('/')
+        {
+            (authority.rsplit('@').next()?, path)
+        } else {
+            ("", url)
+        };
+
+        let path = path.trim_matches('/');
+        if path.is_empty() {
+            return None;
+        }
+
+        Some(RemoteUrl {
+            host: host.to_lowercase(),
+            path: path.to_string(),
+        })
     }
 
-    // Handle HTTP(S) URLs and file paths
-    url.split('/')
-        .next_back()
-        .filter(|s| !s.is_empty())
-        .map(|s| s.to_string())
+    /// Namespace part of the path (everything before the repository name)
+    pub fn owner(&self) -> &str {
+        self.path.rsplit_once('/').map_or("", |(owner, _)| owner)
+    }
+
+    /// Repository name, the last part of the path
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+}
+
+/// Parse host and path from a remote URL
+///
+/// Local paths have no host and return `None`.
+pub fn parse_remote_url(url: &str) -> Option<RemoteUrl> {
+    RemoteUrl::parse(url).filter(|remote| !remote.host.is_empty())
 }
 
 fn get_repo_name_from_directory() -> Result<String> {
@@ -171,6 +216,65 @@ pub fn has_unpushed_commits() -> bool {
     execute_git(&["log", "@{u}.."]).is_ok_and(|output| !output.is_empty())
 }
 
+/// Count commits on `branch` that are not reachable from any remote-tracking
+/// branch or any other local branch. These commits only exist on this branch
+/// and would be lost if it were deleted, even when no upstream is configured.
+pub fn count_local_only_commits(branch: &str) -> Result<usize> {
+    let exclude = format!("--exclude={branch}");
+    let output = execute_git(&[
+        "rev-list",
+        "--count",
+        branch,
+        "--not",
+        &exclude,
+        "--branches",
+        "--remotes",
+    ])?;
+    output
+        .parse()
+        .context("Failed to parse commit count from git rev-list")
+}
+
+/// List stash entries that were created on the given branch
+pub fn list_stashes_for_branch(branch: &str) -> Result<Vec<String>> {
+    let output = execute_git(&["stash", "list", "--format=%gd %gs"])?;
+    let wip_prefix = format!("WIP on {branch}:");
+    let msg_prefix = format!("On {branch}:");
+
+    Ok(output
+        .lines()
+        .filter(|line| {
+            line.split_once(' ').is_some_and(|(_, subject)| {
+                subject.starts_with(&wip_prefix) || subject.starts_with(&msg_prefix)
+            })
+        })
+        .map(std::string::ToString::to_string)
+        .collect())
+}
+
+/// List ignored files and directories in the current worktree
+pub fn list_ignored_files() -> Result<Vec<String>> {
+    let output = execute_git(&[
+        "ls-files",
+        "--others",
+        "--ignored",
+        "--exclude-standard",
+        "--directory",
+    ])?;
+    Ok(output
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(std::string::ToString::to_string)
+        .collect())
+}
+
+/// Committer date of the most recent commit on HEAD
+pub fn last_commit_date() -> Result<DateTime<Utc>> {
+    let output = execute_git(&["log", "-1", "--format=%cI"])?;
+    let date = DateTime::parse_from_rfc3339(&output).context("Failed to parse commit date")?;
+    Ok(date.with_timezone(&Utc))
+}
+
 pub fn is_in_worktree() -> Result<bool> {
     // Check if we're in a worktree by looking for .git file (not directory)
     let git_path = Path::new(".git");
@@ -201,6 +305,22 @@ pub fn list_worktrees() -> Result<Vec<PathBuf>> {
     Ok(worktrees)
 }
 
+/// Path of the worktree that has `branch` checked out
+pub fn find_worktree_for_branch(branch: &str) -> Result<Option<PathBuf>> {
+    let output = execute_git(&["worktree", "list", "--porcelain"])?;
+    let mut path = None;
+
+    for line in output.lines() {
+        if let Some(worktree) = line.strip_prefix("worktree ") {
+            path = Some(PathBuf::from(worktree));
+        } else if line.strip_prefix("branch refs/heads/") == Some(branch) {
+            return Ok(path);
+        }
+    }
+
+    Ok(None)
+}
+
 pub fn update_submodules(worktree_path: &Path) -> Result<()> {
     // Check if submodules exist
     let gitmodules = worktree_path.join(".gitmodules");
@@ -265,6 +385,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_remote_url() {
+        let parsed = parse_remote_url("git@github.com:user/my-repo.git").unwrap();
+        assert_eq!(parsed.host, "github.com");
+        assert_eq!(parsed.path, "user/my-repo");
+        assert_eq!(parsed.owner(), "user");
+
+        let parsed = parse_remote_url("https://gitlab.example.com/group/sub/repo").unwrap();
+        assert_eq!(parsed.host, "gitlab.example.com");
+        assert_eq!(parsed.owner(), "group/sub");
+
+        let parsed = parse_remote_url("ssh://git@Codeberg.org:2222/user/repo.git").unwrap();
+        assert_eq!(parsed.host, "codeberg.org");
+        assert_eq!(parsed.path, "user/repo");
+
+        assert_eq!(parse_remote_url("/path/to/repos/my-repo.git"), None);
+    }
+
     #[test]
     fn test_get_default_branch() {
         // This test will work based on the actual git repository it's run in
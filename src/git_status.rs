@@ -0,0 +1,299 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::commands::delete::get_main_repo_path;
+use crate::forge::{PrState, PullRequest, find_pull_request};
+use crate::git::execute_git;
+use crate::state::WorktreeInfo;
+
+/// Commits a branch has that another ref lacks, and vice versa
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AheadBehind {
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LastCommit {
+    pub subject: String,
+    pub committed_at: DateTime<Utc>,
+}
+
+/// Git and forge status of a single worktree
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorktreeStatus {
+    /// Number of modified, staged or untracked files
+    pub dirty_files: usize,
+    /// Ahead/behind relative to the upstream branch, if one is configured
+    pub upstream: Option<AheadBehind>,
+    /// Branch the worktree is compared against, e.g. `origin/main`
+    pub base_branch: Option<String>,
+    /// Ahead/behind relative to `base_branch`
+    pub base: Option<AheadBehind>,
+    pub last_commit: Option<LastCommit>,
+    /// Whether the branch is merged into the base branch or via a pull request
+    pub merged: bool,
+    pub pull_request: Option<PullRequest>,
+}
+
+impl WorktreeStatus {
+    pub fn is_dirty(&self) -> bool {
+        self.dirty_files > 0
+    }
+}
+
+/// Collect the status of every worktree, one thread per worktree.
+///
+/// Results are returned in the same order as `worktrees`. Pull requests
+/// are only looked up with `with_pull_requests`, since that asks the forge
+/// once per worktree; without it `merged` only reflects what git can tell.
+pub fn collect_statuses(
+    worktrees: &[&WorktreeInfo],
+    with_pull_requests: bool,
+) -> Vec<WorktreeStatus> {
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = worktrees
+            .iter()
+            .map(|info| scope.spawn(move || collect(info, with_pull_requests)))
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap_or_default())
+            .collect()
+    })
+}
+
+/// Collect the status of a single worktree, including its pull request
+pub fn collect_status(info: &WorktreeInfo) -> WorktreeStatus {
+    collect(info, true)
+}
+
+fn collect(info: &WorktreeInfo, with_pull_request: bool) -> WorktreeStatus {
+    let path = info.path.as_path();
+    if !path.exists() {
+        return WorktreeStatus::default();
+    }
+
+    let dirty_files = git(path, &["status", "--porcelain"])
+        .map(|output| output.lines().count())
+        .unwrap_or(0);
+
+    let upstream = git(
+        path,
+        &["rev-list", "--left-right", "--count", "@{u}...HEAD"],
+    )
+    .ok()
+    .and_then(|output| parse_left_right(&output));
+
+    let base_branch = detect_base_branch(path);
+    let base = base_branch
+        .as_ref()
+        .and_then(|base| ahead_behind(path, base));
+
+    let last_commit = git(path, &["log", "-1", "--format=%cI%x09%s"])
+        .ok()
+        .and_then(|output| parse_last_commit(&output));
+
+    let pull_request = with_pull_request.then(|| pull_request_for(info)).flatten();
+    let merged = base_branch
+        .as_ref()
+        .is_some_and(|base_branch| merged_into_base(path, &info.branch, base_branch, base))
+        || pull_request
+            .as_ref()
+            .is_some_and(|pr| pr.state == PrState::Merged);
+
+    WorktreeStatus {
+        dirty_files,
+        upstream,
+        base_branch,
+        base,
+        last_commit,
+        merged,
+        pull_request,
+    }
+}
+
+/// How the branch of a worktree was found to be merged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergedVia {
+    /// The base branch contains the commits of the branch
+    Base,
+    /// Its pull request was merged, possibly squashed or rebased so the
+    /// commits of the branch itself never reached the base
+    PullRequest,
+}
+
+/// Whether and how the branch of a worktree is merged, without collecting
+/// the rest of its status
+pub fn merged_via(info: &WorktreeInfo) -> Option<MergedVia> {
+    let path = info.path.as_path();
+    if !path.exists() {
+        return None;
+    }
+    if let Some(base_branch) = detect_base_branch(path)
+        && merged_into_base(
+            path,
+            &info.branch,
+            &base_branch,
+            ahead_behind(path, &base_branch),
+        )
+    {
+        return Some(MergedVia::Base);
+    }
+    // The pull request is only looked up when git can't tell
+    pull_request_for(info)
+        .filter(|pr| pr.state == PrState::Merged)
+        .map(|_| MergedVia::PullRequest)
+}
+
+/// A branch without commits ahead of its base is merged once the base moved
+/// past it, but only if it ever had commits of its own: a branch nothing was
+/// committed on yet is new, not merged
+fn merged_into_base(
+    dir: &Path,
+    branch: &str,
+    base_branch: &str,
+    base: Option<AheadBehind>,
+) -> bool {
+    base.is_some_and(|b| b.ahead == 0 && b.behind > 0) && has_own_commits(dir, branch, base_branch)
+}
+
+/// Whether commits were made on `branch` according to its reflog, or it
+/// tracks an upstream branch of its own
+fn has_own_commits(dir: &Path, branch: &str, base_branch: &str) -> bool {
+    let committed = git(
+        dir,
+        &[
+            "reflog",
+            "show",
+            "--format=%gs",
+            &format!("refs/heads/{branch}"),
+            "--",
+        ],
+    )
+    .is_ok_and(|log| {
+        log.lines()
+            .any(|entry| entry.starts_with("commit") || entry.starts_with("cherry-pick"))
+    });
+    committed
+        || git(
+            dir,
+            &[
+                "rev-parse",
+                "--abbrev-ref",
+                &format!("{branch}@{{upstream}}"),
+            ],
+        )
+        .is_ok_and(|upstream| upstream != base_branch)
+}
+
+fn pull_request_for(info: &WorktreeInfo) -> Option<PullRequest> {
+    let repo = get_main_repo_path(info).ok()?;
+    find_pull_request(&repo, &info.branch)
+}
+
+fn ahead_behind(dir: &Path, base: &str) -> Option<AheadBehind> {
+    git(
+        dir,
+        &[
+            "rev-list",
+            "--left-right",
+            "--count",
+            &format!("{base}...HEAD"),
+        ],
+    )
+    .ok()
+    .and_then(|output| parse_left_right(&output))
+}
+
+/// One-line log of the commits on HEAD that are not on `base`
+pub fn commits_since(dir: &Path, base: &str) -> Vec<String> {
+    git(dir, &["log", "--oneline", &format!("{base}..HEAD")])
+        .map(|output| output.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// `git diff --stat` of HEAD against its merge base with `base`
+pub fn diff_stat(dir: &Path, base: &str) -> Option<String> {
+    git(dir, &["diff", "--stat", &format!("{base}...HEAD")])
+        .ok()
+        .filter(|output| !output.is_empty())
+}
+
+/// Modified, staged and untracked files in `git status --short` format
+pub fn dirty_files(dir: &Path) -> Vec<String> {
+    git(dir, &["status", "--porcelain"])
+        .map(|output| output.lines().map(|line| line.trim().to_string()).collect())
+        .unwrap_or_default()
+}
+
+fn git(dir: &Path, args: &[&str]) -> anyhow::Result<String> {
+    let dir = dir.to_string_lossy();
+    let mut full_args = vec!["-C", dir.as_ref()];
+    full_args.extend_from_slice(args);
+    execute_git(&full_args)
+}
+
+/// Find the base branch without touching the network: the remote HEAD if
+/// known, otherwise the first common base branch that exists
+fn detect_base_branch(dir: &Path) -> Option<String> {
+    if let Ok(remote_head) = git(
+        dir,
+        &["symbolic-ref", "--short", "refs/remotes/origin/HEAD"],
+    ) {
+        return Some(remote_head);
+    }
+
+    ["main", "master", "develop"]
+        .iter()
+        .flat_map(|branch| [format!("origin/{branch}"), (*branch).to_string()])
+        .find(|candidate| git(dir, &["rev-parse", "--verify", "--quiet", candidate]).is_ok())
+}
+
+/// Parse `git rev-list --left-right --count <other>...HEAD` output
+fn parse_left_right(output: &str) -> Option<AheadBehind> {
+    let mut parts = output.split_whitespace();
+    let behind = parts.next()?.parse().ok()?;
+    let ahead = parts.next()?.parse().ok()?;
+    Some(AheadBehind { ahead, behind })
+}
+
+fn parse_last_commit(output: &str) -> Option<LastCommit> {
+    let (date, subject) = output.split_once('\t')?;
+    let committed_at = DateTime::parse_from_rfc3339(date).ok()?.with_timezone(&Utc);
+    Some(LastCommit {
+        subject: subject.to_string(),
+        committed_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_left_right() {
+        assert_eq!(
+            parse_left_right("3\t5"),
+            Some(AheadBehind {
+                ahead: 5,
+                behind: 3
+            })
+        );
+        assert_eq!(parse_left_right(""), None);
+        assert_eq!(parse_left_right("x\ty"), None);
+    }
+
+    #[test]
+    fn test_parse_last_commit() {
+        let commit = parse_last_commit("2024-05-01T10:00:00+02:00\tFix\tthe parser").unwrap();
+        assert_eq!(commit.subject, "Fix\tthe parser");
+        assert_eq!(
+            commit.committed_at.to_rfc3339(),
+            "2024-05-01T08:00:00+00:00"
+        );
+        assert!(parse_last_commit("not a commit").is_none());
+    }
+}
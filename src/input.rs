@@ -1,9 +1,12 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use atty::Stream;
 use dialoguer::{Confirm, Select};
 use std::io::{self, BufRead, BufReader};
 use std::sync::Mutex;
 
+use crate::output::{ErrorCode, coded};
+use crate::state::{WorktreeInfo, XlaudeState};
+
 /// Check if stdin is piped (not a terminal)
 pub fn is_piped_input() -> bool {
     !atty::is(Stream::Stdin)
@@ -158,3 +161,76 @@ pub fn drain_stdin() -> Result<()> {
     // to not inherit stdin in child processes (using Stdio::null()).
     Ok(())
 }
+
+/// Resolve the worktree a command works on: by name (or `repo/name` when
+/// several repositories have it) from the CLI argument or piped input,
+/// otherwise by interactive selection
+pub fn select_worktree(
+    state: &XlaudeState,
+    name: Option<String>,
+    prompt: &str,
+) -> Result<(String, WorktreeInfo)> {
+    if state.worktrees.is_empty() {
+        return Err(coded(
+            ErrorCode::NotFound,
+            "No worktrees found. Create one first with 'xlaude create'",
+        )
+        .into());
+    }
+
+    if let Some(n) = get_command_arg(name)? {
+        // `repo/name` picks the worktree of one repository
+        if let Some(info) = state.worktrees.get(&n) {
+            return Ok((n, info.clone()));
+        }
+
+        // Find worktree by name across all projects
+        let mut matches: Vec<(String, WorktreeInfo)> = state
+            .worktrees
+            .iter()
+            .filter(|(_, w)| w.name == n)
+            .map(|(k, w)| (k.clone(), w.clone()))
+            .collect();
+        matches.sort_by(|a, b| a.0.cmp(&b.0));
+        if matches.len() > 1 {
+            let prompt = format!("'{n}' exists in several repositories");
+            let selection = smart_select(&prompt, &matches, |(key, _)| key.clone())?;
+            return selection
+                .map(|idx| matches.swap_remove(idx))
+                .with_context(|| {
+                    let keys: Vec<&str> = matches.iter().map(|(key, _)| key.as_str()).collect();
+                    coded(
+                        ErrorCode::InputRequired,
+                        format!(
+                            "Worktree name '{n}' is ambiguous; use one of {}",
+                            keys.join(", ")
+                        ),
+                    )
+                });
+        }
+        return matches.pop().context(coded(
+            ErrorCode::NotFound,
+            format!("Worktree '{n}' not found"),
+        ));
+    }
+
+    // Interactive selection - show repo/name format
+    let worktree_list: Vec<(String, WorktreeInfo)> = state
+        .worktrees
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+
+    let selection = smart_select(prompt, &worktree_list, |(_, info)| {
+        format!("{}/{}", info.repo_name, info.name)
+    })?;
+
+    match selection {
+        Some(idx) => Ok(worktree_list[idx].clone()),
+        None => Err(coded(
+            ErrorCode::InputRequired,
+            "Interactive selection not available in non-interactive mode. Please specify a worktree name.",
+        )
+        .into()),
+    }
+}
@@ -1,27 +1,50 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use clap_complete::Shell;
+use std::fs;
+use std::path::PathBuf;
 
 mod ai_tools;
+mod cache;
 mod claude;
 mod claude_status;
 mod commands;
 mod completions;
+mod executables;
+mod forge;
 mod git;
+mod git_status;
 mod input;
+mod opencode;
+mod output;
+mod qwen;
+mod search;
+mod session_provider;
 mod state;
 mod tmux;
+mod transcript;
+mod usage;
 mod utils;
 
+use ai_tools::SessionResume;
 use commands::{
-    handle_add, handle_clean, handle_create, handle_delete, handle_dir, handle_list, handle_open,
-    handle_rename,
+    DEFAULT_MAX_OUTPUT, ListOptions, OpenOptions, PruneOptions, RunOptions, SearchOptions,
+    SessionsAction, SortKey, StatusFilter, TranscriptAction, TranscriptFormat, TranscriptOptions,
+    UsageGroup, handle_add, handle_clean, handle_create, handle_delete, handle_dir, handle_gc,
+    handle_list, handle_open, handle_rename, handle_run, handle_search, handle_sessions,
+    handle_sessions_relocate, handle_show, handle_status, handle_tag, handle_transcript,
+    handle_usage,
 };
 
 #[derive(Parser)]
 #[command(name = "xlaude")]
 #[command(about = "Manage AI coding tools with git worktrees", long_about = None)]
 struct Cli {
+    /// Output format; `json` prints a single versioned result object on stdout
+    /// and sends human-readable progress to stderr
+    #[arg(long, value_enum, global = true, default_value_t)]
+    output: output::OutputFormat,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -32,16 +55,64 @@ enum Commands {
     Create {
         /// Name for the worktree (random BIP39 word if not provided)
         name: Option<String>,
+        /// Open the worktree and send this as the agent's first message
+        #[arg(long, conflicts_with = "prompt_file")]
+        prompt: Option<String>,
+        /// Like --prompt, reading the message from a file
+        #[arg(long, value_name = "FILE")]
+        prompt_file: Option<PathBuf>,
     },
     /// Open an existing worktree and launch AI coding tool (OpenCode, Qwen Code, or Claude)
     Open {
         /// Name of the worktree to open (interactive selection if not provided)
         name: Option<String>,
+        /// Continue the most recent conversation in the worktree
+        #[arg(long = "continue", conflicts_with = "resume")]
+        continue_session: bool,
+        /// Resume a conversation by id (select from the worktree's sessions if no id is given)
+        #[arg(long, value_name = "SESSION_ID", num_args = 0..=1)]
+        resume: Option<Option<String>>,
+        /// Id of the AI tool to launch, e.g. claude (remembered for the worktree)
+        #[arg(long)]
+        tool: Option<String>,
+        /// Permission profile, e.g. yolo, ask or readonly (remembered for the worktree)
+        #[arg(long)]
+        profile: Option<String>,
+        /// Extra arguments for the AI tool, after `--`
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    /// Run an AI tool on a task without a terminal, logging its output
+    Run {
+        /// Name of the worktree to run in
+        name: String,
+        /// Task for the agent
+        #[arg(
+            long,
+            conflicts_with = "prompt_file",
+            required_unless_present = "prompt_file"
+        )]
+        prompt: Option<String>,
+        /// Like --prompt, reading the task from a file
+        #[arg(long, value_name = "FILE")]
+        prompt_file: Option<PathBuf>,
+        /// Stop the agent after this long (e.g. 30m, 2h)
+        #[arg(long, value_parser = utils::parse_duration)]
+        timeout: Option<chrono::Duration>,
+        /// Id of the AI tool to run instead of the worktree's own
+        #[arg(long)]
+        tool: Option<String>,
+        /// Permission profile to run with instead of the worktree's own
+        #[arg(long)]
+        profile: Option<String>,
     },
     /// Delete a worktree and clean up
     Delete {
         /// Name of the worktree to delete (current if not provided)
         name: Option<String>,
+        /// What to do with the worktree's Claude transcripts
+        #[arg(long, value_enum, default_value_t)]
+        transcripts: TranscriptAction,
     },
     /// Add current worktree to xlaude management
     Add {
@@ -60,14 +131,143 @@ enum Commands {
         /// Output as JSON
         #[arg(long)]
         json: bool,
+        /// Show git status (dirty files, ahead/behind, last commit, merge/PR state);
+        /// with a value, only list worktrees in that state
+        #[arg(long, value_enum, num_args = 0..=1, value_name = "STATE")]
+        status: Option<Option<StatusFilter>>,
+        /// Only list worktrees of this repository
+        #[arg(long)]
+        repo: Option<String>,
+        /// Only list worktrees with this tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Sort order
+        #[arg(long, value_enum, default_value_t)]
+        sort: SortKey,
+        /// Print one line per worktree using a template, e.g. '{repo}/{name}\t{branch}\t{status}'
+        #[arg(long, conflicts_with = "json")]
+        format: Option<String>,
+        /// Print only worktree names, one per line
+        #[arg(long, conflicts_with_all = ["json", "format"])]
+        names_only: bool,
+        /// Print only worktree names separated by NUL, for `xargs -0`
+        #[arg(short = '0', conflicts_with_all = ["json", "format"])]
+        null: bool,
+    },
+    /// Show everything about a worktree: git state, sessions and tmux
+    Show {
+        /// Name of the worktree (interactive selection if not provided)
+        name: Option<String>,
+    },
+    /// List the Claude sessions of a worktree with message and tool counts
+    #[command(args_conflicts_with_subcommands = true)]
+    Sessions {
+        /// Name of the worktree (interactive selection if not provided)
+        name: Option<String>,
+
+        #[command(subcommand)]
+        action: Option<SessionsAction>,
+    },
+    /// Export a Claude session as Markdown, HTML or JSON
+    Transcript {
+        /// Name of the worktree (interactive selection if not provided)
+        name: Option<String>,
+        /// Session id or id prefix (defaults to the most recent session)
+        #[arg(long)]
+        session: Option<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t)]
+        format: TranscriptFormat,
+        /// Extra regular expression to redact (can be repeated)
+        #[arg(long, value_name = "REGEX")]
+        redact: Vec<String>,
+        /// Characters kept of each tool input and output (0 for no limit)
+        #[arg(long, value_name = "CHARS", default_value_t = DEFAULT_MAX_OUTPUT)]
+        max_output: usize,
+    },
+    /// Search the Claude transcripts of all worktrees
+    Search {
+        /// Text to search for in prompts and replies
+        query: String,
+        /// Only search worktrees of this repository
+        #[arg(long)]
+        repo: Option<String>,
+        /// Only search messages within this period (e.g. 7d, 12h)
+        #[arg(long, value_parser = utils::parse_duration)]
+        since: Option<chrono::Duration>,
+        /// Treat the query as a regular expression
+        #[arg(short = 'e', long)]
+        regex: bool,
+        /// Match case exactly (case-insensitive by default)
+        #[arg(short = 's', long)]
+        case_sensitive: bool,
+        /// Maximum number of matches to show (0 for no limit)
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+    },
+    /// Report Claude token usage and cost
+    Usage {
+        /// Only count usage within this period (e.g. 7d, 12h)
+        #[arg(long, value_parser = utils::parse_duration)]
+        since: Option<chrono::Duration>,
+        /// Group usage by repository, worktree, session or day
+        #[arg(long, value_enum, default_value_t)]
+        by: UsageGroup,
+    },
+    /// Add or remove tags on a worktree
+    Tag {
+        /// Name of the worktree
+        name: String,
+        /// Tags to add (or remove with --remove)
+        tags: Vec<String>,
+        /// Remove the given tags instead of adding them
+        #[arg(long)]
+        remove: bool,
+    },
+    /// Clean up invalid worktrees and prune stale ones
+    Clean {
+        /// Remove worktrees created longer ago than this (e.g. 14d, 12h)
+        #[arg(long, value_parser = utils::parse_duration)]
+        older_than: Option<chrono::Duration>,
+        /// Remove worktrees whose branch is merged (git ancestry or pull request)
+        #[arg(long)]
+        merged: bool,
+        /// Remove worktrees with no commits or agent activity for this long
+        #[arg(long, value_parser = utils::parse_duration)]
+        idle: Option<chrono::Duration>,
+        /// Also remove worktrees with uncommitted or unpushed work
+        #[arg(long)]
+        force: bool,
+        /// Show what would be removed without removing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Prune worktrees according to the configured retention policy
+    Gc {
+        /// Also remove worktrees with uncommitted or unpushed work
+        #[arg(long)]
+        force: bool,
+        /// Show what would be removed without removing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Print the current worktree and agent status, for shell prompts
+    Status {
+        /// Template with {name}, {repo}, {branch}, {status}, {counts}, {waiting},
+        /// {processing}, {error}, {idle} and {running}
+        #[arg(long)]
+        format: Option<String>,
+        /// Also print agent counts across all worktrees
+        #[arg(long)]
+        counts: bool,
     },
-    /// Clean up invalid worktrees from state
-    Clean,
     /// Get the directory path of a worktree
     Dir {
         /// Name of the worktree (interactive selection if not provided)
         name: Option<String>,
     },
+    /// Check git, tmux, AI tools and xlaude's own files for problems
+    Doctor,
     /// Generate shell completions
     Completions {
         /// Shell to generate completions for
@@ -85,20 +285,188 @@ enum Commands {
     Dashboard,
 }
 
-fn main() -> Result<()> {
+impl Commands {
+    /// Name reported in the `--output json` envelope
+    fn name(&self) -> &'static str {
+        match self {
+            Commands::Create { .. } => "create",
+            Commands::Open { .. } => "open",
+            Commands::Run { .. } => "run",
+            Commands::Delete { .. } => "delete",
+            Commands::Add { .. } => "add",
+            Commands::Rename { .. } => "rename",
+            Commands::List { .. } => "list",
+            Commands::Show { .. } => "show",
+            Commands::Sessions {
+                action: Some(SessionsAction::Relocate { .. }),
+                ..
+            } => "sessions relocate",
+            Commands::Sessions { .. } => "sessions",
+            Commands::Transcript { .. } => "transcript",
+            Commands::Search { .. } => "search",
+            Commands::Usage { .. } => "usage",
+            Commands::Tag { .. } => "tag",
+            Commands::Clean { .. } => "clean",
+            Commands::Gc { .. } => "gc",
+            Commands::Status { .. } => "status",
+            Commands::Dir { .. } => "dir",
+            Commands::Doctor => "doctor",
+            Commands::Completions { .. } => "completions",
+            Commands::CompleteWorktrees { .. } => "complete-worktrees",
+            Commands::Dashboard => "dashboard",
+        }
+    }
+}
+
+fn main() {
     let cli = Cli::parse();
+    output::init(cli.output);
+
+    let command = cli.command.name();
+    let result = run(cli.command);
+    output::finish(command, &result);
 
-    match cli.command {
-        Commands::Create { name } => handle_create(name),
-        Commands::Open { name } => handle_open(name),
-        Commands::Delete { name } => handle_delete(name),
+    // In JSON mode the error is also part of the result on stdout
+    if let Err(err) = result {
+        eprintln!("Error: {err:?}");
+        std::process::exit(output::exit_code());
+    }
+}
+
+fn run(command: Commands) -> Result<()> {
+    match command {
+        Commands::Create {
+            name,
+            prompt,
+            prompt_file,
+        } => handle_create(name, read_prompt(prompt, prompt_file)?),
+        Commands::Open {
+            name,
+            continue_session,
+            resume,
+            tool,
+            profile,
+            args,
+        } => {
+            let resume = if continue_session {
+                Some(SessionResume::Continue)
+            } else {
+                resume.map(SessionResume::Resume)
+            };
+            let options = OpenOptions {
+                resume,
+                tool,
+                profile,
+                args,
+                prompt: None,
+            };
+            handle_open(name, options)
+        }
+        Commands::Run {
+            name,
+            prompt,
+            prompt_file,
+            timeout,
+            tool,
+            profile,
+        } => handle_run(RunOptions {
+            name,
+            prompt: read_prompt(prompt, prompt_file)?.unwrap_or_default(),
+            tool,
+            profile,
+            timeout,
+        }),
+        Commands::Delete { name, transcripts } => handle_delete(name, transcripts),
         Commands::Add { name } => handle_add(name),
         Commands::Rename { old_name, new_name } => handle_rename(old_name, new_name),
-        Commands::List { json } => handle_list(json),
-        Commands::Clean => handle_clean(),
+        Commands::List {
+            json,
+            status,
+            repo,
+            tag,
+            sort,
+            format,
+            names_only,
+            null,
+        } => handle_list(ListOptions {
+            json: json || output::is_json(),
+            show_status: status.is_some(),
+            status_filter: status.flatten(),
+            repo,
+            tag,
+            sort,
+            format,
+            names_only,
+            null_delimited: null,
+        }),
+        Commands::Show { name } => handle_show(name),
+        Commands::Sessions { name, action } => match action {
+            Some(SessionsAction::Relocate { old_path, name }) => {
+                handle_sessions_relocate(old_path, name)
+            }
+            None => handle_sessions(name),
+        },
+        Commands::Transcript {
+            name,
+            session,
+            format,
+            redact,
+            max_output,
+        } => handle_transcript(
+            name,
+            TranscriptOptions {
+                session,
+                format,
+                redact,
+                max_output,
+            },
+        ),
+        Commands::Search {
+            query,
+            repo,
+            since,
+            regex,
+            case_sensitive,
+            limit,
+        } => handle_search(SearchOptions {
+            query,
+            repo,
+            since,
+            regex,
+            case_sensitive,
+            limit,
+        }),
+        Commands::Usage { since, by } => handle_usage(since, by),
+        Commands::Tag { name, tags, remove } => handle_tag(name, tags, remove),
+        Commands::Clean {
+            older_than,
+            merged,
+            idle,
+            force,
+            dry_run,
+        } => handle_clean(PruneOptions {
+            older_than,
+            idle,
+            merged,
+            force,
+            dry_run,
+        }),
+        Commands::Gc { force, dry_run } => handle_gc(force, dry_run),
+        Commands::Status { format, counts } => handle_status(format, counts),
         Commands::Dir { name } => handle_dir(name),
+        Commands::Doctor => commands::handle_doctor(),
         Commands::Completions { shell } => completions::handle_completions(shell),
         Commands::CompleteWorktrees { format } => commands::handle_complete_worktrees(&format),
         Commands::Dashboard => commands::handle_dashboard(),
     }
 }
+
+/// The prompt given with `--prompt` or read from `--prompt-file`
+fn read_prompt(prompt: Option<String>, prompt_file: Option<PathBuf>) -> Result<Option<String>> {
+    match prompt_file {
+        Some(file) => fs::read_to_string(&file)
+            .map(Some)
+            .with_context(|| format!("Failed to read prompt file {}", file.display())),
+        None => Ok(prompt),
+    }
+}
@@ -0,0 +1,292 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::session_provider::{SessionInfo, SessionProvider, same_path};
+
+/// Sessions from OpenCode's storage in `$XDG_DATA_HOME/opencode/storage`.
+///
+/// OpenCode keeps one JSON file per session, message and message part:
+/// `session/<project>/<session>.json`, `message/<session>/<message>.json`
+/// and `part/<message>/<part>.json`.
+pub struct OpenCodeProvider;
+
+impl SessionProvider for OpenCodeProvider {
+    fn name(&self) -> &'static str {
+        "OpenCode"
+    }
+
+    fn sessions(&self, path: &Path) -> Vec<SessionInfo> {
+        storage_dir()
+            .map(|storage| sessions_in(&storage, path))
+            .unwrap_or_default()
+    }
+
+    fn relocate(&self, old: &Path, new: &Path) -> Result<usize> {
+        match storage_dir() {
+            Some(storage) => relocate_in(&storage, old, new),
+            None => Ok(0),
+        }
+    }
+}
+
+/// Milliseconds since the epoch
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Time {
+    created: Option<i64>,
+    updated: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SessionFile {
+    id: String,
+    #[serde(default)]
+    directory: PathBuf,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    time: Time,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageFile {
+    id: String,
+    role: String,
+    #[serde(default)]
+    time: Time,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Part {
+    Text {
+        text: String,
+        /// Added by OpenCode rather than typed by the user
+        #[serde(default)]
+        synthetic: bool,
+    },
+    #[serde(other)]
+    Other,
+}
+
+fn storage_dir() -> Option<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))?;
+    Some(data_home.join("opencode").join("storage"))
+}
+
+fn sessions_in(storage: &Path, path: &Path) -> Vec<SessionInfo> {
+    let Ok(worktree) = path.canonicalize() else {
+        return vec![];
+    };
+    let Ok(projects) = fs::read_dir(storage.join("session")) else {
+        return vec![];
+    };
+
+    projects
+        .flatten()
+        .flat_map(|project| json_files(&project.path()))
+        .filter_map(|file| read_json::<SessionFile>(&file))
+        .filter(|session| {
+            session
+                .directory
+                .canonicalize()
+                .is_ok_and(|dir| dir == worktree)
+        })
+        .map(|session| to_session_info(storage, session))
+        .collect()
+}
+
+fn to_session_info(storage: &Path, session: SessionFile) -> SessionInfo {
+    let mut prompts: Vec<MessageFile> = json_files(&storage.join("message").join(&session.id))
+        .iter()
+        .filter_map(|file| read_json::<MessageFile>(file))
+        .filter(|message| message.role == "user")
+        .collect();
+    prompts.sort_by_key(|message| message.time.created);
+
+    // Sessions without readable prompts still have a generated title
+    let text = |message: Option<&MessageFile>| {
+        message
+            .map(|message| message_text(storage, &message.id))
+            .filter(|text| !text.is_empty())
+            .unwrap_or_else(|| session.title.clone())
+    };
+    let first_user_message = text(prompts.first());
+    let last_user_message = text(prompts.last());
+
+    SessionInfo {
+        tool: OpenCodeProvider.name(),
+        id: session.id,
+        first_user_message,
+        first_timestamp: timestamp(session.time.created),
+        last_user_message,
+        last_timestamp: timestamp(session.time.updated.or(session.time.created)),
+        usage: vec![],
+    }
+}
+
+/// Point the `directory` of sessions started at `old` to `new`
+fn relocate_in(storage: &Path, old: &Path, new: &Path) -> Result<usize> {
+    let Ok(projects) = fs::read_dir(storage.join("session")) else {
+        return Ok(0);
+    };
+
+    let mut moved = 0;
+    for file in projects
+        .flatten()
+        .flat_map(|project| json_files(&project.path()))
+    {
+        // Edit the raw JSON so fields xlaude does not know survive
+        let Some(mut session) = read_json::<serde_json::Value>(&file) else {
+            continue;
+        };
+        let recorded = session["directory"].as_str().map(PathBuf::from);
+        if !recorded.is_some_and(|dir| same_path(&dir, old)) {
+            continue;
+        }
+        session["directory"] = serde_json::Value::from(new.to_string_lossy());
+        fs::write(&file, serde_json::to_string_pretty(&session)?)
+            .with_context(|| format!("Failed to update {}", file.display()))?;
+        moved += 1;
+    }
+    Ok(moved)
+}
+
+/// Text the user typed in a message, parts in the order they were written
+fn message_text(storage: &Path, message_id: &str) -> String {
+    // Part ids sort by creation time
+    let mut files = json_files(&storage.join("part").join(message_id));
+    files.sort();
+    files
+        .iter()
+        .filter_map(|file| match read_json::<Part>(file)? {
+            Part::Text {
+                text,
+                synthetic: false,
+            } => Some(text),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim()
+        .to_string()
+}
+
+fn timestamp(millis: Option<i64>) -> Option<DateTime<Utc>> {
+    millis.and_then(DateTime::from_timestamp_millis)
+}
+
+fn json_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect()
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn write(path: PathBuf, value: serde_json::Value) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, value.to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_sessions_for_worktree() {
+        let storage = tempfile::tempdir().unwrap();
+        let worktree = tempfile::tempdir().unwrap();
+        let other = tempfile::tempdir().unwrap();
+        let storage = storage.path();
+
+        write(
+            storage.join("session/proj/ses_1.json"),
+            json!({
+                "id": "ses_1",
+                "directory": worktree.path(),
+                "title": "Auth refactor",
+                "time": {"created": 1_714_557_600_000_i64, "updated": 1_714_561_200_000_i64},
+            }),
+        );
+        write(
+            storage.join("session/proj/ses_2.json"),
+            json!({"id": "ses_2", "directory": other.path(), "title": "Elsewhere", "time": {}}),
+        );
+        for (id, created, text) in [("msg_1", 1, "first prompt"), ("msg_3", 3, "second prompt")] {
+            write(
+                storage.join(format!("message/ses_1/{id}.json")),
+                json!({"id": id, "role": "user", "time": {"created": created}}),
+            );
+            write(
+                storage.join(format!("part/{id}/prt_1.json")),
+                json!({"type": "text", "text": text}),
+            );
+            write(
+                storage.join(format!("part/{id}/prt_2.json")),
+                json!({"type": "text", "text": "injected", "synthetic": true}),
+            );
+        }
+        write(
+            storage.join("message/ses_1/msg_2.json"),
+            json!({"id": "msg_2", "role": "assistant", "time": {"created": 2}}),
+        );
+
+        let sessions = sessions_in(storage, worktree.path());
+        assert_eq!(sessions.len(), 1);
+        let session = &sessions[0];
+        assert_eq!(session.tool, "OpenCode");
+        assert_eq!(session.id, "ses_1");
+        assert_eq!(session.first_user_message, "first prompt");
+        assert_eq!(session.last_user_message, "second prompt");
+        assert_eq!(
+            session.last_timestamp.unwrap().to_rfc3339(),
+            "2024-05-01T11:00:00+00:00"
+        );
+
+        // Without messages the title stands in for the prompt
+        let sessions = sessions_in(storage, other.path());
+        assert_eq!(sessions[0].last_user_message, "Elsewhere");
+        assert!(sessions[0].last_timestamp.is_none());
+    }
+
+    #[test]
+    fn test_relocate_sessions() {
+        let storage = tempfile::tempdir().unwrap();
+        let storage = storage.path();
+        let new = tempfile::tempdir().unwrap();
+        let old = storage.join("old-worktree");
+
+        write(
+            storage.join("session/proj/ses_1.json"),
+            json!({"id": "ses_1", "directory": old, "title": "Moved", "version": "0.5"}),
+        );
+        write(
+            storage.join("session/proj/ses_2.json"),
+            json!({"id": "ses_2", "directory": "/elsewhere", "title": "Stays"}),
+        );
+
+        assert_eq!(relocate_in(storage, &old, new.path()).unwrap(), 1);
+        let sessions = sessions_in(storage, new.path());
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, "ses_1");
+        let session: serde_json::Value =
+            read_json(&storage.join("session/proj/ses_1.json")).unwrap();
+        assert_eq!(session["version"], "0.5");
+    }
+}
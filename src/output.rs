@@ -0,0 +1,241 @@
+//! Machine-readable command output.
+//!
+//! With `--output json` every command prints a single versioned result object
+//! on stdout, while the usual human-readable progress goes to stderr:
+//!
+//! ```json
+//! {"version":1,"command":"create","ok":true,"result":{...},"warnings":[],"actions":[]}
+//! {"version":1,"command":"delete","ok":false,"error":{"code":"not_found","message":"..."}}
+//! ```
+//!
+//! A failed command may still carry the partial `result` it collected.
+
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+
+/// Version of the JSON result envelope, bumped on incompatible changes
+pub const OUTPUT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable output
+    #[default]
+    Text,
+    /// A single JSON result object on stdout
+    Json,
+}
+
+static FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+static REPORT: Mutex<Report> = Mutex::new(Report::new());
+
+/// What a command did, collected while it runs
+struct Report {
+    result: Option<Map<String, Value>>,
+    warnings: Vec<String>,
+    actions: Vec<String>,
+    exit_code: Option<i32>,
+}
+
+impl Report {
+    const fn new() -> Self {
+        Self {
+            result: None,
+            warnings: Vec::new(),
+            actions: Vec::new(),
+            exit_code: None,
+        }
+    }
+}
+
+/// Stable, machine-readable error codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The requested worktree does not exist
+    NotFound,
+    /// A worktree, directory or branch with that name already exists
+    AlreadyExists,
+    /// The command must be run inside a git repository
+    NotInRepository,
+    /// The repository is not in a state the command can work with
+    InvalidState,
+    /// A required choice could not be made without a terminal
+    InputRequired,
+    /// A git command failed
+    GitError,
+    /// The AI tool of a headless run exited with an error
+    AgentFailed,
+    /// A headless run was stopped after its timeout
+    Timeout,
+    /// Any other failure
+    Error,
+}
+
+/// An error carrying a stable [`ErrorCode`]
+#[derive(Debug)]
+pub struct CodedError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl fmt::Display for CodedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CodedError {}
+
+/// Build an error with a stable code, usable with `?`, `bail!` or `.context()`
+pub fn coded(code: ErrorCode, message: impl Into<String>) -> CodedError {
+    CodedError {
+        code,
+        message: message.into(),
+    }
+}
+
+/// Find the most specific error code attached to an error
+pub fn error_code(err: &anyhow::Error) -> ErrorCode {
+    if let Some(coded) = err.downcast_ref::<CodedError>() {
+        return coded.code;
+    }
+    if let Some(coded) = err.chain().find_map(|e| e.downcast_ref::<CodedError>()) {
+        return coded.code;
+    }
+    if err
+        .chain()
+        .any(|e| e.to_string().starts_with("Git command failed"))
+    {
+        return ErrorCode::GitError;
+    }
+    ErrorCode::Error
+}
+
+pub fn init(format: OutputFormat) {
+    let _ = FORMAT.set(format);
+}
+
+pub fn is_json() -> bool {
+    FORMAT.get() == Some(&OutputFormat::Json)
+}
+
+/// Record a field of the command result
+pub fn set(key: &str, value: impl Serialize) {
+    let value = serde_json::to_value(value).unwrap_or(Value::Null);
+    let mut report = REPORT.lock().unwrap_or_else(|e| e.into_inner());
+    report
+        .result
+        .get_or_insert_with(Map::new)
+        .insert(key.to_string(), value);
+}
+
+/// Record a warning for the JSON result
+pub fn warn(message: impl Into<String>) {
+    let mut report = REPORT.lock().unwrap_or_else(|e| e.into_inner());
+    report.warnings.push(message.into());
+}
+
+/// Record an action the command performed
+pub fn action(message: impl Into<String>) {
+    let mut report = REPORT.lock().unwrap_or_else(|e| e.into_inner());
+    report.actions.push(message.into());
+}
+
+/// Exit with `code` instead of 1 if the command fails, passing on the
+/// status of a program it ran
+pub fn set_exit_code(code: i32) {
+    let mut report = REPORT.lock().unwrap_or_else(|e| e.into_inner());
+    report.exit_code = Some(code);
+}
+
+/// Status the process exits with after a failed command
+pub fn exit_code() -> i32 {
+    let report = REPORT.lock().unwrap_or_else(|e| e.into_inner());
+    report.exit_code.unwrap_or(1)
+}
+
+/// Print the result envelope for `command` when JSON output is enabled
+pub fn finish(command: &str, outcome: &anyhow::Result<()>) {
+    if !is_json() {
+        return;
+    }
+
+    let report = REPORT.lock().unwrap_or_else(|e| e.into_inner());
+    let envelope = match outcome {
+        Ok(()) => serde_json::json!({
+            "version": OUTPUT_VERSION,
+            "command": command,
+            "ok": true,
+            "result": report.result.clone().unwrap_or_default(),
+            "warnings": report.warnings,
+            "actions": report.actions,
+        }),
+        Err(err) => {
+            let mut envelope = serde_json::json!({
+                "version": OUTPUT_VERSION,
+                "command": command,
+                "ok": false,
+                "error": {
+                    "code": error_code(err),
+                    "message": format!("{err:#}"),
+                },
+                "warnings": report.warnings,
+                "actions": report.actions,
+            });
+            // What is known about a failed run, such as where its log is
+            if let Some(result) = &report.result {
+                envelope["result"] = Value::Object(result.clone());
+            }
+            envelope
+        }
+    };
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&envelope).unwrap_or_default()
+    );
+}
+
+/// Print human-readable output: stdout normally, stderr when stdout carries JSON
+macro_rules! say {
+    () => {
+        if $crate::output::is_json() {
+            eprintln!();
+        } else {
+            println!();
+        }
+    };
+    ($($arg:tt)*) => {
+        if $crate::output::is_json() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+
+pub(crate) use say;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_code_lookup() {
+        let err = anyhow::Error::new(coded(ErrorCode::NotFound, "Worktree 'x' not found"));
+        assert_eq!(error_code(&err), ErrorCode::NotFound);
+
+        let err = anyhow::anyhow!("Git command failed: boom")
+            .context(coded(ErrorCode::NotInRepository, "Not in a git repository"));
+        assert_eq!(error_code(&err), ErrorCode::NotInRepository);
+        assert_eq!(err.to_string(), "Not in a git repository");
+
+        let err = anyhow::anyhow!("Git command failed: boom").context("Failed to create branch");
+        assert_eq!(error_code(&err), ErrorCode::GitError);
+
+        assert_eq!(error_code(&anyhow::anyhow!("other")), ErrorCode::Error);
+    }
+}
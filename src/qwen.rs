@@ -0,0 +1,260 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::session_provider::{SessionInfo, SessionProvider};
+
+/// Sessions from Qwen Code's prompt log in `~/.qwen/tmp/<project hash>/logs.json`.
+///
+/// The log only records user prompts, keyed by session id. The project hash
+/// is the SHA-256 of the directory Qwen Code was started in.
+pub struct QwenProvider;
+
+const LOG_FILE: &str = "logs.json";
+
+impl SessionProvider for QwenProvider {
+    fn name(&self) -> &'static str {
+        "Qwen Code"
+    }
+
+    fn sessions(&self, path: &Path) -> Vec<SessionInfo> {
+        tmp_dir()
+            .map(|tmp_dir| sessions_in(&tmp_dir, path))
+            .unwrap_or_default()
+    }
+
+    fn relocate(&self, old: &Path, new: &Path) -> Result<usize> {
+        match tmp_dir() {
+            Some(tmp_dir) => relocate_in(&tmp_dir, old, new),
+            None => Ok(0),
+        }
+    }
+}
+
+fn tmp_dir() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME")?;
+    Some(Path::new(&home).join(".qwen").join("tmp"))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LogEntry {
+    session_id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    timestamp: Option<DateTime<Utc>>,
+}
+
+fn project_hash(path: &Path) -> String {
+    format!("{:x}", Sha256::digest(path.to_string_lossy().as_bytes()))
+}
+
+/// Project directories Qwen Code may have used for `path`: it hashes the
+/// path as it saw it, which may not be canonical
+fn project_dirs(tmp_dir: &Path, path: &Path) -> Vec<PathBuf> {
+    let mut paths = vec![path.to_path_buf()];
+    if let Ok(canonical) = path.canonicalize()
+        && canonical != path
+    {
+        paths.push(canonical);
+    }
+    paths
+        .iter()
+        .map(|path| tmp_dir.join(project_hash(path)))
+        .collect()
+}
+
+fn read_log(dir: &Path) -> Option<Vec<serde_json::Value>> {
+    load_log(dir).ok().flatten()
+}
+
+/// The log in `dir`, `None` when there is none and an error when it exists
+/// but can't be read
+fn load_log(dir: &Path) -> Result<Option<Vec<serde_json::Value>>> {
+    let path = dir.join(LOG_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+fn sessions_in(tmp_dir: &Path, path: &Path) -> Vec<SessionInfo> {
+    let mut sessions: Vec<SessionInfo> = Vec::new();
+    for dir in project_dirs(tmp_dir, path) {
+        let Some(entries) = read_log(&dir) else {
+            continue;
+        };
+        let entries = entries
+            .into_iter()
+            .filter_map(|entry| serde_json::from_value::<LogEntry>(entry).ok());
+
+        for entry in entries {
+            let message = entry.message.trim();
+            // Slash commands are logged as prompts too
+            if entry.kind != "user" || message.is_empty() || message.starts_with('/') {
+                continue;
+            }
+            match sessions.iter_mut().find(|s| s.id == entry.session_id) {
+                Some(session) => {
+                    session.last_user_message = message.to_string();
+                    session.last_timestamp = entry.timestamp.or(session.last_timestamp);
+                }
+                None => sessions.push(SessionInfo {
+                    tool: QwenProvider.name(),
+                    id: entry.session_id,
+                    first_user_message: message.to_string(),
+                    first_timestamp: entry.timestamp,
+                    last_user_message: message.to_string(),
+                    last_timestamp: entry.timestamp,
+                    usage: vec![],
+                }),
+            }
+        }
+    }
+    sessions
+}
+
+/// Move the project directory of `old` to the hash of `new`, appending its
+/// log to the new one if Qwen Code was already started at the new path
+fn relocate_in(tmp_dir: &Path, old: &Path, new: &Path) -> Result<usize> {
+    let new_path = new.canonicalize().unwrap_or_else(|_| new.to_path_buf());
+    let new_dir = tmp_dir.join(project_hash(&new_path));
+
+    let mut moved = 0;
+    for old_dir in project_dirs(tmp_dir, old) {
+        if old_dir == new_dir || !old_dir.is_dir() {
+            continue;
+        }
+        let count_sessions = |log: &[serde_json::Value]| {
+            log.iter()
+                .filter_map(|entry| entry["sessionId"].as_str())
+                .collect::<HashSet<_>>()
+                .len()
+        };
+
+        // Moving the whole directory keeps even a log that can't be parsed
+        if !new_dir.exists() {
+            moved += read_log(&old_dir).map_or(0, |log| count_sessions(&log));
+            fs::rename(&old_dir, &new_dir).with_context(|| {
+                format!(
+                    "Failed to move {} to {}",
+                    old_dir.display(),
+                    new_dir.display()
+                )
+            })?;
+            continue;
+        }
+
+        // Merging needs both logs; an unreadable one is left alone rather
+        // than dropped
+        let Some(old_log) = load_log(&old_dir)? else {
+            continue;
+        };
+        let mut log = load_log(&new_dir)?.unwrap_or_default();
+        moved += count_sessions(&old_log);
+        log.splice(0..0, old_log);
+        fs::write(new_dir.join(LOG_FILE), serde_json::to_string_pretty(&log)?)
+            .with_context(|| format!("Failed to update {}", new_dir.join(LOG_FILE).display()))?;
+        fs::remove_file(old_dir.join(LOG_FILE))?;
+        // Checkpoints and other files stay behind
+        let _ = fs::remove_dir(&old_dir);
+    }
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sessions_from_log() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let worktree = tempfile::tempdir().unwrap();
+        let worktree = worktree.path().canonicalize().unwrap();
+
+        let log_dir = tmp_dir.path().join(project_hash(&worktree));
+        fs::create_dir_all(&log_dir).unwrap();
+        fs::write(
+            log_dir.join("logs.json"),
+            serde_json::json!([
+                {"sessionId": "a", "messageId": 0, "type": "user", "message": "fix the login bug", "timestamp": "2024-05-01T10:00:00.000Z"},
+                {"sessionId": "a", "messageId": 1, "type": "user", "message": "/quit", "timestamp": "2024-05-01T10:05:00.000Z"},
+                {"sessionId": "b", "messageId": 0, "type": "user", "message": "add tests", "timestamp": "2024-05-02T09:00:00.000Z"},
+                {"sessionId": "a", "messageId": 2, "type": "user", "message": "now the logout bug", "timestamp": "2024-05-01T10:10:00.000Z"},
+            ])
+            .to_string(),
+        )
+        .unwrap();
+
+        let sessions = sessions_in(tmp_dir.path(), &worktree);
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].id, "a");
+        assert_eq!(sessions[0].tool, "Qwen Code");
+        assert_eq!(sessions[0].first_user_message, "fix the login bug");
+        assert_eq!(sessions[0].last_user_message, "now the logout bug");
+        assert_eq!(
+            sessions[0].last_timestamp.unwrap().to_rfc3339(),
+            "2024-05-01T10:10:00+00:00"
+        );
+        assert_eq!(sessions[1].last_user_message, "add tests");
+
+        assert!(sessions_in(tmp_dir.path(), Path::new("/nonexistent")).is_empty());
+    }
+
+    #[test]
+    fn test_relocate_log() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let new = tempfile::tempdir().unwrap();
+        let new = new.path().canonicalize().unwrap();
+        let old = Path::new("/old/worktree");
+
+        let prompt = |session: &str, message: &str| serde_json::json!({"sessionId": session, "type": "user", "message": message});
+        let old_dir = tmp_dir.path().join(project_hash(old));
+        fs::create_dir_all(&old_dir).unwrap();
+        fs::write(
+            old_dir.join(LOG_FILE),
+            serde_json::json!([prompt("a", "first"), prompt("b", "second")]).to_string(),
+        )
+        .unwrap();
+        let new_dir = tmp_dir.path().join(project_hash(&new));
+        fs::create_dir_all(&new_dir).unwrap();
+        fs::write(
+            new_dir.join(LOG_FILE),
+            serde_json::json!([prompt("c", "third")]).to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(relocate_in(tmp_dir.path(), old, &new).unwrap(), 2);
+        assert!(!old_dir.exists());
+        let sessions = sessions_in(tmp_dir.path(), &new);
+        let ids: Vec<&str> = sessions.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b", "c"]);
+
+        // A log that can't be parsed is kept rather than merged away
+        fs::create_dir_all(&old_dir).unwrap();
+        fs::write(old_dir.join(LOG_FILE), "not json").unwrap();
+        assert!(relocate_in(tmp_dir.path(), old, &new).is_err());
+        assert_eq!(
+            fs::read_to_string(old_dir.join(LOG_FILE)).unwrap(),
+            "not json"
+        );
+
+        // Without a log there is nothing to merge
+        fs::remove_file(old_dir.join(LOG_FILE)).unwrap();
+        fs::write(old_dir.join("checkpoint"), "").unwrap();
+        assert_eq!(relocate_in(tmp_dir.path(), old, &new).unwrap(), 0);
+        assert!(old_dir.join("checkpoint").exists());
+        assert_eq!(sessions_in(tmp_dir.path(), &new).len(), 3);
+    }
+}
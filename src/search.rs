@@ -0,0 +1,248 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::cache::{self, Change, Fingerprint};
+use crate::transcript::Record;
+
+/// Directory in the config directory holding one cache file per transcript
+const CACHE_DIR: &str = "search-cache";
+
+/// Transcripts that have a cache file, so those of deleted transcripts can
+/// be removed without reading every cache file
+const MANIFEST_FILE: &str = "search-cache/transcripts.json";
+
+/// Bump when what is cached changes so stale caches are rebuilt
+const CACHE_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    Assistant,
+}
+
+/// Searchable text of one transcript record
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedMessage {
+    pub timestamp: Option<DateTime<Utc>>,
+    pub role: Role,
+    pub text: String,
+}
+
+impl CachedMessage {
+    /// Prompts the user typed and assistant replies; tool traffic, sub-agent
+    /// conversations and injected messages are left out
+    fn from_line(line: &str) -> Option<Self> {
+        let (role, text, record) = match Record::parse(line) {
+            Record::User(record) => (Role::User, record.prompt_text()?, record),
+            Record::Assistant(record) => {
+                let text = record.message.as_ref()?.content.text();
+                (Role::Assistant, text, record)
+            }
+            _ => return None,
+        };
+        if record.is_sidechain || record.is_meta || text.trim().is_empty() {
+            return None;
+        }
+        Some(Self {
+            timestamp: record.timestamp,
+            role,
+            text,
+        })
+    }
+}
+
+/// Cached messages of one transcript file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CachedFile {
+    version: u32,
+    #[serde(flatten)]
+    fingerprint: Fingerprint,
+    /// Bytes read so far, always at a line boundary
+    offset: u64,
+    messages: Vec<CachedMessage>,
+}
+
+impl CachedFile {
+    /// Bring the cache up to date with the transcript, returning whether it
+    /// changed. Transcripts are append-only, so a file that grew is read
+    /// from where the last update stopped; a file that shrank or was
+    /// rewritten is read again.
+    fn refresh(&mut self, path: &Path, fingerprint: Fingerprint) -> bool {
+        let change = self.fingerprint.change(&fingerprint);
+        if change == Change::Unchanged {
+            return false;
+        }
+        if change == Change::Rewritten {
+            *self = Self::default();
+        }
+        if self.scan(path).is_ok() {
+            self.fingerprint = fingerprint;
+        } else {
+            *self = Self::default();
+        }
+        self.version = CACHE_VERSION;
+        true
+    }
+
+    /// Read complete lines from `offset` on. A trailing partial line is left
+    /// for the next update, so nothing is read twice.
+    fn scan(&mut self, path: &Path) -> std::io::Result<()> {
+        let mut file = fs::File::open(path)?;
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut reader = BufReader::new(file);
+
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            let read = reader.read_until(b'\n', &mut buf)?;
+            if read == 0 || !buf.ends_with(b"\n") {
+                break;
+            }
+            self.offset += read as u64;
+            if let Some(message) = CachedMessage::from_line(&String::from_utf8_lossy(&buf)) {
+                self.messages.push(message);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Text of the messages of searched transcripts, cached in the config
+/// directory so a search does not re-parse them.
+///
+/// Each transcript has a cache file of its own, so a search only reads the
+/// transcripts it covers; those are then scanned in full, there is no term
+/// index.
+#[derive(Debug, Default)]
+pub struct SearchCache {
+    transcripts: BTreeSet<PathBuf>,
+    dirty: bool,
+}
+
+impl SearchCache {
+    pub fn load() -> Self {
+        Self {
+            transcripts: cache::load(MANIFEST_FILE).unwrap_or_default(),
+            dirty: false,
+        }
+    }
+
+    /// Messages of a transcript, brought up to date. A transcript last
+    /// modified before `since` has no messages that recent and is not read.
+    pub fn messages(&mut self, path: &Path, since: Option<DateTime<Utc>>) -> Vec<CachedMessage> {
+        let fingerprint = Fingerprint::of(path).unwrap_or_default();
+        if let (Some(since), Some(modified)) = (since, fingerprint.modified)
+            && DateTime::<Utc>::from(modified) < since
+        {
+            return Vec::new();
+        }
+
+        let file = cache_file(path);
+        let mut cached = cache::load::<CachedFile>(&file)
+            .filter(|cached| cached.version == CACHE_VERSION)
+            .unwrap_or_default();
+        if cached.refresh(path, fingerprint) {
+            cache::store(&file, &cached);
+        }
+        self.dirty |= self.transcripts.insert(path.to_path_buf());
+        cached.messages
+    }
+
+    /// Remove the caches of transcripts that no longer exist
+    pub fn prune(&mut self) {
+        let before = self.transcripts.len();
+        self.transcripts.retain(|path| {
+            let exists = path.exists();
+            if !exists {
+                cache::remove(&cache_file(path));
+            }
+            exists
+        });
+        self.dirty |= self.transcripts.len() != before;
+    }
+
+    /// Write the list of cached transcripts if it changed
+    pub fn save(&mut self) {
+        if self.dirty && cache::store(MANIFEST_FILE, &self.transcripts) {
+            self.dirty = false;
+        }
+    }
+}
+
+/// Cache file of a transcript, named after the hash of its path
+fn cache_file(path: &Path) -> String {
+    let hash = Sha256::digest(path.to_string_lossy().as_bytes());
+    format!("{CACHE_DIR}/{hash:x}.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn line(kind: &str, timestamp: &str, text: &str) -> String {
+        serde_json::json!({
+            "type": kind,
+            "timestamp": timestamp,
+            "message": {"role": kind, "content": [{"type": "text", "text": text}]},
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_incremental_update() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let mut file = fs::File::create(&path).unwrap();
+        writeln!(
+            file,
+            "{}",
+            line("user", "2024-05-01T10:00:00Z", "refactor auth")
+        )
+        .unwrap();
+        writeln!(
+            file,
+            r#"{{"type":"user","isMeta":true,"message":{{"content":"meta"}}}}"#
+        )
+        .unwrap();
+        // A line still being written is not read yet
+        write!(
+            file,
+            "{}",
+            line("assistant", "2024-05-01T10:01:00Z", "Done")
+        )
+        .unwrap();
+
+        let mut cache = CachedFile::default();
+        let refresh =
+            |cache: &mut CachedFile| cache.refresh(&path, Fingerprint::of(&path).unwrap());
+        assert!(refresh(&mut cache));
+        assert!(!refresh(&mut cache));
+        let messages = &cache.messages;
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].role, Role::User);
+        assert_eq!(messages[0].text, "refactor auth");
+
+        writeln!(file).unwrap();
+        writeln!(file, "{}", line("user", "2024-05-01T10:02:00Z", "thanks")).unwrap();
+        assert!(refresh(&mut cache));
+        let texts: Vec<&str> = cache.messages.iter().map(|m| m.text.as_str()).collect();
+        assert_eq!(texts, ["refactor auth", "Done", "thanks"]);
+
+        // A rewritten, shorter file is read from scratch
+        fs::write(
+            &path,
+            format!("{}\n", line("user", "2024-05-02T09:00:00Z", "new")),
+        )
+        .unwrap();
+        assert!(refresh(&mut cache));
+        let texts: Vec<&str> = cache.messages.iter().map(|m| m.text.as_str()).collect();
+        assert_eq!(texts, ["new"]);
+    }
+}
@@ -0,0 +1,88 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::path::Path;
+
+use crate::claude::ClaudeProvider;
+use crate::opencode::OpenCodeProvider;
+use crate::qwen::QwenProvider;
+use crate::usage::UsageBucket;
+
+/// One conversation of an AI tool in a worktree
+#[derive(Debug, Clone)]
+pub struct SessionInfo {
+    /// Name of the tool the session belongs to, see `SessionProvider::name`
+    pub tool: &'static str,
+    /// Session id as the tool knows it
+    pub id: String,
+    pub first_user_message: String,
+    pub first_timestamp: Option<DateTime<Utc>>,
+    pub last_user_message: String,
+    pub last_timestamp: Option<DateTime<Utc>>,
+    /// Token usage per hour and model, only known for Claude
+    pub usage: Vec<UsageBucket>,
+}
+
+/// Where an AI tool keeps its session history
+pub trait SessionProvider: Sync {
+    /// Tool name as shown to users
+    fn name(&self) -> &'static str;
+
+    /// Sessions started in the worktree at `path`, in any order
+    fn sessions(&self, path: &Path) -> Vec<SessionInfo>;
+
+    /// Make sessions started at `old` belong to the worktree now at `new`,
+    /// returning how many sessions were moved
+    fn relocate(&self, old: &Path, new: &Path) -> Result<usize>;
+}
+
+/// Every known session provider
+pub fn providers() -> [&'static dyn SessionProvider; 3] {
+    [&ClaudeProvider, &OpenCodeProvider, &QwenProvider]
+}
+
+/// The provider configured for a tool by id: "claude", "opencode" or "qwen"
+pub fn provider(id: &str) -> Option<&'static dyn SessionProvider> {
+    match id {
+        "claude" => Some(&ClaudeProvider),
+        "opencode" => Some(&OpenCodeProvider),
+        "qwen" => Some(&QwenProvider),
+        _ => None,
+    }
+}
+
+/// Sessions of all tools for a worktree, most recent first
+pub fn get_sessions(path: &Path) -> Vec<SessionInfo> {
+    let mut sessions: Vec<SessionInfo> = providers()
+        .iter()
+        .flat_map(|provider| provider.sessions(path))
+        .collect();
+    sort_sessions(&mut sessions);
+    sessions
+}
+
+/// Move the session history of every tool after a worktree moved from `old` to `new`
+pub fn relocate_sessions(old: &Path, new: &Path) -> Vec<(&'static str, Result<usize>)> {
+    providers()
+        .iter()
+        .map(|provider| (provider.name(), provider.relocate(old, new)))
+        .collect()
+}
+
+/// Whether a path recorded by a tool refers to `path`, which may no longer exist
+pub fn same_path(recorded: &Path, path: &Path) -> bool {
+    recorded == path
+        || matches!(
+            (recorded.canonicalize(), path.canonicalize()),
+            (Ok(a), Ok(b)) if a == b
+        )
+}
+
+/// Sort by last activity, most recent first, sessions without a timestamp last
+pub fn sort_sessions(sessions: &mut [SessionInfo]) {
+    sessions.sort_by(|a, b| match (&b.last_timestamp, &a.last_timestamp) {
+        (Some(b_ts), Some(a_ts)) => b_ts.cmp(a_ts),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+}
@@ -6,6 +6,10 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
+use crate::ai_tools::ToolConfig;
+use crate::forge::ForgeHost;
+use crate::usage::ModelPrice;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorktreeInfo {
     pub name: String,
@@ -13,6 +17,85 @@ pub struct WorktreeInfo {
     pub path: PathBuf,
     pub repo_name: String,
     pub created_at: DateTime<Utc>,
+    /// Free-form labels for filtering, set with `xlaude tag`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Id of the AI tool the worktree was last opened with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    /// Permission profile the worktree was last opened with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Outcome of the latest `xlaude run`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_run: Option<RunRecord>,
+}
+
+/// How a headless run ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Succeeded,
+    Failed,
+    TimedOut,
+}
+
+impl RunStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RunStatus::Succeeded => "succeeded",
+            RunStatus::Failed => "failed",
+            RunStatus::TimedOut => "timed out",
+        }
+    }
+}
+
+/// A headless agent run started with `xlaude run`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    /// Id of the AI tool that ran
+    pub tool: String,
+    /// Permission profile it ran with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub duration_secs: u64,
+    pub status: RunStatus,
+    /// Exit code of the tool, unknown when it was killed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// File holding everything the tool printed
+    pub log: PathBuf,
+    /// Last paragraph the tool printed, usually its answer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_message: Option<String>,
+}
+
+/// Settings shared by the worktrees of one repository
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RepoConfig {
+    /// Id of the AI tool worktrees are opened with until one was chosen
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_tool: Option<String>,
+    /// Permission profile for worktrees that were not given one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+}
+
+/// Retention policy applied by `xlaude gc`
+///
+/// All configured criteria must match for a worktree to be pruned.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RetentionPolicy {
+    /// Minimum age since creation, e.g. "14d"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub older_than: Option<String>,
+    /// Minimum time without commits or agent sessions, e.g. "3d"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle: Option<String>,
+    /// Only prune branches that are merged (via git or PR)
+    #[serde(default)]
+    pub merged: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -20,6 +103,34 @@ pub struct XlaudeState {
     // Key format: "{repo_name}/{worktree_name}"
     pub worktrees: HashMap<String, WorktreeInfo>,
     pub editor: Option<String>,
+    // Glob patterns for ignored files that should block deletion
+    // (falls back to built-in defaults when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protected_patterns: Option<Vec<String>>,
+    // Forge configuration for self-hosted hosts, keyed by host name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub forge_hosts: HashMap<String, ForgeHost>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention: Option<RetentionPolicy>,
+    // Per-model prices for `xlaude usage`, keyed by model name prefix
+    // (overrides the built-in table)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub model_prices: HashMap<String, ModelPrice>,
+    // Extra regular expressions redacted from exported transcripts
+    // (in addition to the built-in secret patterns)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redact_patterns: Vec<String>,
+    // Claude's config directory holding `projects/`, used when
+    // CLAUDE_CONFIG_DIR is not set (defaults to ~/.claude)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claude_config_dir: Option<PathBuf>,
+    // AI tools keyed by id, overriding or disabling built-in tools with
+    // the same id
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tools: HashMap<String, ToolConfig>,
+    // Per-repository settings keyed by repo name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub repos: HashMap<String, RepoConfig>,
 }
 
 impl XlaudeState {
@@ -81,7 +192,10 @@ impl XlaudeState {
             fs::create_dir_all(parent).context("Failed to create config directory")?;
         }
         let content = serde_json::to_string_pretty(self).context("Failed to serialize state")?;
-        fs::write(&config_path, content).context("Failed to write config file")?;
+        // Write and rename so concurrent xlaude processes never read a half-written file
+        let tmp_path = config_path.with_extension(format!("json.{}.tmp", std::process::id()));
+        fs::write(&tmp_path, content).context("Failed to write config file")?;
+        fs::rename(&tmp_path, &config_path).context("Failed to write config file")?;
         Ok(())
     }
 }
@@ -1,8 +1,11 @@
 use anyhow::{Context, Result};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 use std::process::Command;
 
+use crate::executables::find_executable;
+
 pub struct TmuxManager {
     session_prefix: String,
 }
@@ -16,15 +19,20 @@ impl TmuxManager {
 
     /// Check if tmux is available
     pub fn is_available() -> bool {
-        Command::new("which")
-            .arg("tmux")
-            .output()
-            .map(|output| output.status.success())
-            .unwrap_or(false)
+        find_executable("tmux").is_some()
     }
 
-    /// Create a new tmux session for a project
-    pub fn create_session(&self, project: &str, work_dir: &Path) -> Result<()> {
+    /// Create a new tmux session for a project running `command`, the
+    /// program and its arguments, showing its permission profile in the
+    /// status bar
+    pub fn create_session(
+        &self,
+        project: &str,
+        work_dir: &Path,
+        command: &[String],
+        env: &BTreeMap<String, String>,
+        profile: Option<&str>,
+    ) -> Result<()> {
         let session_name = self.make_session_name(project);
 
         // Check if session already exists
@@ -35,7 +43,11 @@ impl TmuxManager {
         // Create custom tmux config
         let config_path = self.create_custom_config()?;
 
-        // Create detached tmux session with custom config and start claude directly
+        // Create detached tmux session with custom config and start the agent directly
+        let env: Vec<String> = env
+            .iter()
+            .flat_map(|(key, value)| ["-e".to_string(), format!("{key}={value}")])
+            .collect();
         let output = Command::new("tmux")
             .args([
                 "-f",
@@ -46,9 +58,9 @@ impl TmuxManager {
                 &session_name,
                 "-c",
                 work_dir.to_str().unwrap(),
-                "claude",
-                "--dangerously-skip-permissions",
             ])
+            .args(env)
+            .args(command)
             .output()
             .context("Failed to create tmux session")?;
 
@@ -57,6 +69,19 @@ impl TmuxManager {
             anyhow::bail!("Failed to create tmux session: {}", stderr);
         }
 
+        // Remembered in the session so the status bar shows it after reattaching
+        if let Some(profile) = profile {
+            Command::new("tmux")
+                .args([
+                    "set-option",
+                    "-t",
+                    &session_name,
+                    "@xlaude_profile",
+                    profile,
+                ])
+                .output()?;
+        }
+
         // Configure key bindings and status bar for the session
         self.configure_session_keys(&session_name)?;
         self.configure_session_status(&session_name)?;
@@ -186,6 +211,25 @@ impl TmuxManager {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
 
+    /// Type `text` into a pane and press Enter once the program about to
+    /// start there had time to draw its input, without waiting for it
+    pub fn send_text_later(pane: &str, text: &str) -> Result<()> {
+        Command::new("sh")
+            .args([
+                "-c",
+                r#"sleep 2 && tmux send-keys -t "$1" -l -- "$2" && tmux send-keys -t "$1" Enter"#,
+                "sh",
+                pane,
+                text,
+            ])
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .context("Failed to schedule typing the prompt")?;
+        Ok(())
+    }
+
     /// Configure key bindings for a specific session
     fn configure_session_keys(&self, session_name: &str) -> Result<()> {
         // Set Ctrl+Q to detach (session-specific)
@@ -270,9 +314,12 @@ impl TmuxManager {
             ])
             .output()?;
 
-        // Set left section with project name
+        // Set left section with project name and permission profile
         let project = session_name.strip_prefix("xlaude_").unwrap_or(session_name);
-        let left_text = format!(" 📂 xlaude: {} ", project.replace('_', "-"));
+        let left_text = format!(
+            " 📂 xlaude: {} #{{?@xlaude_profile,| 🔒 #{{@xlaude_profile}} ,}}",
+            project.replace('_', "-")
+        );
         Command::new("tmux")
             .args(["set-option", "-t", session_name, "status-left", &left_text])
             .output()?;
@@ -290,7 +337,7 @@ impl TmuxManager {
 
         // Set lengths
         Command::new("tmux")
-            .args(["set-option", "-t", session_name, "status-left-length", "50"])
+            .args(["set-option", "-t", session_name, "status-left-length", "70"])
             .output()?;
 
         Command::new("tmux")
@@ -400,6 +447,11 @@ pub struct SessionInfo {
 }
 
 impl SessionInfo {
+    /// Whether this is the session of the worktree `name`
+    pub fn belongs_to(&self, name: &str) -> bool {
+        self.project == name.replace(['-', '.'], "_") || self.project == name
+    }
+
     pub fn format_time(timestamp: i64) -> String {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
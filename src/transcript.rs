@@ -0,0 +1,454 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// One line of a Claude Code transcript (`.jsonl`).
+///
+/// Only the records xlaude understands are modelled; anything else,
+/// including malformed lines, becomes `Unknown` so new Claude versions
+/// never break parsing.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Record {
+    User(MessageRecord),
+    Assistant(MessageRecord),
+    Summary(SummaryRecord),
+    #[serde(other)]
+    Unknown,
+}
+
+impl Record {
+    pub fn parse(line: &str) -> Self {
+        serde_json::from_str(line).unwrap_or(Self::Unknown)
+    }
+
+    /// Message fields shared by user and assistant records
+    pub fn message(&self) -> Option<&MessageRecord> {
+        match self {
+            Self::User(record) | Self::Assistant(record) => Some(record),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MessageRecord {
+    pub uuid: Option<String>,
+    pub session_id: Option<String>,
+    #[serde(deserialize_with = "lenient_timestamp")]
+    pub timestamp: Option<DateTime<Utc>>,
+    pub cwd: Option<PathBuf>,
+    pub git_branch: Option<String>,
+    /// Injected by Claude Code rather than typed by the user
+    pub is_meta: bool,
+    /// Part of a sub-agent conversation
+    pub is_sidechain: bool,
+    pub message: Option<Message>,
+}
+
+impl MessageRecord {
+    /// Text of a user prompt, or `None` for tool results, command output and
+    /// other messages the user did not type
+    pub fn prompt_text(&self) -> Option<String> {
+        let text = self.message.as_ref()?.content.text();
+
+        // Filter out system messages and empty content
+        if text.is_empty()
+            || text.starts_with("<local-command")
+            || text.starts_with("<command-")
+            || text.starts_with("Caveat:")
+            || text.contains("[Request interrupted")
+        {
+            return None;
+        }
+        Some(text)
+    }
+
+    pub fn blocks(&self) -> &[ContentBlock] {
+        self.message.as_ref().map_or(&[], |m| m.content.blocks())
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Message {
+    /// API message id, shared by all records of one assistant response
+    pub id: Option<String>,
+    pub role: Option<String>,
+    pub model: Option<String>,
+    pub content: Content,
+    /// Token usage, only present on assistant messages
+    pub usage: Option<Usage>,
+}
+
+/// Token counts reported by the API for one response
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Usage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+}
+
+impl Usage {
+    pub fn total(&self) -> u64 {
+        self.input_tokens
+            + self.output_tokens
+            + self.cache_creation_input_tokens
+            + self.cache_read_input_tokens
+    }
+
+    pub fn add(&mut self, other: &Self) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_input_tokens += other.cache_creation_input_tokens;
+        self.cache_read_input_tokens += other.cache_read_input_tokens;
+    }
+
+    pub fn subtract(&mut self, other: &Self) {
+        self.input_tokens = self.input_tokens.saturating_sub(other.input_tokens);
+        self.output_tokens = self.output_tokens.saturating_sub(other.output_tokens);
+        self.cache_creation_input_tokens = self
+            .cache_creation_input_tokens
+            .saturating_sub(other.cache_creation_input_tokens);
+        self.cache_read_input_tokens = self
+            .cache_read_input_tokens
+            .saturating_sub(other.cache_read_input_tokens);
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Content {
+    Text(String),
+    Blocks(Vec<ContentBlock>),
+}
+
+impl Default for Content {
+    fn default() -> Self {
+        Self::Blocks(Vec::new())
+    }
+}
+
+impl Content {
+    /// Plain text of the message, text blocks joined by spaces
+    pub fn text(&self) -> String {
+        match self {
+            Self::Text(text) => text.clone(),
+            Self::Blocks(blocks) => blocks
+                .iter()
+                .filter_map(|block| match block {
+                    ContentBlock::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+
+    pub fn blocks(&self) -> &[ContentBlock] {
+        match self {
+            Self::Text(_) => &[],
+            Self::Blocks(blocks) => blocks,
+        }
+    }
+}
+
+/// Content block of a message; kinds nothing reads (e.g. thinking) become `Other`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text {
+        #[serde(default)]
+        text: String,
+    },
+    ToolUse {
+        #[serde(default)]
+        id: String,
+        #[serde(default)]
+        name: String,
+        #[serde(default)]
+        input: serde_json::Value,
+    },
+    ToolResult {
+        #[serde(default)]
+        tool_use_id: String,
+        #[serde(default)]
+        content: serde_json::Value,
+        #[serde(default)]
+        is_error: bool,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// Title Claude generates for a conversation
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SummaryRecord {
+    pub summary: String,
+    pub leaf_uuid: Option<String>,
+}
+
+fn lenient_timestamp<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<String>::deserialize(deserializer)?;
+    Ok(value
+        .and_then(|ts| DateTime::parse_from_rfc3339(&ts).ok())
+        .map(|ts| ts.with_timezone(&Utc)))
+}
+
+/// Read every record of a transcript file
+pub fn read_transcript(path: &Path) -> std::io::Result<Vec<Record>> {
+    let reader = BufReader::new(fs::File::open(path)?);
+    let mut records = Vec::new();
+    for line in reader.split(b'\n') {
+        let line = line?;
+        if !line.is_empty() {
+            records.push(Record::parse(&String::from_utf8_lossy(&line)));
+        }
+    }
+    Ok(records)
+}
+
+/// One step of a conversation, in transcript order
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Entry {
+    Prompt {
+        timestamp: Option<DateTime<Utc>>,
+        text: String,
+    },
+    Assistant {
+        timestamp: Option<DateTime<Utc>>,
+        text: String,
+    },
+    ToolCall {
+        timestamp: Option<DateTime<Utc>>,
+        name: String,
+        input: serde_json::Value,
+        /// Output of the tool, once its result was recorded
+        result: Option<String>,
+        is_error: bool,
+    },
+}
+
+/// User prompts, assistant text and tool calls with their results.
+///
+/// Sub-agent records, thinking and messages injected by Claude Code are left out.
+pub fn conversation(records: &[Record]) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    // Tool use id to the index of its call in `entries`
+    let mut calls: HashMap<String, usize> = HashMap::new();
+
+    for record in records {
+        let Some(message) = record.message() else {
+            continue;
+        };
+        if message.is_sidechain || message.is_meta {
+            continue;
+        }
+        let timestamp = message.timestamp;
+
+        match record {
+            Record::User(user) => {
+                if let Some(text) = user.prompt_text() {
+                    entries.push(Entry::Prompt { timestamp, text });
+                    continue;
+                }
+                for block in user.blocks() {
+                    if let ContentBlock::ToolResult {
+                        tool_use_id,
+                        content,
+                        is_error,
+                    } = block
+                        && let Some(&idx) = calls.get(tool_use_id)
+                        && let Entry::ToolCall {
+                            result,
+                            is_error: call_error,
+                            ..
+                        } = &mut entries[idx]
+                    {
+                        *result = Some(tool_result_text(content));
+                        *call_error = *is_error;
+                    }
+                }
+            }
+            Record::Assistant(assistant) => {
+                for block in assistant.blocks() {
+                    match block {
+                        ContentBlock::Text { text } if !text.trim().is_empty() => {
+                            entries.push(Entry::Assistant {
+                                timestamp,
+                                text: text.clone(),
+                            });
+                        }
+                        ContentBlock::ToolUse { id, name, input } => {
+                            calls.insert(id.clone(), entries.len());
+                            entries.push(Entry::ToolCall {
+                                timestamp,
+                                name: name.clone(),
+                                input: input.clone(),
+                                result: None,
+                                is_error: false,
+                            });
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    entries
+}
+
+/// Tool results are either a string or a list of content blocks
+fn tool_result_text(content: &serde_json::Value) -> String {
+    match content {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(blocks) => blocks
+            .iter()
+            .map(|block| match block.get("type").and_then(|t| t.as_str()) {
+                Some("text") => block
+                    .get("text")
+                    .and_then(|t| t.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                Some(other) => format!("[{other}]"),
+                None => block.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Statistics of one Claude session
+#[derive(Debug, Default, Serialize)]
+pub struct SessionDetails {
+    /// Transcript file name without extension
+    pub id: String,
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+    /// Prompts typed by the user
+    pub user_messages: usize,
+    /// Assistant responses, counting each API message once
+    pub assistant_messages: usize,
+    /// Tool name to number of calls
+    pub tools: BTreeMap<String, usize>,
+    pub models: BTreeSet<String>,
+    pub first_prompt: Option<String>,
+    pub summary: Option<String>,
+    pub git_branch: Option<String>,
+}
+
+impl SessionDetails {
+    pub fn from_records(id: String, records: &[Record]) -> Self {
+        let mut details = Self {
+            id,
+            ..Self::default()
+        };
+        let mut responses = HashSet::new();
+
+        for record in records {
+            if let Record::Summary(summary) = record {
+                details.summary = Some(summary.summary.clone());
+                continue;
+            }
+            let Some(message) = record.message() else {
+                continue;
+            };
+
+            if let Some(ts) = message.timestamp {
+                details.started_at.get_or_insert(ts);
+                details.ended_at = Some(ts);
+            }
+            if let Some(branch) = &message.git_branch
+                && !branch.is_empty()
+            {
+                details.git_branch = Some(branch.clone());
+            }
+
+            match record {
+                Record::User(user) => {
+                    if let Some(text) = user.prompt_text() {
+                        details.user_messages += 1;
+                        details.first_prompt.get_or_insert(text);
+                    }
+                }
+                Record::Assistant(assistant) => {
+                    let api_message = assistant.message.as_ref();
+                    // Claude Code writes one record per content block of a response
+                    let new_response = api_message
+                        .and_then(|m| m.id.clone())
+                        .is_none_or(|id| responses.insert(id));
+                    if new_response {
+                        details.assistant_messages += 1;
+                    }
+                    if let Some(model) = api_message.and_then(|m| m.model.clone())
+                        && !model.starts_with('<')
+                    {
+                        details.models.insert(model);
+                    }
+                    for block in assistant.blocks() {
+                        if let ContentBlock::ToolUse { name, .. } = block {
+                            *details.tools.entry(name.clone()).or_default() += 1;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        details
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_details_from_records() {
+        let lines = [
+            r#"{"type":"summary","summary":"Parser fixes","leafUuid":"abc"}"#,
+            r#"{"type":"user","sessionId":"s1","gitBranch":"feature","timestamp":"2024-05-01T10:00:00Z","message":{"role":"user","content":"Fix the parser"}}"#,
+            r#"{"type":"assistant","timestamp":"2024-05-01T10:00:05Z","message":{"id":"msg_1","model":"claude-sonnet-4","content":[{"type":"thinking","thinking":"hmm"}]}}"#,
+            r#"{"type":"assistant","timestamp":"2024-05-01T10:00:06Z","message":{"id":"msg_1","model":"claude-sonnet-4","content":[{"type":"tool_use","id":"t1","name":"Read","input":{}}]}}"#,
+            r#"{"type":"user","timestamp":"2024-05-01T10:00:07Z","message":{"content":[{"type":"tool_result","tool_use_id":"t1","content":"ok"}]}}"#,
+            r#"{"type":"assistant","timestamp":"2024-05-01T10:00:09Z","message":{"id":"msg_2","model":"claude-sonnet-4","content":[{"type":"tool_use","id":"t2","name":"Read"},{"type":"server_tool_use"}]}}"#,
+            r#"{"type":"file-history-snapshot","snapshot":{}}"#,
+            r#"{"type":"user","timestamp":"not a date","message":{"content":[{"type":"text","text":"Thanks"}]}}"#,
+            "not json",
+        ];
+        let records: Vec<Record> = lines.iter().map(|line| Record::parse(line)).collect();
+        assert!(matches!(records[6], Record::Unknown));
+        assert!(matches!(records[8], Record::Unknown));
+
+        let details = SessionDetails::from_records("s1".to_string(), &records);
+        assert_eq!(details.summary.as_deref(), Some("Parser fixes"));
+        assert_eq!(details.first_prompt.as_deref(), Some("Fix the parser"));
+        assert_eq!(details.user_messages, 2);
+        assert_eq!(details.assistant_messages, 2);
+        assert_eq!(details.tools.get("Read"), Some(&2));
+        assert_eq!(
+            details.models.into_iter().collect::<Vec<_>>(),
+            ["claude-sonnet-4"]
+        );
+        assert_eq!(details.git_branch.as_deref(), Some("feature"));
+        assert_eq!(
+            details.ended_at.unwrap().to_rfc3339(),
+            "2024-05-01T10:00:09+00:00"
+        );
+    }
+}
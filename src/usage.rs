@@ -0,0 +1,211 @@
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::state::XlaudeState;
+use crate::transcript::Usage;
+
+/// Price of a model in USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+    /// Writing to the prompt cache
+    pub cache_write: f64,
+    /// Reading from the prompt cache
+    pub cache_read: f64,
+}
+
+impl ModelPrice {
+    const fn new(input: f64, output: f64, cache_write: f64, cache_read: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_write,
+            cache_read,
+        }
+    }
+
+    pub fn cost(&self, usage: &Usage) -> f64 {
+        (usage.input_tokens as f64 * self.input
+            + usage.output_tokens as f64 * self.output
+            + usage.cache_creation_input_tokens as f64 * self.cache_write
+            + usage.cache_read_input_tokens as f64 * self.cache_read)
+            / 1_000_000.0
+    }
+}
+
+/// Built-in prices, matched by model name prefix
+const DEFAULT_PRICES: &[(&str, ModelPrice)] = &[
+    ("claude-opus-4", ModelPrice::new(15.0, 75.0, 18.75, 1.5)),
+    ("claude-opus-4-5", ModelPrice::new(5.0, 25.0, 6.25, 0.5)),
+    ("claude-sonnet-4", ModelPrice::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-3-7-sonnet", ModelPrice::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-3-5-sonnet", ModelPrice::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-haiku-4-5", ModelPrice::new(1.0, 5.0, 1.25, 0.1)),
+    ("claude-3-5-haiku", ModelPrice::new(0.8, 4.0, 1.0, 0.08)),
+];
+
+/// Model prices from `model_prices` in the state file on top of the built-in table
+pub struct PriceTable {
+    prices: Vec<(String, ModelPrice)>,
+}
+
+impl PriceTable {
+    pub fn from_state(state: &XlaudeState) -> Self {
+        let mut prices: Vec<(String, ModelPrice)> = DEFAULT_PRICES
+            .iter()
+            .filter(|(model, _)| !state.model_prices.contains_key(*model))
+            .map(|(model, price)| (model.to_string(), *price))
+            .collect();
+        prices.extend(
+            state
+                .model_prices
+                .iter()
+                .map(|(model, price)| (model.clone(), *price)),
+        );
+        Self { prices }
+    }
+
+    /// Price of the longest matching model prefix
+    pub fn lookup(&self, model: &str) -> Option<&ModelPrice> {
+        self.prices
+            .iter()
+            .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, price)| price)
+    }
+
+    /// Cost of the usage, or `None` if the model has no known price
+    pub fn cost(&self, model: &str, usage: &Usage) -> Option<f64> {
+        self.lookup(model).map(|price| price.cost(usage))
+    }
+}
+
+/// Tokens used by one model within one hour
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageBucket {
+    /// Start of the hour (UTC)
+    pub hour: DateTime<Utc>,
+    pub model: String,
+    pub usage: Usage,
+}
+
+impl UsageBucket {
+    /// Hour a response at `timestamp` is accounted to
+    pub fn hour_of(timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        timestamp
+            .duration_trunc(TimeDelta::hours(1))
+            .unwrap_or(timestamp)
+    }
+}
+
+/// Usage totals with their cost
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageTotals {
+    #[serde(flatten)]
+    pub usage: Usage,
+    /// Cost in USD of the tokens with a known price
+    pub cost_usd: f64,
+    /// Tokens of models without a known price, not included in the cost
+    pub unpriced_tokens: u64,
+}
+
+impl UsageTotals {
+    pub fn add(&mut self, prices: &PriceTable, bucket: &UsageBucket) {
+        self.usage.add(&bucket.usage);
+        match prices.cost(&bucket.model, &bucket.usage) {
+            Some(cost) => self.cost_usd += cost,
+            None => self.unpriced_tokens += bucket.usage.total(),
+        }
+    }
+
+    /// Compact cost figure, e.g. `$1.23 (45.6k tokens)`
+    pub fn summary(&self) -> String {
+        let mut text = format!(
+            "{} ({} tokens)",
+            format_cost(self.cost_usd),
+            format_tokens(self.usage.total())
+        );
+        if self.unpriced_tokens > 0 {
+            text.push_str(&format!(
+                ", {} unpriced",
+                format_tokens(self.unpriced_tokens)
+            ));
+        }
+        text
+    }
+}
+
+pub fn format_cost(cost: f64) -> String {
+    format!("${cost:.2}")
+}
+
+/// Token count with a k/M suffix, e.g. `45.6k`
+pub fn format_tokens(tokens: u64) -> String {
+    match tokens {
+        0..1_000 => tokens.to_string(),
+        1_000..1_000_000 => format!("{:.1}k", tokens as f64 / 1_000.0),
+        _ => format!("{:.1}M", tokens as f64 / 1_000_000.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_lookup_and_cost() {
+        let mut state = XlaudeState::default();
+        state.model_prices.insert(
+            "claude-sonnet-4".to_string(),
+            ModelPrice::new(1.0, 2.0, 0.0, 0.0),
+        );
+        state
+            .model_prices
+            .insert("gpt-5".to_string(), ModelPrice::new(1.25, 10.0, 0.0, 0.125));
+        let prices = PriceTable::from_state(&state);
+
+        // Longest prefix wins over the shorter built-in entry
+        assert_eq!(
+            prices.lookup("claude-opus-4-5-20251101").unwrap().input,
+            5.0
+        );
+        assert_eq!(
+            prices.lookup("claude-opus-4-1-20250805").unwrap().input,
+            15.0
+        );
+        // Configured prices replace built-in ones
+        assert_eq!(
+            prices.lookup("claude-sonnet-4-20250514").unwrap().input,
+            1.0
+        );
+        assert!(prices.lookup("unknown-model").is_none());
+
+        let usage = Usage {
+            input_tokens: 1_000_000,
+            output_tokens: 500_000,
+            ..Usage::default()
+        };
+        assert_eq!(prices.cost("claude-sonnet-4-20250514", &usage), Some(2.0));
+
+        let mut totals = UsageTotals::default();
+        for model in ["claude-sonnet-4", "mystery"] {
+            totals.add(
+                &prices,
+                &UsageBucket {
+                    hour: Utc::now(),
+                    model: model.to_string(),
+                    usage,
+                },
+            );
+        }
+        assert_eq!(totals.summary(), "$2.00 (3.0M tokens), 1.5M unpriced");
+    }
+
+    #[test]
+    fn test_format_tokens() {
+        assert_eq!(format_tokens(999), "999");
+        assert_eq!(format_tokens(45_600), "45.6k");
+        assert_eq!(format_tokens(2_500_000), "2.5M");
+    }
+}
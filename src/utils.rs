@@ -1,7 +1,8 @@
 use anyhow::{Context, Result};
 use rand::seq::SliceRandom;
 use rand::{RngCore, SeedableRng};
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 pub fn generate_random_name() -> Result<String> {
     // Allow setting seed for testing
@@ -54,3 +55,245 @@ where
 
     result
 }
+
+/// Recursively copy a directory, creating `dst` if needed
+pub fn copy_dir_all(src: &Path, dst: &Path) -> Result<()> {
+    fs::create_dir_all(dst).with_context(|| format!("Failed to create {}", dst.display()))?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)
+                .with_context(|| format!("Failed to copy {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Parse a human friendly duration such as `30m`, `12h`, `14d` or `2w`
+pub fn parse_duration(input: &str) -> Result<chrono::Duration> {
+    let input = input.trim();
+    let split = input.find(|c: char| !c.is_ascii_digit()).context(format!(
+        "Missing unit in duration '{input}' (use s, m, h, d or w)"
+    ))?;
+    let (value, unit) = input.split_at(split);
+    let value: i64 = value
+        .parse()
+        .with_context(|| format!("Invalid duration '{input}'"))?;
+
+    match unit {
+        "s" => Ok(chrono::Duration::seconds(value)),
+        "m" => Ok(chrono::Duration::minutes(value)),
+        "h" => Ok(chrono::Duration::hours(value)),
+        "d" => Ok(chrono::Duration::days(value)),
+        "w" => Ok(chrono::Duration::weeks(value)),
+        _ => anyhow::bail!("Unknown unit '{unit}' in duration '{input}' (use s, m, h, d or w)"),
+    }
+}
+
+/// Format a duration compactly using its largest unit, e.g. `45m`, `3h`, `12d`
+pub fn format_duration_short(duration: chrono::Duration) -> String {
+    if duration.num_minutes() < 60 {
+        format!("{}m", duration.num_minutes().max(0))
+    } else if duration.num_hours() < 24 {
+        format!("{}h", duration.num_hours())
+    } else {
+        format!("{}d", duration.num_days())
+    }
+}
+
+/// Match `text` against a shell-style glob pattern supporting `*` and `?`
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            // Let the last `*` swallow one more character and retry
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Expand `{field}` placeholders in a user template.
+///
+/// Unknown fields are left untouched. The escapes `\t`, `\n` and `\\` are
+/// interpreted so templates can be passed in single quotes from a shell.
+pub fn expand_template(template: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.peek() {
+                Some('t') => {
+                    chars.next();
+                    output.push('\t');
+                }
+                Some('n') => {
+                    chars.next();
+                    output.push('\n');
+                }
+                Some('\\') => {
+                    chars.next();
+                    output.push('\\');
+                }
+                _ => output.push(c),
+            },
+            '{' => {
+                let field: String = chars.clone().take_while(|&c| c != '}').collect();
+                let closed = chars.clone().nth(field.chars().count()) == Some('}');
+                match lookup(&field) {
+                    Some(value) if closed => {
+                        output.push_str(&value);
+                        // Skip the field name and the closing brace
+                        for _ in 0..=field.chars().count() {
+                            chars.next();
+                        }
+                    }
+                    _ => output.push(c),
+                }
+            }
+            _ => output.push(c),
+        }
+    }
+
+    output
+}
+
+/// A running process whose working directory is inside a given path
+#[derive(Debug, Clone)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub command: String,
+    pub cwd: PathBuf,
+}
+
+/// Find processes whose working directory is inside `dir`
+///
+/// Uses `/proc` where available and falls back to `lsof` elsewhere (macOS).
+/// Processes that cannot be inspected are silently skipped.
+pub fn find_processes_in_dir(dir: &Path) -> Vec<ProcessInfo> {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    let own_pid = std::process::id();
+
+    let processes = if Path::new("/proc").is_dir() {
+        list_processes_from_proc()
+    } else {
+        list_processes_from_lsof()
+    };
+
+    processes
+        .into_iter()
+        .filter(|p| p.pid != own_pid && p.cwd.starts_with(&dir))
+        .collect()
+}
+
+fn list_processes_from_proc() -> Vec<ProcessInfo> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return vec![];
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+            let cwd = fs::read_link(entry.path().join("cwd")).ok()?;
+            let command = fs::read_to_string(entry.path().join("comm")).ok()?;
+            Some(ProcessInfo {
+                pid,
+                command: command.trim().to_string(),
+                cwd,
+            })
+        })
+        .collect()
+}
+
+fn list_processes_from_lsof() -> Vec<ProcessInfo> {
+    let Ok(output) = std::process::Command::new("lsof")
+        .args(["-a", "-d", "cwd", "-F", "pcn"])
+        .output()
+    else {
+        return vec![];
+    };
+
+    // lsof -F emits one field per line: p<pid>, c<command>, n<path>
+    let mut processes = Vec::new();
+    let (mut pid, mut command) = (None, String::new());
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        match line.split_at_checked(1) {
+            Some(("p", value)) => pid = value.parse().ok(),
+            Some(("c", value)) => command = value.to_string(),
+            Some(("n", value)) => {
+                if let Some(pid) = pid {
+                    processes.push(ProcessInfo {
+                        pid,
+                        command: command.clone(),
+                        cwd: PathBuf::from(value),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    processes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("14d").unwrap(), chrono::Duration::days(14));
+        assert_eq!(parse_duration("3h").unwrap(), chrono::Duration::hours(3));
+        assert_eq!(parse_duration("2w").unwrap(), chrono::Duration::weeks(2));
+        assert!(parse_duration("14").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("5y").is_err());
+    }
+
+    #[test]
+    fn test_expand_template() {
+        let lookup = |field: &str| match field {
+            "name" => Some("feature".to_string()),
+            "repo" => Some("xlaude".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            expand_template("{repo}/{name}\\t{unknown}", lookup),
+            "xlaude/feature\t{unknown}"
+        );
+        assert_eq!(expand_template("{name", lookup), "{name");
+        assert_eq!(expand_template("a\\nb\\\\c", lookup), "a\nb\\c");
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(".env", ".env"));
+        assert!(glob_match(".env.*", ".env.local"));
+        assert!(!glob_match(".env.*", ".env"));
+        assert!(glob_match("*.pem", "certs/server.pem"));
+        assert!(glob_match("data-?.db", "data-1.db"));
+        assert!(!glob_match("data-?.db", "data-10.db"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("*.key", "keys/"));
+    }
+}
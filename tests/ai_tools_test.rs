@@ -2,12 +2,11 @@ use assert_cmd::Command;
 use predicates::prelude::*;
 use std::env;
 use std::fs;
-use std::path::Path;
 use std::process::Command as StdCommand;
 use tempfile::TempDir;
 
-// Set up a mock git repository with a worktree
-fn setup_git_repo_with_worktree() -> (TempDir, TempDir) {
+// Set up a mock git repository with a worktree, registered in an isolated xlaude config dir
+fn setup_git_repo_with_worktree() -> (TempDir, TempDir, TempDir) {
     // Create main repo
     let repo_dir = TempDir::new().unwrap();
     
@@ -55,10 +54,8 @@ fn setup_git_repo_with_worktree() -> (TempDir, TempDir) {
         .output()
         .unwrap();
     
-    // Create the xlaude state directory if it doesn't exist
-    let home_dir = env::var("HOME").unwrap();
-    let state_dir = Path::new(&home_dir).join(".local/share/xlaude");
-    fs::create_dir_all(&state_dir).unwrap_or_default();
+    // Keep xlaude state isolated from the user's real config
+    let config_dir = TempDir::new().unwrap();
     
     // Register the worktree with xlaude
     Command::cargo_bin("xlaude")
@@ -66,11 +63,12 @@ fn setup_git_repo_with_worktree() -> (TempDir, TempDir) {
         .arg("add")
         .arg(worktree_name)
         .current_dir(worktree_dir.path())
+        .env("XLAUDE_CONFIG_DIR", config_dir.path())
         .env("XLAUDE_YES", "1")
         .output()
         .unwrap();
     
-    (repo_dir, worktree_dir)
+    (repo_dir, worktree_dir, config_dir)
 }
 
 // Mock an AI tool for testing by creating a dummy executable
@@ -119,7 +117,7 @@ exit 0
 #[test]
 fn test_opencode_priority() {
     // Set up git repo with worktree
-    let (_repo_dir, worktree_dir) = setup_git_repo_with_worktree();
+    let (_repo_dir, worktree_dir, config_dir) = setup_git_repo_with_worktree();
     
     // Create a temporary directory for mock tools
     let temp_dir = TempDir::new().unwrap();
@@ -135,6 +133,7 @@ fn test_opencode_priority() {
         .arg("open")
         .arg("test-feature") // Specify the worktree name
         .current_dir(worktree_dir.path())
+        .env("XLAUDE_CONFIG_DIR", config_dir.path())
         .env("PATH", new_path)
         .env("XLAUDE_MOCK_TEST", "1") // Signal that we're in test mode
         .env("XLAUDE_MOCK_OUTPUT", "1") // Capture mock tool execution
@@ -151,7 +150,7 @@ fn test_opencode_priority() {
 #[test]
 fn test_ai_tool_fallback() {
     // Set up git repo with worktree
-    let (_repo_dir, worktree_dir) = setup_git_repo_with_worktree();
+    let (_repo_dir, worktree_dir, config_dir) = setup_git_repo_with_worktree();
     
     // Create a temporary directory for mock tools
     let temp_dir = TempDir::new().unwrap();
@@ -169,6 +168,7 @@ fn test_ai_tool_fallback() {
         .arg("open")
         .arg("test-feature") // Specify the worktree name
         .current_dir(worktree_dir.path())
+        .env("XLAUDE_CONFIG_DIR", config_dir.path())
         .env("PATH", new_path)
         .env("XLAUDE_MOCK_TEST", "1") // Signal that we're in test mode
         .env("XLAUDE_MOCK_OUTPUT", "1") // Capture mock tool execution
@@ -185,7 +185,7 @@ fn test_ai_tool_fallback() {
 #[test]
 fn test_env_override() {
     // Set up git repo with worktree
-    let (_repo_dir, worktree_dir) = setup_git_repo_with_worktree();
+    let (_repo_dir, worktree_dir, config_dir) = setup_git_repo_with_worktree();
     
     // Create a temporary directory for mock tools
     let temp_dir = TempDir::new().unwrap();
@@ -204,6 +204,7 @@ fn test_env_override() {
         .arg("open")
         .arg("test-feature") // Specify the worktree name
         .current_dir(worktree_dir.path())
+        .env("XLAUDE_CONFIG_DIR", config_dir.path())
         .env("PATH", new_path)
         .env("XLAUDE_OPENCODE_CMD", &custom_tool_path) // Override the opencode command
         .env("XLAUDE_MOCK_TEST", "1") // Signal that we're in test mode
@@ -217,3 +218,411 @@ fn test_env_override() {
         .stderr(predicate::str::contains("MOCK_AI_TOOL_EXECUTED"))
         .stderr(predicate::str::contains("custom_ai_tool"));
 }
+
+#[test]
+fn test_continue_and_resume_flags() {
+    // Set up git repo with worktree
+    let (_repo_dir, worktree_dir, config_dir) = setup_git_repo_with_worktree();
+
+    let temp_dir = TempDir::new().unwrap();
+    let mock_path = create_mock_ai_tool(&temp_dir, "opencode");
+    let original_path = env::var("PATH").unwrap_or_default();
+    let new_path = format!("{}{}{}", mock_path, if cfg!(windows) { ";" } else { ":" }, original_path);
+
+    let run = |args: &[&str]| {
+        Command::cargo_bin("xlaude")
+            .unwrap()
+            .arg("open")
+            .arg("test-feature")
+            .args(args)
+            .current_dir(worktree_dir.path())
+            .env("XLAUDE_CONFIG_DIR", config_dir.path())
+            .env("PATH", &new_path)
+            .env("XLAUDE_YES", "1")
+            .env("XLAUDE_NON_INTERACTIVE", "1")
+            .assert()
+    };
+
+    // OpenCode continues with --continue and resumes with --session
+    run(&["--continue"])
+        .success()
+        .stdout(predicate::str::contains("--continue"));
+    run(&["--resume", "ses_123"])
+        .success()
+        .stdout(predicate::str::contains("--session ses_123"));
+
+    // Without an id the session is picked from the tool's own history
+    run(&["--resume"])
+        .failure()
+        .stderr(predicate::str::contains("No OpenCode sessions to resume"));
+
+    // The two options are mutually exclusive
+    run(&["--continue", "--resume", "ses_123"]).failure();
+}
+
+#[test]
+fn test_tools_from_config() {
+    // Set up git repo with worktree
+    let (_repo_dir, worktree_dir, config_dir) = setup_git_repo_with_worktree();
+
+    let temp_dir = TempDir::new().unwrap();
+    create_mock_ai_tool(&temp_dir, "opencode");
+    let mock_path = create_mock_ai_tool(&temp_dir, "aider");
+    let original_path = env::var("PATH").unwrap_or_default();
+    let new_path = format!("{}{}{}", mock_path, if cfg!(windows) { ";" } else { ":" }, original_path);
+
+    // A user-defined tool ahead of the built-ins, which can also be disabled
+    let state_path = config_dir.path().join("state.json");
+    let write_tools = |tools: serde_json::Value| {
+        let mut state: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&state_path).unwrap()).unwrap();
+        state["tools"] = tools;
+        fs::write(&state_path, state.to_string()).unwrap();
+    };
+    write_tools(serde_json::json!({
+        "aider": {"name": "Aider", "args": ["--no-auto-commits"], "priority": 1, "resume_flag": "--restore"},
+    }));
+
+    let run = |args: &[&str]| {
+        Command::cargo_bin("xlaude")
+            .unwrap()
+            .arg("open")
+            .arg("test-feature")
+            .args(args)
+            .current_dir(worktree_dir.path())
+            .env("XLAUDE_CONFIG_DIR", config_dir.path())
+            .env("PATH", &new_path)
+            .env("XLAUDE_YES", "1")
+            .env("XLAUDE_NON_INTERACTIVE", "1")
+            .assert()
+    };
+
+    run(&[])
+        .success()
+        .stdout(predicate::str::contains("Using Aider"))
+        .stdout(predicate::str::contains("aider --no-auto-commits"));
+
+    // Aider has no session provider, so an id is required
+    run(&["--resume"])
+        .failure()
+        .stderr(predicate::str::contains("needs a session id"));
+    run(&["--continue"])
+        .failure()
+        .stderr(predicate::str::contains("Aider cannot continue"));
+
+    write_tools(serde_json::json!({
+        "aider": {"disabled": true},
+        "opencode": {"args": ["--model", "local"]},
+    }));
+    run(&[])
+        .success()
+        .stdout(predicate::str::contains("opencode --model local"));
+}
+
+#[test]
+fn test_tool_selection_is_remembered() {
+    // Set up git repo with worktree
+    let (_repo_dir, worktree_dir, config_dir) = setup_git_repo_with_worktree();
+
+    let temp_dir = TempDir::new().unwrap();
+    for tool in ["opencode", "qwen", "claude"] {
+        create_mock_ai_tool(&temp_dir, tool);
+    }
+    let mock_path = temp_dir.path().to_string_lossy().to_string();
+    let original_path = env::var("PATH").unwrap_or_default();
+    let new_path = format!("{}{}{}", mock_path, if cfg!(windows) { ";" } else { ":" }, original_path);
+
+    let xlaude = |args: &[&str]| {
+        Command::cargo_bin("xlaude")
+            .unwrap()
+            .args(args)
+            .current_dir(worktree_dir.path())
+            .env("XLAUDE_CONFIG_DIR", config_dir.path())
+            .env("PATH", &new_path)
+            .env("XLAUDE_YES", "1")
+            .env("XLAUDE_NON_INTERACTIVE", "1")
+            .assert()
+    };
+    let state_path = config_dir.path().join("state.json");
+    let read_state = || -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(&state_path).unwrap()).unwrap()
+    };
+
+    // The repository's default tool wins over the built-in priority
+    let mut state = read_state();
+    let repo_name = state["worktrees"].as_object().unwrap().values().next().unwrap()["repo_name"].clone();
+    state["repos"] = serde_json::json!({repo_name.as_str().unwrap(): {"default_tool": "qwen"}});
+    state["tools"] = serde_json::json!({"missing": {"command": "xlaude-missing-tool"}});
+    fs::write(&state_path, state.to_string()).unwrap();
+    xlaude(&["open", "test-feature"])
+        .success()
+        .stderr(predicate::str::contains("/qwen"));
+
+    // An explicit tool is used and remembered
+    xlaude(&["open", "test-feature", "--tool", "claude"])
+        .success()
+        .stdout(predicate::str::contains("claude --dangerously-skip-permissions"));
+    xlaude(&["open", "test-feature"])
+        .success()
+        .stderr(predicate::str::contains("/claude"));
+
+    let output = xlaude(&["list", "--json"]).success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(json["worktrees"][0]["tool"], "claude");
+    xlaude(&["list"])
+        .success()
+        .stdout(predicate::str::contains("Tool: claude"));
+
+    // Tools that are unknown or not installed are reported
+    xlaude(&["open", "test-feature", "--tool", "gemini"])
+        .failure()
+        .stderr(predicate::str::contains("Unknown AI tool 'gemini'"));
+    xlaude(&["open", "test-feature", "--tool", "missing"])
+        .failure()
+        .stderr(predicate::str::contains("not installed"));
+}
+
+#[test]
+fn test_extra_args_and_prompt() {
+    // Set up git repo with worktree
+    let (repo_dir, worktree_dir, config_dir) = setup_git_repo_with_worktree();
+
+    let temp_dir = TempDir::new().unwrap();
+    let mock_path = create_mock_ai_tool(&temp_dir, "claude");
+    let original_path = env::var("PATH").unwrap_or_default();
+    let new_path = format!("{}{}{}", mock_path, if cfg!(windows) { ";" } else { ":" }, original_path);
+
+    let xlaude = |dir: &std::path::Path, args: &[&str]| {
+        Command::cargo_bin("xlaude")
+            .unwrap()
+            .args(args)
+            .current_dir(dir)
+            .env("XLAUDE_CONFIG_DIR", config_dir.path())
+            .env("PATH", &new_path)
+            .env_remove("TMUX_PANE")
+            .env("XLAUDE_YES", "1")
+            .env("XLAUDE_NON_INTERACTIVE", "1")
+            .assert()
+    };
+
+    // Arguments after `--` follow the tool's default arguments
+    xlaude(worktree_dir.path(), &["open", "test-feature", "--tool", "claude", "--", "--model", "opus"])
+        .success()
+        .stdout(predicate::str::contains("claude --dangerously-skip-permissions --model opus"));
+
+    // A command override keeps the default arguments
+    Command::cargo_bin("xlaude")
+        .unwrap()
+        .args(["open", "test-feature"])
+        .current_dir(worktree_dir.path())
+        .env("XLAUDE_CONFIG_DIR", config_dir.path())
+        .env("XLAUDE_CLAUDE_CMD", format!("{}/claude", mock_path))
+        .env("XLAUDE_YES", "1")
+        .env("XLAUDE_NON_INTERACTIVE", "1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("claude --dangerously-skip-permissions"));
+
+    // A new worktree is opened right away with the prompt as the first message
+    let state_path = config_dir.path().join("state.json");
+    let mut state: serde_json::Value = serde_json::from_str(&fs::read_to_string(&state_path).unwrap()).unwrap();
+    let repo_name = state["worktrees"].as_object().unwrap().values().next().unwrap()["repo_name"].clone();
+    state["repos"] = serde_json::json!({repo_name.as_str().unwrap(): {"default_tool": "claude"}});
+    fs::write(&state_path, state.to_string()).unwrap();
+
+    let prompt_file = temp_dir.path().join("task.md");
+    fs::write(&prompt_file, "fix the login bug").unwrap();
+    xlaude(repo_dir.path(), &["create", "fix-login", "--prompt", "a", "--prompt-file", "task.md"]).failure();
+    xlaude(repo_dir.path(), &["create", "fix-login", "--prompt-file", prompt_file.to_str().unwrap()])
+        .success()
+        .stdout(predicate::str::contains("--dangerously-skip-permissions fix the login bug"));
+
+    let state: serde_json::Value = serde_json::from_str(&fs::read_to_string(&state_path).unwrap()).unwrap();
+    let created = state["worktrees"].as_object().unwrap().values().find(|info| info["name"] == "fix-login").unwrap();
+    assert_eq!(created["tool"], "claude");
+    let _ = fs::remove_dir_all(created["path"].as_str().unwrap());
+}
+
+#[test]
+fn test_headless_run() {
+    // Set up git repo with worktree
+    let (_repo_dir, worktree_dir, config_dir) = setup_git_repo_with_worktree();
+
+    let temp_dir = TempDir::new().unwrap();
+    let mock_path = create_mock_ai_tool(&temp_dir, "claude");
+    let original_path = env::var("PATH").unwrap_or_default();
+    let new_path = format!("{}{}{}", mock_path, if cfg!(windows) { ";" } else { ":" }, original_path);
+
+    let state_path = config_dir.path().join("state.json");
+    let read_state = || -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(&state_path).unwrap()).unwrap()
+    };
+    let mut state = read_state();
+    state["tools"] = serde_json::json!({
+        "failing": {"command": "sh", "headless_args": ["-c", "echo {prompt}; exit 3"]},
+        "slow": {"command": "sleep", "headless_args": ["10"]},
+        "pause": {"command": "sleep", "headless_args": ["1"]},
+    });
+    fs::write(&state_path, state.to_string()).unwrap();
+
+    let xlaude = |args: &[&str]| {
+        Command::cargo_bin("xlaude")
+            .unwrap()
+            .args(args)
+            .current_dir(worktree_dir.path())
+            .env("XLAUDE_CONFIG_DIR", config_dir.path())
+            .env("PATH", &new_path)
+            .env("XLAUDE_NON_INTERACTIVE", "1")
+            .assert()
+    };
+
+    // The tool runs in print mode and its output ends up in the log
+    xlaude(&["run", "test-feature", "--tool", "claude", "--prompt", "fix the bug"])
+        .success()
+        .stdout(predicate::str::contains("claude --dangerously-skip-permissions --print fix the bug"));
+    let run = read_state()["worktrees"].as_object().unwrap().values().next().unwrap()["last_run"].clone();
+    assert_eq!(run["status"], "succeeded");
+    assert_eq!(run["exit_code"], 0);
+    assert!(run["final_message"].as_str().unwrap().starts_with("Mock AI tool:"));
+    let log = fs::read_to_string(run["log"].as_str().unwrap()).unwrap();
+    assert!(log.contains("--print fix the bug"));
+    assert!(log.contains("MOCK_AI_TOOL_EXECUTED"));
+
+    // The exit code of a failing tool is passed on
+    let output = xlaude(&["--output", "json", "run", "test-feature", "--tool", "failing", "--prompt", "oops"])
+        .code(3)
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["ok"], false);
+    assert_eq!(json["error"]["code"], "agent_failed");
+    assert_eq!(json["result"]["run"]["status"], "failed");
+    assert_eq!(json["result"]["run"]["final_message"], "oops");
+
+    // A run exceeding its timeout is stopped
+    xlaude(&["run", "test-feature", "--tool", "slow", "--prompt", "wait", "--timeout", "1s"])
+        .code(124)
+        .stderr(predicate::str::contains("was stopped after"));
+    let run = read_state()["worktrees"].as_object().unwrap().values().next().unwrap()["last_run"].clone();
+    assert_eq!(run["status"], "timed_out");
+
+    // Concurrent runs get their own logs and keep changes made meanwhile
+    let spawn_run = || {
+        StdCommand::new(assert_cmd::cargo::cargo_bin("xlaude"))
+            .args(["run", "test-feature", "--tool", "pause", "--prompt", "wait"])
+            .current_dir(worktree_dir.path())
+            .env("XLAUDE_CONFIG_DIR", config_dir.path())
+            .env("PATH", &new_path)
+            .env("XLAUDE_NON_INTERACTIVE", "1")
+            .spawn()
+            .unwrap()
+    };
+    let runs = [spawn_run(), spawn_run()];
+    xlaude(&["tag", "test-feature", "during-run"]).success();
+    for mut run in runs {
+        assert!(run.wait().unwrap().success());
+    }
+    let info = read_state()["worktrees"].as_object().unwrap().values().next().unwrap().clone();
+    assert_eq!(info["tags"], serde_json::json!(["during-run"]));
+    assert_eq!(info["last_run"]["tool"], "pause");
+    let logs: Vec<_> = fs::read_dir(config_dir.path().join("logs").join(info["repo_name"].as_str().unwrap()))
+        .unwrap()
+        .collect();
+    assert_eq!(logs.len(), 5);
+
+    #[cfg(unix)]
+    {
+        // A killed process lingers until it is reaped, so give that a moment
+        let is_running = |pid_file: &std::path::Path| {
+            let pid = fs::read_to_string(pid_file).unwrap();
+            (0..50).all(|_| {
+                let alive = StdCommand::new("kill").args(["-0", pid.trim()]).status().unwrap().success();
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                alive
+            })
+        };
+        let spawning = temp_dir.path().join("spawning.pid");
+        let detaching = temp_dir.path().join("detaching.pid");
+        let mut state = read_state();
+        state["tools"]["spawning"] = serde_json::json!({
+            "command": "sh",
+            "headless_args": ["-c", format!("sleep 30 & echo $! > {}; wait", spawning.display())],
+        });
+        state["tools"]["detaching"] = serde_json::json!({
+            "command": "sh",
+            "headless_args": ["-c", format!("sleep 30 & echo $! > {}", detaching.display())],
+        });
+        fs::write(&state_path, state.to_string()).unwrap();
+
+        // A timeout also stops the processes the tool started
+        xlaude(&["run", "test-feature", "--tool", "spawning", "--prompt", "wait", "--timeout", "1s"]).code(124);
+        assert!(!is_running(&spawning));
+
+        // A background process holding the output open does not keep the run waiting
+        let started = std::time::Instant::now();
+        xlaude(&["run", "test-feature", "--tool", "detaching", "--prompt", "go"]).success();
+        assert!(started.elapsed() < std::time::Duration::from_secs(20));
+        let pid = fs::read_to_string(&detaching).unwrap();
+        let _ = StdCommand::new("kill").arg(pid.trim()).status();
+    }
+
+    xlaude(&["run", "test-feature"]).failure();
+}
+
+#[test]
+fn test_permission_profiles() {
+    // Set up git repo with worktree
+    let (_repo_dir, worktree_dir, config_dir) = setup_git_repo_with_worktree();
+
+    let temp_dir = TempDir::new().unwrap();
+    let mock_path = create_mock_ai_tool(&temp_dir, "claude");
+    let original_path = env::var("PATH").unwrap_or_default();
+    let new_path = format!("{}{}{}", mock_path, if cfg!(windows) { ";" } else { ":" }, original_path);
+
+    let xlaude = |args: &[&str]| {
+        Command::cargo_bin("xlaude")
+            .unwrap()
+            .args(args)
+            .current_dir(worktree_dir.path())
+            .env("XLAUDE_CONFIG_DIR", config_dir.path())
+            .env("PATH", &new_path)
+            .env("XLAUDE_YES", "1")
+            .env("XLAUDE_NON_INTERACTIVE", "1")
+            .assert()
+    };
+    let state_path = config_dir.path().join("state.json");
+    let read_state = || -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(&state_path).unwrap()).unwrap()
+    };
+
+    // The repository's default profile replaces the tool's own
+    let mut state = read_state();
+    let repo_name = state["worktrees"].as_object().unwrap().values().next().unwrap()["repo_name"].clone();
+    state["repos"] = serde_json::json!({repo_name.as_str().unwrap(): {"default_tool": "claude", "default_profile": "ask"}});
+    fs::write(&state_path, state.to_string()).unwrap();
+    xlaude(&["open", "test-feature"])
+        .success()
+        .stdout(predicate::str::contains("claude --permission-mode default"))
+        .stdout(predicate::str::contains("dangerously").not());
+
+    // An explicit profile is used and remembered
+    xlaude(&["open", "test-feature", "--profile", "readonly"])
+        .success()
+        .stdout(predicate::str::contains("claude --permission-mode plan"));
+    xlaude(&["open", "test-feature"])
+        .success()
+        .stdout(predicate::str::contains("claude --permission-mode plan"));
+    let output = xlaude(&["list", "--json"]).success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(json["worktrees"][0]["profile"], "readonly");
+
+    // Headless runs use the same profile
+    xlaude(&["run", "test-feature", "--prompt", "review", "--profile", "yolo"])
+        .success()
+        .stdout(predicate::str::contains("claude --dangerously-skip-permissions --print review"));
+
+    xlaude(&["open", "test-feature", "--profile", "unknown"])
+        .failure()
+        .stderr(predicate::str::contains("no permission profile 'unknown'"));
+}
@@ -77,6 +77,17 @@ impl TestContext {
             .unwrap();
     }
 
+    /// Where Claude keeps transcripts for `path`: every character other
+    /// than an ASCII letter or digit is encoded as `-`
+    fn claude_project_dir(&self, path: &Path) -> PathBuf {
+        let encoded: String = path
+            .to_string_lossy()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        self.temp_dir.path().join(".claude/projects").join(encoded)
+    }
+
     fn xlaude(&self, args: &[&str]) -> Command {
         let mut cmd = Command::cargo_bin("xlaude").unwrap();
         cmd.current_dir(&self.repo_dir)
@@ -87,7 +98,9 @@ impl TestContext {
             // Disable color output for consistent snapshots
             .env("NO_COLOR", "1")
             // Enable non-interactive mode for testing
-            .env("XLAUDE_NON_INTERACTIVE", "1");
+            .env("XLAUDE_NON_INTERACTIVE", "1")
+            // Claude transcripts live under the fake HOME
+            .env_remove("CLAUDE_CONFIG_DIR");
 
         cmd.args(args);
         cmd
@@ -100,7 +113,8 @@ impl TestContext {
             .env("XLAUDE_CONFIG_DIR", &self.config_dir)
             .env("XLAUDE_CLAUDE_CMD", "true")
             .env("NO_COLOR", "1")
-            .env("XLAUDE_NON_INTERACTIVE", "1");
+            .env("XLAUDE_NON_INTERACTIVE", "1")
+            .env_remove("CLAUDE_CONFIG_DIR");
 
         cmd.args(args);
         cmd
@@ -239,6 +253,187 @@ fn test_list_with_worktrees() {
     assert_snapshot!(redacted_stdout);
 }
 
+#[test]
+fn test_list_with_status_json() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.xlaude(&["create", "busy"]).assert().success();
+    ctx.xlaude(&["create", "idle"]).assert().success();
+
+    // One commit ahead of the base branch plus an untracked file
+    let worktree_path = ctx.temp_dir.path().join("test-repo-busy");
+    std::process::Command::new("git")
+        .args([
+            "commit",
+            "--allow-empty",
+            "--no-gpg-sign",
+            "-m",
+            "Add busy work",
+        ])
+        .current_dir(&worktree_path)
+        .output()
+        .unwrap();
+    fs::write(worktree_path.join("notes.txt"), "wip").unwrap();
+
+    let output = ctx
+        .xlaude(&["list", "--json", "--status"])
+        .assert()
+        .success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let worktrees = json["worktrees"].as_array().unwrap();
+
+    let busy = &worktrees[0]["status"];
+    assert_eq!(busy["dirty_files"], 1);
+    assert_eq!(busy["base"]["ahead"], 1);
+    assert_eq!(busy["last_commit"]["subject"], "Add busy work");
+    assert_eq!(busy["upstream"], serde_json::Value::Null);
+    assert_eq!(busy["merged"], false);
+
+    let idle = &worktrees[1]["status"];
+    assert_eq!(idle["dirty_files"], 0);
+    assert_eq!(idle["base"]["ahead"], 0);
+
+    // Plain JSON output stays unchanged
+    let output = ctx.xlaude(&["list", "--json"]).assert().success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert!(json["worktrees"][0].get("status").is_none());
+}
+
+#[test]
+fn test_list_json_includes_runtime() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "no-session"]).assert().success();
+
+    let output = ctx.xlaude(&["list", "--json"]).assert().success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let runtime = &json["worktrees"][0]["runtime"];
+    assert_eq!(runtime["session_running"], false);
+    assert_eq!(runtime["attached"], false);
+    assert_eq!(runtime["last_activity"], serde_json::Value::Null);
+    assert_eq!(runtime["status"], "not_running");
+}
+
+#[test]
+fn test_status_for_prompt() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "feature-x"]).assert().success();
+    let worktree_path = ctx.temp_dir.path().join("test-repo-feature-x");
+
+    ctx.xlaude_in_dir(&worktree_path, &["status"])
+        .assert()
+        .success()
+        .stdout("feature-x (feature-x) not_running\n");
+
+    ctx.xlaude_in_dir(
+        &worktree_path,
+        &["status", "--format", "xlaude: {name} ({waiting} waiting)"],
+    )
+    .assert()
+    .success()
+    .stdout("xlaude: feature-x (0 waiting)\n");
+    assert!(ctx.config_dir.join("status-cache.json").exists());
+
+    // Outside a managed worktree there is nothing to show
+    ctx.xlaude(&["status"]).assert().success().stdout("");
+}
+
+#[test]
+fn test_show_worktree_report() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "feature"]).assert().success();
+
+    let worktree_path = ctx.temp_dir.path().join("test-repo-feature");
+    fs::write(worktree_path.join("feature.txt"), "done").unwrap();
+    for args in [
+        &["add", "feature.txt"][..],
+        &["commit", "--no-gpg-sign", "-m", "Add feature"],
+    ] {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(&worktree_path)
+            .output()
+            .unwrap();
+    }
+    fs::write(worktree_path.join("notes.txt"), "wip").unwrap();
+
+    let output = ctx.xlaude(&["show", "feature"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Add feature"));
+    assert!(stdout.contains("1 file changed"));
+    assert!(stdout.contains("?? notes.txt"));
+    assert!(stdout.contains("tmux session: not running"));
+
+    let output = ctx
+        .xlaude(&["--output", "json", "show", "feature"])
+        .assert()
+        .success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let report = &json["result"]["worktree"];
+    assert_eq!(report["name"], "feature");
+    assert_eq!(report["status"]["base"]["ahead"], 1);
+    assert_eq!(report["commits_since_base"].as_array().unwrap().len(), 1);
+    assert_eq!(report["dirty_files"][0], "?? notes.txt");
+    assert_eq!(report["tmux"]["running"], false);
+}
+
+#[test]
+fn test_list_filters_and_formats() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.xlaude(&["create", "alpha"]).assert().success();
+    ctx.xlaude(&["create", "beta"]).assert().success();
+    ctx.xlaude(&["create", "gamma"]).assert().success();
+    ctx.xlaude(&["tag", "beta", "backend"]).assert().success();
+    ctx.xlaude(&["tag", "gamma", "backend"]).assert().success();
+    fs::write(ctx.temp_dir.path().join("test-repo-gamma/wip.txt"), "wip").unwrap();
+
+    let output = ctx
+        .xlaude(&["list", "--tag", "backend", "--names-only"])
+        .assert()
+        .success();
+    assert_eq!(
+        String::from_utf8_lossy(&output.get_output().stdout),
+        "beta\ngamma\n"
+    );
+
+    let output = ctx.xlaude(&["list", "-0"]).assert().success();
+    assert_eq!(output.get_output().stdout, b"alpha\0beta\0gamma\0");
+
+    let output = ctx
+        .xlaude(&[
+            "list",
+            "--status",
+            "dirty",
+            "--format",
+            "{repo}/{name}\\t{branch}\\t{status}\\t{tags}",
+        ])
+        .assert()
+        .success();
+    assert_eq!(
+        String::from_utf8_lossy(&output.get_output().stdout),
+        "test-repo/gamma\tgamma\tdirty\tbackend\n"
+    );
+
+    let output = ctx
+        .xlaude(&["list", "--repo", "other-repo", "--names-only"])
+        .assert()
+        .success();
+    assert!(output.get_output().stdout.is_empty());
+
+    // Removing the tag drops the worktree from the filter
+    ctx.xlaude(&["tag", "beta", "backend", "--remove"])
+        .assert()
+        .success();
+    let output = ctx
+        .xlaude(&["list", "--tag", "backend", "--names-only"])
+        .assert()
+        .success();
+    assert_eq!(
+        String::from_utf8_lossy(&output.get_output().stdout),
+        "gamma\n"
+    );
+}
+
 // Delete command tests
 #[test]
 fn test_delete_clean_worktree() {
@@ -262,6 +457,46 @@ fn test_delete_clean_worktree() {
     assert_eq!(state["worktrees"].as_object().unwrap().len(), 0);
 }
 
+#[test]
+fn test_delete_archives_transcripts() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.xlaude(&["create", "chatty"]).assert().success();
+
+    // Fake a Claude project directory for the worktree
+    let worktree_path = ctx
+        .temp_dir
+        .path()
+        .join("test-repo-chatty")
+        .canonicalize()
+        .unwrap();
+    let project_dir = ctx.claude_project_dir(&worktree_path);
+    fs::create_dir_all(&project_dir).unwrap();
+    fs::write(project_dir.join("session.jsonl"), "{}\n").unwrap();
+
+    let output = ctx
+        .xlaude(&["delete", "chatty", "--transcripts", "archive"])
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("transcripts: archived to"));
+    assert!(!project_dir.exists());
+
+    let archived: Vec<_> = fs::read_dir(ctx.config_dir.join("archive/transcripts"))
+        .unwrap()
+        .flatten()
+        .collect();
+    assert_eq!(archived.len(), 1);
+    assert!(
+        archived[0]
+            .file_name()
+            .to_string_lossy()
+            .starts_with("test-repo-chatty-")
+    );
+    assert!(archived[0].path().join("session.jsonl").exists());
+}
+
 #[test]
 fn test_delete_with_changes() {
     let ctx = TestContext::new("test-repo");
@@ -285,6 +520,143 @@ fn test_delete_with_changes() {
     assert!(worktree_path.exists());
 }
 
+#[test]
+fn test_delete_with_local_only_commits() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.xlaude(&["create", "local-commits"]).assert().success();
+
+    // Commit on a branch that has no upstream configured
+    let worktree_path = ctx.temp_dir.path().join("test-repo-local-commits");
+    fs::write(worktree_path.join("work.txt"), "agent work").unwrap();
+    for args in [
+        vec!["add", "."],
+        vec!["commit", "--no-gpg-sign", "-m", "Agent work"],
+    ] {
+        std::process::Command::new("git")
+            .args(&args)
+            .current_dir(&worktree_path)
+            .output()
+            .unwrap();
+    }
+
+    let output = ctx.xlaude(&["delete", "local-commits"]).assert().success();
+
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("1 commit(s) on 'local-commits' are not on any remote"));
+    assert!(stdout.contains("Cancelled"));
+    assert!(worktree_path.exists());
+}
+
+#[test]
+fn test_delete_with_stash_and_protected_files() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.xlaude(&["create", "stashed"]).assert().success();
+
+    let worktree_path = ctx.temp_dir.path().join("test-repo-stashed");
+
+    // Stash a change made on the worktree branch
+    fs::write(worktree_path.join("README.md"), "# Changed").unwrap();
+    std::process::Command::new("git")
+        .args(["stash", "push", "-m", "half-done"])
+        .current_dir(&worktree_path)
+        .output()
+        .unwrap();
+
+    // Ignored file matching a default protected pattern
+    fs::write(worktree_path.join(".gitignore"), ".env\n").unwrap();
+    fs::write(worktree_path.join(".env"), "TOKEN=secret").unwrap();
+    std::process::Command::new("git")
+        .args(["add", ".gitignore"])
+        .current_dir(&worktree_path)
+        .output()
+        .unwrap();
+    std::process::Command::new("git")
+        .args(["commit", "--no-gpg-sign", "-m", "Ignore env"])
+        .current_dir(&worktree_path)
+        .output()
+        .unwrap();
+
+    let output = ctx.xlaude(&["delete", "stashed"]).assert().success();
+
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("1 stash entry reference branch 'stashed'"));
+    assert!(stdout.contains("Ignored files matching protected patterns"));
+    assert!(stdout.contains(".env"));
+    assert!(stdout.contains("Cancelled"));
+    assert!(worktree_path.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_delete_detects_merged_pr_via_forge_cli() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let ctx = TestContext::new("test-repo");
+    std::process::Command::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/user/test-repo.git",
+        ])
+        .current_dir(&ctx.repo_dir)
+        .output()
+        .unwrap();
+
+    ctx.xlaude(&["create", "pr-merged"]).assert().success();
+
+    // Squash-merged branch: commit is on the remote but not merged locally
+    let worktree_path = ctx.temp_dir.path().join("test-repo-pr-merged");
+    fs::write(worktree_path.join("feature.txt"), "feature").unwrap();
+    for args in [
+        vec!["add", "."],
+        vec!["commit", "--no-gpg-sign", "-m", "Feature"],
+        vec!["update-ref", "refs/remotes/origin/pr-merged", "HEAD"],
+    ] {
+        std::process::Command::new("git")
+            .args(&args)
+            .current_dir(&worktree_path)
+            .output()
+            .unwrap();
+    }
+
+    // Fake gh CLI that records its arguments and reports a merged PR
+    let bin_dir = ctx.temp_dir.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let args_log = ctx.temp_dir.path().join("gh-args.log");
+    let gh = bin_dir.join("gh");
+    fs::write(
+        &gh,
+        format!(
+            "#!/bin/sh\necho \"$@\" >> {}\necho '[{{\"number\":12,\"state\":\"MERGED\",\"url\":\"u\",\"reviewDecision\":\"APPROVED\",\"statusCheckRollup\":[]}}]'\n",
+            args_log.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&gh, fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!(
+        "{}:{}",
+        bin_dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    let output = ctx
+        .xlaude(&["delete", "pr-merged"])
+        .env("PATH", path)
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Branch was merged via PR"));
+    assert!(stdout.contains("Worktree 'pr-merged' deleted successfully"));
+
+    let gh_args = fs::read_to_string(args_log).unwrap();
+    assert!(gh_args.contains("pr list"));
+    assert!(gh_args.contains("--head pr-merged"));
+}
+
 #[test]
 fn test_delete_current_worktree() {
     let ctx = TestContext::new("test-repo");
@@ -414,6 +786,13 @@ fn test_clean_invalid_worktrees() {
     new_state.insert("worktrees".to_string(), json!(new_worktrees));
     ctx.write_state(&json!(new_state));
 
+    // A dry run only reports the invalid worktree
+    let before = ctx.read_state();
+    let output = ctx.xlaude(&["clean", "--dry-run"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Dry run: 1 invalid worktree(s) would be removed"));
+    assert_eq!(ctx.read_state(), before);
+
     // Run clean
     let output = ctx.xlaude(&["clean"]).assert().success();
 
@@ -446,6 +825,247 @@ fn test_clean_with_no_invalid() {
     );
 }
 
+#[test]
+fn test_clean_older_than_prunes_stale_worktrees() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.xlaude(&["create", "stale"]).assert().success();
+    ctx.xlaude(&["create", "stale-dirty"]).assert().success();
+    ctx.xlaude(&["create", "fresh"]).assert().success();
+
+    // Backdate two of the worktrees
+    let mut state = ctx.read_state();
+    for key in ["test-repo/stale", "test-repo/stale-dirty"] {
+        state["worktrees"][key]["created_at"] = json!("2024-01-01T00:00:00Z");
+    }
+    ctx.write_state(&state);
+
+    let dirty_dir = ctx.temp_dir.path().join("test-repo-stale-dirty");
+    fs::write(dirty_dir.join("wip.txt"), "unsaved work").unwrap();
+
+    // Dry run only previews
+    let output = ctx
+        .xlaude(&["clean", "--older-than", "14d", "--dry-run"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("test-repo/stale "));
+    assert!(stdout.contains("keep (dirty)"));
+    assert!(!stdout.contains("test-repo/fresh"));
+    assert!(stdout.contains("Dry run: 1 worktree(s) would be removed"));
+    assert!(ctx.worktree_exists("stale"));
+
+    ctx.xlaude(&["clean", "--older-than", "14d"])
+        .env("XLAUDE_YES", "1")
+        .assert()
+        .success();
+
+    assert!(!ctx.worktree_exists("stale"));
+    assert!(ctx.worktree_exists("stale-dirty"));
+    assert!(ctx.worktree_exists("fresh"));
+
+    let state = ctx.read_state();
+    let worktrees = state["worktrees"].as_object().unwrap();
+    assert!(!worktrees.contains_key("test-repo/stale"));
+    assert!(worktrees.contains_key("test-repo/stale-dirty"));
+    assert!(worktrees.contains_key("test-repo/fresh"));
+}
+
+#[test]
+fn test_gc_uses_retention_policy() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.xlaude(&["create", "merged"]).assert().success();
+    ctx.xlaude(&["create", "fresh"]).assert().success();
+
+    // Without a policy gc refuses to run
+    let output = ctx.xlaude(&["gc"]).assert().failure();
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(stderr.contains("No retention policy configured"));
+
+    let mut state = ctx.read_state();
+    state["retention"] = json!({ "merged": true });
+    ctx.write_state(&state);
+
+    let git = |dir: &Path, args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?} failed");
+    };
+
+    // Work committed on a branch and fast-forwarded into the base
+    let merged_path = ctx.temp_dir.path().join("test-repo-merged");
+    fs::write(merged_path.join("feature.txt"), "feature").unwrap();
+    git(&merged_path, &["add", "feature.txt"]);
+    git(&merged_path, &["commit", "--no-gpg-sign", "-m", "Feature"]);
+    git(&ctx.repo_dir, &["merge", "--ff-only", "merged"]);
+
+    // A branch level with its base is kept
+    ctx.xlaude(&["gc"])
+        .env("XLAUDE_YES", "1")
+        .assert()
+        .success();
+    assert!(ctx.worktree_exists("merged"));
+
+    // Once the base moves past it, only the branch that had commits of its
+    // own counts as merged; the fresh one nothing was committed on is kept
+    fs::write(ctx.repo_dir.join("next.txt"), "next").unwrap();
+    git(&ctx.repo_dir, &["add", "next.txt"]);
+    git(&ctx.repo_dir, &["commit", "--no-gpg-sign", "-m", "Next"]);
+
+    let output = ctx.xlaude(&["gc", "--dry-run"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Dry run: 1 worktree(s) would be removed"));
+    assert!(ctx.worktree_exists("merged"));
+
+    ctx.xlaude(&["gc"])
+        .env("XLAUDE_YES", "1")
+        .assert()
+        .success();
+
+    assert!(!ctx.worktree_exists("merged"));
+    assert!(ctx.worktree_exists("fresh"));
+    let state = ctx.read_state();
+    let keys: Vec<&String> = state["worktrees"].as_object().unwrap().keys().collect();
+    assert_eq!(keys, ["test-repo/fresh"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_list_only_queries_forge_for_merge_state() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let ctx = TestContext::new("test-repo");
+    std::process::Command::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/user/test-repo.git",
+        ])
+        .current_dir(&ctx.repo_dir)
+        .output()
+        .unwrap();
+    ctx.xlaude(&["create", "feature"]).assert().success();
+    fs::write(
+        ctx.temp_dir
+            .path()
+            .join("test-repo-feature")
+            .join("wip.txt"),
+        "wip",
+    )
+    .unwrap();
+
+    // Fake gh CLI that records every query
+    let bin_dir = ctx.temp_dir.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let args_log = ctx.temp_dir.path().join("gh-args.log");
+    let gh = bin_dir.join("gh");
+    fs::write(
+        &gh,
+        format!(
+            "#!/bin/sh\necho \"$@\" >> {}\necho '[]'\n",
+            args_log.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&gh, fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!(
+        "{}:{}",
+        bin_dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    // Dirtiness and activity only need local git
+    for args in [
+        &["list", "--status", "dirty", "--names-only"][..],
+        &["list", "--sort", "activity", "--json"][..],
+    ] {
+        let output = ctx.xlaude(args).env("PATH", &path).assert().success();
+        let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+        assert!(stdout.contains("feature"));
+    }
+    assert!(
+        !args_log.exists(),
+        "{}",
+        fs::read_to_string(&args_log).unwrap_or_default()
+    );
+
+    ctx.xlaude(&["list", "--status", "merged", "--names-only"])
+        .env("PATH", &path)
+        .assert()
+        .success();
+    assert!(fs::read_to_string(&args_log).unwrap().contains("pr list"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_clean_merged_squashed_pull_request() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let ctx = TestContext::new("test-repo");
+    std::process::Command::new("git")
+        .args([
+            "remote",
+            "add",
+            "origin",
+            "https://github.com/user/test-repo.git",
+        ])
+        .current_dir(&ctx.repo_dir)
+        .output()
+        .unwrap();
+    ctx.xlaude(&["create", "squashed"]).assert().success();
+    ctx.xlaude(&["create", "open-pr"]).assert().success();
+
+    // The commits of a squash-merged branch whose remote branch was deleted
+    // exist nowhere but locally
+    let worktree_path = ctx.temp_dir.path().join("test-repo-squashed");
+    fs::write(worktree_path.join("feature.txt"), "feature").unwrap();
+    for args in [
+        vec!["add", "."],
+        vec!["commit", "--no-gpg-sign", "-m", "Feature"],
+    ] {
+        std::process::Command::new("git")
+            .args(&args)
+            .current_dir(&worktree_path)
+            .output()
+            .unwrap();
+    }
+
+    // Fake gh CLI reporting a merged PR for that branch only
+    let bin_dir = ctx.temp_dir.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let gh = bin_dir.join("gh");
+    fs::write(
+        &gh,
+        "#!/bin/sh
+case \"$*\" in
+*squashed*) echo '[{\"number\":7,\"state\":\"MERGED\",\"url\":\"u\",\"reviewDecision\":\"APPROVED\",\"statusCheckRollup\":[]}]' ;;
+*) echo '[]' ;;
+esac
+",
+    )
+    .unwrap();
+    fs::set_permissions(&gh, fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!(
+        "{}:{}",
+        bin_dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    ctx.xlaude(&["clean", "--merged"])
+        .env("PATH", path)
+        .env("XLAUDE_YES", "1")
+        .assert()
+        .success();
+
+    assert!(!ctx.worktree_exists("squashed"));
+    assert!(ctx.worktree_exists("open-pr"));
+}
+
 // Open command tests (basic, since we can't actually launch Claude)
 #[test]
 fn test_open_specific_worktree() {
@@ -475,6 +1095,109 @@ fn test_open_nonexistent_worktree() {
     assert!(stderr.contains("not found") || stderr.contains("No worktree"));
 }
 
+// Machine-readable output tests
+#[test]
+fn test_output_json_create_and_delete() {
+    let ctx = TestContext::new("test-repo");
+
+    let output = ctx
+        .xlaude(&["--output", "json", "create", "feature"])
+        .assert()
+        .success();
+    let stdout = &output.get_output().stdout;
+    let json: serde_json::Value = serde_json::from_slice(stdout).unwrap();
+    assert_eq!(json["version"], 1);
+    assert_eq!(json["command"], "create");
+    assert_eq!(json["ok"], true);
+    assert_eq!(json["result"]["name"], "feature");
+    assert_eq!(json["result"]["branch"], "feature");
+    assert_eq!(json["result"]["branch_created"], true);
+    assert!(
+        json["result"]["path"]
+            .as_str()
+            .unwrap()
+            .ends_with("test-repo-feature")
+    );
+    assert_eq!(json["actions"][0], "created branch feature");
+
+    // Human-readable progress moves to stderr
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(stderr.contains("Worktree created at"));
+
+    let output = ctx
+        .xlaude(&["delete", "feature", "--output", "json"])
+        .assert()
+        .success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(json["command"], "delete");
+    assert_eq!(json["result"]["deleted"], true);
+    let artifacts = json["result"]["artifacts"].as_array().unwrap();
+    assert!(
+        artifacts
+            .iter()
+            .any(|a| a["artifact"] == "worktree" && a["status"] == "done")
+    );
+    assert!(!ctx.worktree_exists("feature"));
+}
+
+#[test]
+fn test_output_json_error_codes() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "feature"]).assert().success();
+
+    let output = ctx
+        .xlaude(&["--output", "json", "dir", "missing"])
+        .assert()
+        .failure();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(json["ok"], false);
+    assert_eq!(json["error"]["code"], "not_found");
+    assert_eq!(json["error"]["message"], "Worktree 'missing' not found");
+
+    let output = ctx
+        .xlaude(&["--output", "json", "create", "feature"])
+        .assert()
+        .failure();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(json["error"]["code"], "already_exists");
+}
+
+#[test]
+fn test_same_worktree_name_in_two_repos() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "feature"]).assert().success();
+
+    let other_path = ctx.temp_dir.path().join("other-repo-feature");
+    fs::create_dir_all(&other_path).unwrap();
+    let mut state = ctx.read_state();
+    let mut other = state["worktrees"]["test-repo/feature"].clone();
+    other["repo_name"] = json!("other-repo");
+    other["path"] = json!(other_path);
+    state["worktrees"]["other-repo/feature"] = other;
+    ctx.write_state(&state);
+
+    // A bare name shared by two repositories is not resolved at random
+    for args in [
+        &["--output", "json", "dir", "feature"][..],
+        &["--output", "json", "run", "feature", "--prompt", "hi"][..],
+    ] {
+        let output = ctx.xlaude(args).assert().failure();
+        let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+        assert_eq!(json["error"]["code"], "input_required");
+        assert_eq!(
+            json["error"]["message"],
+            "Worktree name 'feature' is ambiguous; use one of other-repo/feature, test-repo/feature"
+        );
+    }
+
+    let output = ctx
+        .xlaude(&["dir", "other-repo/feature"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert_eq!(stdout.trim(), other_path.to_string_lossy());
+}
+
 // Migration test
 #[test]
 fn test_v02_to_v03_migration() {
@@ -921,3 +1644,542 @@ fn test_delete_with_slash_in_branch_name() {
     let key = "test-repo/feature-awesome".to_string();
     assert!(!state["worktrees"].as_object().unwrap().contains_key(&key));
 }
+
+#[test]
+fn test_sessions_lists_transcripts() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "chatty"]).assert().success();
+
+    let output = ctx.xlaude(&["sessions", "chatty"]).assert().success();
+    assert!(
+        String::from_utf8_lossy(&output.get_output().stdout).contains("No Claude sessions found")
+    );
+
+    let worktree_path = ctx
+        .temp_dir
+        .path()
+        .join("test-repo-chatty")
+        .canonicalize()
+        .unwrap();
+    let project_dir = ctx.claude_project_dir(&worktree_path);
+    fs::create_dir_all(&project_dir).unwrap();
+    fs::write(
+        project_dir.join("abc123.jsonl"),
+        [
+            r#"{"type":"user","timestamp":"2024-05-01T10:00:00Z","message":{"content":"Add a parser"}}"#,
+            r#"{"type":"assistant","timestamp":"2024-05-01T10:00:05Z","message":{"id":"m1","model":"claude-sonnet-4","content":[{"type":"tool_use","id":"t1","name":"Edit","input":{}}]}}"#,
+            r#"{"type":"progress","data":{}}"#,
+            r#"{"type":"user","timestamp":"2024-05-01T10:30:00Z","message":{"content":"Now add tests"}}"#,
+        ]
+        .join("\n"),
+    )
+    .unwrap();
+
+    let output = ctx.xlaude(&["sessions", "chatty"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("abc123"));
+    assert!(stdout.contains("2 user, 1 assistant"));
+    assert!(stdout.contains("Edit ×1"));
+    assert!(stdout.contains("Add a parser"));
+
+    let output = ctx
+        .xlaude(&["--output", "json", "sessions", "chatty"])
+        .assert()
+        .success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let session = &json["result"]["sessions"][0];
+    assert_eq!(session["id"], "abc123");
+    assert_eq!(session["user_messages"], 2);
+    assert_eq!(session["tools"]["Edit"], 1);
+    assert_eq!(session["models"][0], "claude-sonnet-4");
+    assert_eq!(session["ended_at"], "2024-05-01T10:30:00Z");
+}
+
+#[test]
+fn test_usage_report() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "alpha"]).assert().success();
+    ctx.xlaude(&["create", "beta"]).assert().success();
+
+    for (name, model, output_tokens) in [
+        ("alpha", "claude-sonnet-4-20250514", 1_000_000),
+        ("beta", "some-local-model", 2_000),
+    ] {
+        let worktree_path = ctx
+            .temp_dir
+            .path()
+            .join(format!("test-repo-{name}"))
+            .canonicalize()
+            .unwrap();
+        let project_dir = ctx.claude_project_dir(&worktree_path);
+        fs::create_dir_all(&project_dir).unwrap();
+        fs::write(
+            project_dir.join("session.jsonl"),
+            [
+                r#"{"type":"user","timestamp":"2024-05-01T10:00:00Z","message":{"content":"Go"}}"#.to_string(),
+                format!(
+                    r#"{{"type":"assistant","timestamp":"2024-05-01T10:00:05Z","message":{{"id":"m1","model":"{model}","usage":{{"input_tokens":1000000,"output_tokens":{output_tokens}}}}}}}"#
+                ),
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+    }
+
+    let output = ctx.xlaude(&["usage"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("test-repo/alpha"));
+    assert!(stdout.contains("$18.00"));
+    assert!(stdout.contains("No price configured for some-local-model"));
+
+    let output = ctx
+        .xlaude(&["--output", "json", "usage", "--by", "repo"])
+        .assert()
+        .success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let groups = json["result"]["groups"].as_array().unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0]["key"], "test-repo");
+    assert_eq!(groups[0]["input_tokens"], 2_000_000);
+    assert_eq!(groups[0]["cost_usd"], 18.0);
+    assert_eq!(groups[0]["unpriced_tokens"], 1_002_000);
+    assert_eq!(json["warnings"].as_array().unwrap().len(), 1);
+
+    // Old transcripts fall outside the period
+    let output = ctx
+        .xlaude(&["--output", "json", "usage", "--since", "7d"])
+        .assert()
+        .success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert!(json["result"]["groups"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_transcript_export() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "chatty"]).assert().success();
+
+    let worktree_path = ctx
+        .temp_dir
+        .path()
+        .join("test-repo-chatty")
+        .canonicalize()
+        .unwrap();
+    let project_dir = ctx.claude_project_dir(&worktree_path);
+    fs::create_dir_all(&project_dir).unwrap();
+    let long_output = "x".repeat(50);
+    fs::write(
+        project_dir.join("abc123.jsonl"),
+        [
+            r#"{"type":"user","timestamp":"2024-05-01T10:00:00Z","message":{"content":"Deploy with token=supersecret123"}}"#.to_string(),
+            r#"{"type":"assistant","timestamp":"2024-05-01T10:00:05Z","message":{"id":"m1","content":[{"type":"text","text":"Running the <deploy> script"},{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"./deploy.sh"}}]}}"#.to_string(),
+            format!(
+                r#"{{"type":"user","timestamp":"2024-05-01T10:00:09Z","message":{{"content":[{{"type":"tool_result","tool_use_id":"t1","content":"{long_output}"}}]}}}}"#
+            ),
+        ]
+        .join("\n"),
+    )
+    .unwrap();
+
+    let output = ctx
+        .xlaude(&[
+            "transcript",
+            "chatty",
+            "--max-output",
+            "20",
+            "--redact",
+            "deploy\\.sh",
+        ])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("# Claude session abc123"));
+    assert!(stdout.contains("Deploy with token=[REDACTED]"));
+    assert!(!stdout.contains("supersecret123"));
+    assert!(stdout.contains("<summary>🔧 Bash: ./[REDACTED]</summary>"));
+    assert!(stdout.contains("[truncated 30 characters]"));
+
+    let output = ctx
+        .xlaude(&["transcript", "chatty", "--format", "html"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Running the &lt;deploy&gt; script"));
+    assert!(stdout.contains("<details class=\"tool\">"));
+
+    let output = ctx
+        .xlaude(&[
+            "transcript",
+            "chatty",
+            "--session",
+            "abc",
+            "--format",
+            "json",
+        ])
+        .assert()
+        .success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let entries = json["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[2]["kind"], "tool_call");
+    assert_eq!(entries[2]["result"], long_output);
+
+    ctx.xlaude(&["transcript", "chatty", "--session", "nope"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_search_transcripts() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "auth"]).assert().success();
+
+    let worktree_path = ctx
+        .temp_dir
+        .path()
+        .join("test-repo-auth")
+        .canonicalize()
+        .unwrap();
+    let project_dir = ctx.claude_project_dir(&worktree_path);
+    fs::create_dir_all(&project_dir).unwrap();
+    let transcript = project_dir.join("abc123.jsonl");
+    fs::write(
+        &transcript,
+        concat!(
+            r#"{"type":"user","timestamp":"2024-05-01T10:00:00Z","message":{"content":"Please refactor the Auth middleware"}}"#,
+            "\n",
+            r#"{"type":"assistant","timestamp":"2024-05-01T10:00:05Z","message":{"id":"m1","content":[{"type":"text","text":"I split the auth middleware in two"}]}}"#,
+            "\n",
+        ),
+    )
+    .unwrap();
+
+    let output = ctx
+        .xlaude(&["search", "auth middleware"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("test-repo/auth"));
+    assert!(stdout.contains("Please refactor the Auth middleware"));
+    assert!(stdout.contains("I split the auth middleware in two"));
+    // One cache file per transcript next to the list of cached transcripts
+    assert_eq!(
+        fs::read_dir(ctx.config_dir.join("search-cache"))
+            .unwrap()
+            .count(),
+        2
+    );
+
+    // Appended messages are picked up by the existing cache
+    let mut content = fs::read_to_string(&transcript).unwrap();
+    content.push_str(
+        r#"{"type":"user","timestamp":"2024-05-02T09:00:00Z","message":{"content":"Now add rate limiting"}}"#,
+    );
+    content.push('\n');
+    fs::write(&transcript, content).unwrap();
+
+    let output = ctx
+        .xlaude(&["--output", "json", "search", "-s", "-e", "Auth|rate"])
+        .assert()
+        .success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let matches = json["result"]["matches"].as_array().unwrap();
+    assert_eq!(json["result"]["total"], 2);
+    assert_eq!(matches[0]["snippet"], "Now add rate limiting");
+    assert_eq!(matches[1]["role"], "user");
+    assert_eq!(matches[1]["session"], "abc123");
+
+    let output = ctx
+        .xlaude(&["--output", "json", "search", "auth", "--repo", "other-repo"])
+        .assert()
+        .success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(json["result"]["total"], 0);
+
+    // With --since, transcripts not modified since then are not read
+    #[cfg(unix)]
+    {
+        let recent = chrono::Utc::now().to_rfc3339();
+        let stale = project_dir.join("stale.jsonl");
+        fs::write(
+            &stale,
+            format!(
+                "{}\n",
+                json!({"type": "user", "timestamp": recent, "message": {"content": "Stale auth notes"}})
+            ),
+        )
+        .unwrap();
+        std::process::Command::new("touch")
+            .args(["-t", "202001010000"])
+            .arg(&stale)
+            .status()
+            .unwrap();
+        let search = |args: &[&str]| -> serde_json::Value {
+            let output = ctx.xlaude(args).assert().success();
+            serde_json::from_slice(&output.get_output().stdout).unwrap()
+        };
+        let json = search(&["--output", "json", "search", "Stale", "--since", "1d"]);
+        assert_eq!(json["result"]["total"], 0);
+        let json = search(&["--output", "json", "search", "Stale"]);
+        assert_eq!(json["result"]["total"], 1);
+    }
+
+    // Caches of deleted transcripts are removed
+    fs::remove_file(&transcript).unwrap();
+    #[cfg(unix)]
+    fs::remove_file(project_dir.join("stale.jsonl")).unwrap();
+    ctx.xlaude(&["search", "auth"]).assert().success();
+    let cached: Vec<_> = fs::read_dir(ctx.config_dir.join("search-cache"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(cached, ["transcripts.json"]);
+
+    ctx.xlaude(&["search", "-e", "("]).assert().failure();
+}
+
+#[test]
+fn test_sessions_of_other_tools() {
+    use sha2::{Digest, Sha256};
+
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "multi"]).assert().success();
+    let worktree_path = ctx
+        .temp_dir
+        .path()
+        .join("test-repo-multi")
+        .canonicalize()
+        .unwrap();
+
+    // OpenCode: session, user message and text part files
+    let storage = ctx.temp_dir.path().join(".local/share/opencode/storage");
+    fs::create_dir_all(storage.join("session/proj")).unwrap();
+    fs::write(
+        storage.join("session/proj/ses_1.json"),
+        serde_json::json!({
+            "id": "ses_1",
+            "directory": worktree_path,
+            "title": "Session title",
+            "time": {"created": 1_714_557_600_000_i64, "updated": 1_714_557_900_000_i64},
+        })
+        .to_string(),
+    )
+    .unwrap();
+    fs::create_dir_all(storage.join("message/ses_1")).unwrap();
+    fs::write(
+        storage.join("message/ses_1/msg_1.json"),
+        r#"{"id":"msg_1","role":"user","time":{"created":1714557600000}}"#,
+    )
+    .unwrap();
+    fs::create_dir_all(storage.join("part/msg_1")).unwrap();
+    fs::write(
+        storage.join("part/msg_1/prt_1.json"),
+        r#"{"type":"text","text":"Port the parser to nom"}"#,
+    )
+    .unwrap();
+
+    // Qwen Code: prompt log keyed by the hash of the project path
+    let hash = format!(
+        "{:x}",
+        Sha256::digest(worktree_path.to_string_lossy().as_bytes())
+    );
+    let qwen_dir = ctx.temp_dir.path().join(".qwen/tmp").join(hash);
+    fs::create_dir_all(&qwen_dir).unwrap();
+    fs::write(
+        qwen_dir.join("logs.json"),
+        r#"[{"sessionId":"q1","messageId":0,"type":"user","message":"Write the changelog","timestamp":"2024-05-02T10:00:00.000Z"}]"#,
+    )
+    .unwrap();
+
+    let output = ctx
+        .xlaude(&["list", "--json"])
+        .env("XDG_DATA_HOME", ctx.temp_dir.path().join(".local/share"))
+        .assert()
+        .success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let sessions = json["worktrees"][0]["sessions"].as_array().unwrap();
+    assert_eq!(sessions.len(), 2);
+    // Most recent first
+    assert_eq!(sessions[0]["tool"], "Qwen Code");
+    assert_eq!(sessions[0]["last_user_message"], "Write the changelog");
+    assert_eq!(sessions[1]["tool"], "OpenCode");
+    assert_eq!(sessions[1]["last_user_message"], "Port the parser to nom");
+
+    let output = ctx
+        .xlaude(&["complete-worktrees", "--format=detailed"])
+        .env("XDG_DATA_HOME", ctx.temp_dir.path().join(".local/share"))
+        .assert()
+        .success();
+    assert!(String::from_utf8_lossy(&output.get_output().stdout).contains("multi\ttest-repo"));
+    assert!(String::from_utf8_lossy(&output.get_output().stdout).contains("\t2 sessions"));
+}
+
+#[test]
+fn test_claude_project_dir_resolution() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "feat_x.y"]).assert().success();
+    let worktree_path = ctx
+        .temp_dir
+        .path()
+        .join("test-repo-feat_x.y")
+        .canonicalize()
+        .unwrap();
+    let transcript = format!(
+        "{}\n",
+        json!({
+            "type": "user",
+            "timestamp": "2024-05-01T10:00:00Z",
+            "cwd": worktree_path,
+            "message": {"content": "hello from a custom config dir"},
+        })
+    );
+    let session_count = |cmd: &mut Command| {
+        let output = cmd.assert().success();
+        let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+        json["result"]["sessions"].as_array().unwrap().len()
+    };
+
+    // CLAUDE_CONFIG_DIR replaces ~/.claude
+    let custom = ctx.temp_dir.path().join("claude-home");
+    let encoded: String = worktree_path
+        .to_string_lossy()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let project_dir = custom.join("projects").join(&encoded);
+    fs::create_dir_all(&project_dir).unwrap();
+    fs::write(project_dir.join("s1.jsonl"), &transcript).unwrap();
+    let args = ["--output", "json", "sessions", "feat_x.y"];
+    assert_eq!(session_count(&mut ctx.xlaude(&args)), 0);
+    assert_eq!(
+        session_count(ctx.xlaude(&args).env("CLAUDE_CONFIG_DIR", &custom)),
+        1
+    );
+
+    // So does the xlaude setting
+    let mut state = ctx.read_state();
+    state["claude_config_dir"] = json!(custom);
+    ctx.write_state(&state);
+    assert_eq!(session_count(&mut ctx.xlaude(&args)), 1);
+
+    // A directory encoded some other way is found through the recorded cwd
+    let mut state = ctx.read_state();
+    state.as_object_mut().unwrap().remove("claude_config_dir");
+    ctx.write_state(&state);
+    let renamed = ctx.temp_dir.path().join(".claude/projects/renamed-project");
+    fs::create_dir_all(&renamed).unwrap();
+    fs::write(renamed.join("s2.jsonl"), &transcript).unwrap();
+    assert_eq!(session_count(&mut ctx.xlaude(&args)), 1);
+}
+
+#[test]
+fn test_sessions_relocate_after_move() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "feat"]).assert().success();
+    let root = ctx.temp_dir.path().canonicalize().unwrap();
+    let old_path = root.join("test-repo-feat");
+    let new_path = root.join("moved-feat");
+
+    let project_dir = ctx.claude_project_dir(&old_path);
+    fs::create_dir_all(&project_dir).unwrap();
+    fs::write(
+        project_dir.join("s1.jsonl"),
+        format!(
+            "{}\n",
+            json!({
+                "type": "user",
+                "timestamp": "2024-05-01T10:00:00Z",
+                "message": {"content": "before the move"},
+            })
+        ),
+    )
+    .unwrap();
+
+    std::process::Command::new("git")
+        .args(["worktree", "move"])
+        .arg(&old_path)
+        .arg(&new_path)
+        .current_dir(&ctx.repo_dir)
+        .output()
+        .unwrap();
+
+    let output = ctx
+        .xlaude(&[
+            "--output",
+            "json",
+            "sessions",
+            "relocate",
+            "../test-repo-feat",
+            "feat",
+        ])
+        .assert()
+        .success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(json["result"]["new_path"], json!(new_path));
+    assert_eq!(json["result"]["relocated"]["Claude"], 1);
+    assert!(!project_dir.exists());
+    assert!(ctx.claude_project_dir(&new_path).join("s1.jsonl").exists());
+
+    // The state follows the worktree to its new path
+    let state = ctx.read_state();
+    assert_eq!(
+        state["worktrees"]["test-repo/feat"]["path"],
+        json!(new_path)
+    );
+
+    let output = ctx
+        .xlaude(&["--output", "json", "sessions", "feat"])
+        .assert()
+        .success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(json["result"]["sessions"].as_array().unwrap().len(), 1);
+
+    // Relocating onto the current path is refused
+    ctx.xlaude(&["sessions", "relocate", new_path.to_str().unwrap(), "feat"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_doctor() {
+    let ctx = TestContext::new("test-repo");
+    ctx.xlaude(&["create", "feature"]).assert().success();
+
+    // A worktree whose directory was removed behind xlaude's back
+    let mut state = ctx.read_state();
+    state["worktrees"]["test-repo/gone"] = state["worktrees"]["test-repo/feature"].clone();
+    state["worktrees"]["test-repo/gone"]["name"] = json!("gone");
+    state["worktrees"]["test-repo/gone"]["path"] = json!(ctx.temp_dir.path().join("gone"));
+    ctx.write_state(&state);
+
+    let output = ctx
+        .xlaude(&["--output", "json", "doctor"])
+        .env("XLAUDE_OPENCODE_CMD", "no-such-opencode")
+        .assert()
+        .failure();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(json["error"]["code"], "invalid_state");
+    let checks = json["result"]["checks"].as_array().unwrap();
+    let check = |name: &str| {
+        checks
+            .iter()
+            .find(|check| check["name"] == name)
+            .unwrap_or_else(|| panic!("no check named {name}"))
+    };
+
+    assert_eq!(check("git")["level"], "ok");
+    assert_eq!(check("worktrees")["level"], "ok");
+
+    let opencode = check("OpenCode (opencode)");
+    assert_eq!(opencode["level"], "error");
+    assert_eq!(opencode["fix"], "Fix or unset XLAUDE_OPENCODE_CMD");
+
+    let gone = check("worktree test-repo/gone");
+    assert_eq!(gone["level"], "warning");
+    assert_eq!(gone["section"], "Orphaned artifacts");
+    assert!(
+        gone["fix"].as_str().unwrap().contains("xlaude clean"),
+        "{gone}"
+    );
+    assert_eq!(json["result"]["errors"], 1);
+}